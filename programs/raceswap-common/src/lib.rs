@@ -0,0 +1,213 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_lang::system_program;
+
+pub mod validation;
+
+pub const WEIGHT_DENOMINATOR: u32 = 10_000;
+pub const MAX_FEE_RECIPIENTS: usize = 4;
+/// Upper bound on forwarded Jupiter instruction data. Real routes are a few
+/// hundred bytes; anything larger only burns compute.
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 1_024;
+
+/// One recipient of the authority-configured SOL fee split and its weight in
+/// bps of the fee.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeShare {
+    pub recipient: Pubkey,
+    pub weight: u16,
+}
+
+impl FeeShare {
+    pub const LEN: usize = 32 + 2;
+    /// Space for a `Vec<FeeShare>` of up to `MAX_FEE_RECIPIENTS` in a config account
+    pub const VEC_LEN: usize = 4 + MAX_FEE_RECIPIENTS * Self::LEN;
+}
+
+/// Checks a fee split before the config authority stores it: empty (the whole
+/// fee goes to the treasury) or at most 4 non-zero weights summing to 10_000.
+pub fn validate_fee_shares(shares: &[FeeShare]) -> Result<()> {
+    if shares.is_empty() {
+        return Ok(());
+    }
+    require!(
+        shares.len() <= MAX_FEE_RECIPIENTS,
+        FeeSplitError::InvalidFeeWeights
+    );
+    require!(
+        shares.iter().all(|share| share.weight > 0),
+        FeeSplitError::InvalidFeeWeights
+    );
+    require!(
+        shares.iter().map(|share| share.weight as u32).sum::<u32>() == WEIGHT_DENOMINATOR,
+        FeeSplitError::InvalidFeeWeights
+    );
+    Ok(())
+}
+
+/// Pays `total_fee` lamports from `payer` to the treasury.
+///
+/// `shares` is the split stored in the config PDA, never one chosen by the
+/// caller. With no shares the whole fee goes to `treasury`. Otherwise the fee
+/// is split across `recipients`, which must be the configured recipients in
+/// order, with the treasury first so the protocol always takes a non-zero
+/// share. The last recipient receives the rounding remainder so no lamports
+/// are lost.
+pub fn distribute_sol_fee<'info>(
+    system_program: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    recipients: &[AccountInfo<'info>],
+    shares: &[FeeShare],
+    total_fee: u64,
+) -> Result<()> {
+    if shares.is_empty() {
+        transfer_lamports(system_program, payer, treasury, total_fee)?;
+        return require_rent_exempt(treasury);
+    }
+
+    validate_fee_shares(shares)?;
+    require!(
+        recipients.len() == shares.len(),
+        FeeSplitError::FeeRecipientMismatch
+    );
+    require_keys_eq!(
+        shares[0].recipient,
+        treasury.key(),
+        FeeSplitError::FeeRecipientMismatch
+    );
+
+    let mut remaining = total_fee;
+    for (i, (recipient, share)) in recipients.iter().zip(shares.iter()).enumerate() {
+        require_keys_eq!(
+            recipient.key(),
+            share.recipient,
+            FeeSplitError::FeeRecipientMismatch
+        );
+        let amount = if i == shares.len() - 1 {
+            remaining
+        } else {
+            ((total_fee as u128) * (share.weight as u128) / WEIGHT_DENOMINATOR as u128) as u64
+        };
+        remaining = remaining
+            .checked_sub(amount)
+            .ok_or(FeeSplitError::InvalidFeeWeights)?;
+        transfer_lamports(system_program, payer, recipient, amount)?;
+        msg!("Fee share paid: {} lamports to {}", amount, recipient.key());
+    }
+
+    require_rent_exempt(treasury)
+}
+
+/// Rejects a fee that leaves `account` funded but below the rent-exempt
+/// minimum, which would strand the lamports in an account that can't be
+/// closed. An untouched empty account passes.
+pub fn require_rent_exempt(account: &AccountInfo) -> Result<()> {
+    let lamports = account.lamports();
+    if lamports == 0 {
+        return Ok(());
+    }
+    let minimum = Rent::get()?.minimum_balance(account.data_len());
+    if lamports < minimum {
+        msg!(
+            "{} holds {} lamports, below the rent-exempt minimum {}",
+            account.key(),
+            lamports,
+            minimum
+        );
+        return err!(FeeSplitError::TreasuryNotRentExempt);
+    }
+    Ok(())
+}
+
+/// Splits `remaining_accounts` into the leading fee recipients and the accounts
+/// that follow them.
+pub fn split_fee_recipients<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    recipient_count: usize,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    require!(
+        remaining_accounts.len() >= recipient_count,
+        FeeSplitError::FeeRecipientMismatch
+    );
+    Ok(remaining_accounts.split_at(recipient_count))
+}
+
+/// How the treasury fee is collected by `execute_swap`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeMode {
+    /// Lamports from the user's wallet to the treasury
+    #[default]
+    Sol,
+    /// Input tokens from the user's input account to the treasury's token
+    /// account; the Jupiter route must be built for `amount - fee`
+    InputToken,
+}
+
+/// Status every swap variant writes to return data on success. A reverted
+/// swap leaves no return data, so CPI callers treat its absence as failure.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStatus {
+    pub success: bool,
+    pub main_out: u64,
+    pub fee: u64,
+}
+
+impl SwapStatus {
+    pub fn ok(main_out: u64, fee: u64) -> Self {
+        Self {
+            success: true,
+            main_out,
+            fee,
+        }
+    }
+
+    /// Writes the status as the instruction's return data.
+    pub fn set(&self) -> Result<()> {
+        set_return_data(&self.try_to_vec()?);
+        Ok(())
+    }
+}
+
+/// Referrer's cut of the treasury fee: `referrer_fee_bps` of the swap amount,
+/// capped at the treasury fee rate so the protocol never pays out more than it takes.
+pub fn referrer_fee(amount: u64, referrer_fee_bps: u16, treasury_fee_bps: u16) -> Result<u64> {
+    require!(
+        referrer_fee_bps <= treasury_fee_bps,
+        FeeSplitError::ReferrerFeeTooHigh
+    );
+    Ok(((amount as u128) * (referrer_fee_bps as u128) / WEIGHT_DENOMINATOR as u128) as u64)
+}
+
+pub fn transfer_lamports<'info>(
+    system_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    if lamports == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ),
+        lamports,
+    )
+}
+
+#[error_code(offset = 7000)]
+pub enum FeeSplitError {
+    #[msg("Fee split must have at most 4 non-zero weights summing to 10_000")]
+    InvalidFeeWeights,
+    #[msg("Fee recipients do not match the configured split or treasury")]
+    FeeRecipientMismatch,
+    #[msg("Referrer fee bps exceeds the treasury fee bps")]
+    ReferrerFeeTooHigh,
+    #[msg("Fee would leave the treasury below the rent-exempt minimum")]
+    TreasuryNotRentExempt,
+}
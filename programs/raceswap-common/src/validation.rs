@@ -0,0 +1,208 @@
+//! Checks every `execute_swap` variant runs, kept in one place so a variant
+//! cannot quietly fall behind the others.
+
+use anchor_lang::prelude::*;
+
+use crate::MAX_INSTRUCTION_DATA_LEN;
+
+/// Jupiter v6 aggregator on mainnet.
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Rejects a zero swap amount.
+pub fn check_amount(amount: u64) -> Result<()> {
+    require!(amount > 0, ValidationError::InvalidAmount);
+    Ok(())
+}
+
+/// Rejects forwarded Jupiter data longer than `MAX_INSTRUCTION_DATA_LEN`.
+pub fn check_instruction_data(data: &[u8]) -> Result<()> {
+    require!(
+        data.len() <= MAX_INSTRUCTION_DATA_LEN,
+        ValidationError::InstructionDataTooLarge
+    );
+    Ok(())
+}
+
+/// Rejects the swap once `deadline_unix_ts` has passed; `None` never expires.
+pub fn check_deadline(deadline_unix_ts: Option<i64>) -> Result<()> {
+    if let Some(deadline_unix_ts) = deadline_unix_ts {
+        require!(
+            Clock::get()?.unix_timestamp <= deadline_unix_ts,
+            ValidationError::DeadlineExceeded
+        );
+    }
+    Ok(())
+}
+
+/// Returns the filled amount (the destination balance delta), rejecting a
+/// balance that went down or a fill below `min_out`.
+pub fn check_min_out(before: u64, after: u64, min_out: u64) -> Result<u64> {
+    let filled = after
+        .checked_sub(before)
+        .ok_or(ValidationError::BelowMinOut)?;
+    msg!("Received {} (min_out {})", filled, min_out);
+    require!(filled >= min_out, ValidationError::BelowMinOut);
+    Ok(filled)
+}
+
+/// `fee_bps` of `amount`, rounded down.
+pub fn compute_treasury_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(ValidationError::MathOverflow)?
+        / BPS_DENOMINATOR;
+    u64::try_from(fee).map_err(|_| ValidationError::MathOverflow.into())
+}
+
+/// SOL fees must land in a plain system account, never a token account.
+pub fn validate_treasury_account(treasury: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *treasury.owner,
+        anchor_lang::system_program::ID,
+        ValidationError::InvalidTreasuryAccount
+    );
+    Ok(())
+}
+
+/// The CPI target must be the expected Jupiter program and executable.
+pub fn validate_jupiter_program(program: &AccountInfo, expected: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        program.key(),
+        *expected,
+        ValidationError::InvalidJupiterProgram
+    );
+    require!(program.executable, ValidationError::InvalidJupiterProgram);
+    Ok(())
+}
+
+#[error_code(offset = 7100)]
+pub enum ValidationError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Jupiter instruction data exceeds MAX_INSTRUCTION_DATA_LEN")]
+    InstructionDataTooLarge,
+    #[msg("Swap deadline has passed")]
+    DeadlineExceeded,
+    #[msg("Output received is below min_out")]
+    BelowMinOut,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Treasury must be a system-owned account")]
+    InvalidTreasuryAccount,
+    #[msg("Jupiter program is not the expected executable program")]
+    InvalidJupiterProgram,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `check` against an account owned by `owner`.
+    fn with_account<T>(
+        key: Pubkey,
+        owner: Pubkey,
+        executable: bool,
+        check: impl FnOnce(&AccountInfo) -> T,
+    ) -> T {
+        let mut lamports = 1u64;
+        let mut data = [0u8; 0];
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            executable,
+            0,
+        );
+        check(&info)
+    }
+
+    #[test]
+    fn zero_amount_is_rejected() {
+        assert_eq!(
+            check_amount(0).unwrap_err(),
+            error!(ValidationError::InvalidAmount)
+        );
+        assert!(check_amount(1).is_ok());
+    }
+
+    #[test]
+    fn instruction_data_is_capped() {
+        assert!(check_instruction_data(&[0; MAX_INSTRUCTION_DATA_LEN]).is_ok());
+        assert_eq!(
+            check_instruction_data(&[0; MAX_INSTRUCTION_DATA_LEN + 1]).unwrap_err(),
+            error!(ValidationError::InstructionDataTooLarge)
+        );
+    }
+
+    #[test]
+    fn missing_deadline_never_expires() {
+        assert!(check_deadline(None).is_ok());
+    }
+
+    #[test]
+    fn filled_amount_is_the_delta_and_must_reach_min_out() {
+        assert_eq!(check_min_out(1_000, 6_000, 5_000).unwrap(), 5_000);
+        assert_eq!(
+            check_min_out(1_000, 5_999, 5_000).unwrap_err(),
+            error!(ValidationError::BelowMinOut)
+        );
+        // A balance that went down is never a fill, even with no floor
+        assert_eq!(
+            check_min_out(1_000, 999, 0).unwrap_err(),
+            error!(ValidationError::BelowMinOut)
+        );
+    }
+
+    #[test]
+    fn treasury_fee_rounds_down() {
+        assert_eq!(compute_treasury_fee(1_000_000, 20).unwrap(), 2_000);
+        assert_eq!(compute_treasury_fee(1_234_567, 7).unwrap(), 864);
+        assert_eq!(compute_treasury_fee(499, 20).unwrap(), 0);
+        assert_eq!(compute_treasury_fee(u64::MAX, 10_000).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn treasury_fee_past_u64_overflows() {
+        assert_eq!(
+            compute_treasury_fee(u64::MAX, 10_001).unwrap_err(),
+            error!(ValidationError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn treasury_must_be_system_owned() {
+        let key = Pubkey::new_unique();
+        with_account(key, anchor_lang::system_program::ID, false, |info| {
+            assert!(validate_treasury_account(info).is_ok());
+        });
+        with_account(key, Pubkey::new_unique(), false, |info| {
+            assert_eq!(
+                validate_treasury_account(info).unwrap_err(),
+                error!(ValidationError::InvalidTreasuryAccount)
+            );
+        });
+    }
+
+    #[test]
+    fn jupiter_must_be_the_expected_executable() {
+        let loader = Pubkey::new_unique();
+        with_account(JUPITER_PROGRAM_ID, loader, true, |info| {
+            assert!(validate_jupiter_program(info, &JUPITER_PROGRAM_ID).is_ok());
+            assert_eq!(
+                validate_jupiter_program(info, &Pubkey::new_unique()).unwrap_err(),
+                error!(ValidationError::InvalidJupiterProgram)
+            );
+        });
+        with_account(JUPITER_PROGRAM_ID, loader, false, |info| {
+            assert_eq!(
+                validate_jupiter_program(info, &JUPITER_PROGRAM_ID).unwrap_err(),
+                error!(ValidationError::InvalidJupiterProgram)
+            );
+        });
+    }
+}
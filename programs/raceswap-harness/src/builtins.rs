@@ -0,0 +1,402 @@
+//! Host implementations of the system, SPL Token / Token-2022 and associated
+//! token programs, covering the instructions the RACESwap programs invoke.
+
+use crate::{current_clock, replace_data};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    program_option::COption,
+    program_pack::Pack,
+    program_utils::limited_deserialize,
+    system_instruction::{SystemError, SystemInstruction},
+};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
+use anchor_spl::token_2022::spl_token_2022::{
+    error::TokenError,
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    instruction::{AuthorityType, TokenInstruction},
+    state::{Account as TokenState, AccountState, Mint as MintState},
+};
+use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+
+fn account<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    index: usize,
+) -> core::result::Result<&'b AccountInfo<'a>, ProgramError> {
+    accounts
+        .get(index)
+        .ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn require_signer(info: &AccountInfo) -> ProgramResult {
+    if info.is_signer {
+        Ok(())
+    } else {
+        Err(ProgramError::MissingRequiredSignature)
+    }
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if from.lamports() < lamports {
+        return Err(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ));
+    }
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+pub(crate) fn process_system<'a>(
+    _program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    data: &[u8],
+) -> ProgramResult {
+    let instruction: SystemInstruction =
+        limited_deserialize(data, 1_232).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let (from, to) = (account(accounts, 0)?, account(accounts, 1)?);
+            require_signer(from)?;
+            require_signer(to)?;
+            if to.lamports() > 0 || !to.data_is_empty() || *to.owner != system_program::ID {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            move_lamports(from, to, lamports)?;
+            replace_data(to, &vec![0; space as usize])?;
+            to.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Assign { owner } => {
+            let target = account(accounts, 0)?;
+            require_signer(target)?;
+            target.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Transfer { lamports } => {
+            let (from, to) = (account(accounts, 0)?, account(accounts, 1)?);
+            require_signer(from)?;
+            if *from.owner != system_program::ID || !from.data_is_empty() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            move_lamports(from, to, lamports)
+        }
+        SystemInstruction::Allocate { space } => {
+            let target = account(accounts, 0)?;
+            require_signer(target)?;
+            if !target.data_is_empty() || *target.owner != system_program::ID {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            replace_data(target, &vec![0; space as usize])
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn read_token_account(info: &AccountInfo) -> core::result::Result<TokenState, ProgramError> {
+    let data = info.try_borrow_data()?;
+    let state = TokenState::unpack_from_slice(
+        data.get(..TokenState::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )?;
+    if state.state == AccountState::Uninitialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(state)
+}
+
+fn write_token_account(info: &AccountInfo, state: &TokenState) -> ProgramResult {
+    state.pack_into_slice(&mut info.try_borrow_mut_data()?[..TokenState::LEN]);
+    Ok(())
+}
+
+fn read_mint(info: &AccountInfo) -> core::result::Result<MintState, ProgramError> {
+    let data = info.try_borrow_data()?;
+    MintState::unpack_from_slice(
+        data.get(..MintState::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )
+}
+
+/// Token-2022 transfer fee `mint` charges on `amount` in the current epoch.
+fn transfer_fee(mint: &AccountInfo, amount: u64) -> core::result::Result<u64, ProgramError> {
+    if *mint.owner != TOKEN_2022_PROGRAM_ID {
+        return Ok(0);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(current_clock().epoch, amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?,
+        Err(_) => 0,
+    })
+}
+
+/// Checks that `authority` may move `amount` out of `state`, as its owner or
+/// as a delegate (whose allowance is then drawn down).
+fn authorize(state: &mut TokenState, authority: &AccountInfo, amount: u64) -> ProgramResult {
+    if *authority.key == state.owner {
+        return require_signer(authority);
+    }
+    if state.delegate == COption::Some(*authority.key) && state.delegated_amount >= amount {
+        require_signer(authority)?;
+        state.delegated_amount -= amount;
+        if state.delegated_amount == 0 {
+            state.delegate = COption::None;
+        }
+        return Ok(());
+    }
+    Err(TokenError::OwnerMismatch.into())
+}
+
+fn transfer(
+    source: &AccountInfo,
+    mint: Option<(&AccountInfo, u8)>,
+    destination: &AccountInfo,
+    authority: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    let mut from = read_token_account(source)?;
+    if let Some((mint, decimals)) = mint {
+        if *mint.key != from.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
+        if read_mint(mint)?.decimals != decimals {
+            return Err(TokenError::MintDecimalsMismatch.into());
+        }
+    }
+    if from.amount < amount {
+        return Err(TokenError::InsufficientFunds.into());
+    }
+    authorize(&mut from, authority, amount)?;
+    if source.key == destination.key {
+        return write_token_account(source, &from);
+    }
+
+    let mut to = read_token_account(destination)?;
+    if to.mint != from.mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    let fee = match mint {
+        Some((mint, _)) => transfer_fee(mint, amount)?,
+        None => 0,
+    };
+    from.amount -= amount;
+    to.amount += amount - fee;
+    if from.is_native() {
+        move_lamports(source, destination, amount)?;
+    }
+    write_token_account(source, &from)?;
+    write_token_account(destination, &to)
+}
+
+pub(crate) fn process_token<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    data: &[u8],
+) -> ProgramResult {
+    // The first account (source, target or mint) always belongs to this program
+    if account(accounts, 0)?.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    match TokenInstruction::unpack(data)? {
+        #[allow(deprecated)]
+        TokenInstruction::Transfer { amount } => transfer(
+            account(accounts, 0)?,
+            None,
+            account(accounts, 1)?,
+            account(accounts, 2)?,
+            amount,
+        ),
+        TokenInstruction::TransferChecked { amount, decimals } => transfer(
+            account(accounts, 0)?,
+            Some((account(accounts, 1)?, decimals)),
+            account(accounts, 2)?,
+            account(accounts, 3)?,
+            amount,
+        ),
+        TokenInstruction::MintTo { amount } | TokenInstruction::MintToChecked { amount, .. } => {
+            let (mint, destination, authority) = (
+                account(accounts, 0)?,
+                account(accounts, 1)?,
+                account(accounts, 2)?,
+            );
+            let mut mint_state = read_mint(mint)?;
+            if mint_state.mint_authority != COption::Some(*authority.key) {
+                return Err(TokenError::OwnerMismatch.into());
+            }
+            require_signer(authority)?;
+            let mut to = read_token_account(destination)?;
+            mint_state.supply += amount;
+            to.amount += amount;
+            mint_state.pack_into_slice(&mut mint.try_borrow_mut_data()?[..MintState::LEN]);
+            write_token_account(destination, &to)
+        }
+        TokenInstruction::SyncNative => {
+            let target = account(accounts, 0)?;
+            let mut state = read_token_account(target)?;
+            let COption::Some(reserve) = state.is_native else {
+                return Err(TokenError::NonNativeNotSupported.into());
+            };
+            state.amount = target.lamports().saturating_sub(reserve);
+            write_token_account(target, &state)
+        }
+        TokenInstruction::CloseAccount => {
+            let (target, destination, authority) = (
+                account(accounts, 0)?,
+                account(accounts, 1)?,
+                account(accounts, 2)?,
+            );
+            let state = read_token_account(target)?;
+            if !state.is_native() && state.amount != 0 {
+                return Err(TokenError::NonNativeHasBalance.into());
+            }
+            let close_authority = state.close_authority.unwrap_or(state.owner);
+            if *authority.key != close_authority {
+                return Err(TokenError::OwnerMismatch.into());
+            }
+            require_signer(authority)?;
+            move_lamports(target, destination, target.lamports())?;
+            replace_data(target, &[])?;
+            target.assign(&system_program::ID);
+            Ok(())
+        }
+        TokenInstruction::SetAuthority {
+            authority_type,
+            new_authority,
+        } => {
+            let (target, authority) = (account(accounts, 0)?, account(accounts, 1)?);
+            let mut state = read_token_account(target)?;
+            match authority_type {
+                AuthorityType::AccountOwner => {
+                    if *authority.key != state.owner {
+                        return Err(TokenError::OwnerMismatch.into());
+                    }
+                    require_signer(authority)?;
+                    state.owner = new_authority.ok_or(ProgramError::InvalidArgument)?;
+                    state.delegate = COption::None;
+                    state.delegated_amount = 0;
+                }
+                AuthorityType::CloseAccount => {
+                    if *authority.key != state.close_authority.unwrap_or(state.owner) {
+                        return Err(TokenError::OwnerMismatch.into());
+                    }
+                    require_signer(authority)?;
+                    state.close_authority = new_authority;
+                }
+                _ => return Err(ProgramError::InvalidInstructionData),
+            }
+            write_token_account(target, &state)
+        }
+        TokenInstruction::InitializeAccount3 { owner } => {
+            let (target, mint) = (account(accounts, 0)?, account(accounts, 1)?);
+            initialize_token_account(target, mint, owner)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn initialize_token_account(
+    target: &AccountInfo,
+    mint: &AccountInfo,
+    owner: Pubkey,
+) -> ProgramResult {
+    let rent = Rent::default().minimum_balance(target.data_len());
+    let is_native = *mint.key == NATIVE_MINT;
+    let state = TokenState {
+        mint: *mint.key,
+        owner,
+        amount: if is_native {
+            target.lamports().saturating_sub(rent)
+        } else {
+            0
+        },
+        state: AccountState::Initialized,
+        is_native: if is_native {
+            COption::Some(rent)
+        } else {
+            COption::None
+        },
+        ..TokenState::default()
+    };
+    write_token_account(target, &state)
+}
+
+/// `Create` (empty data or `[0]`) and `CreateIdempotent` (`[1]`).
+pub(crate) fn process_associated_token<'a>(
+    _program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    data: &[u8],
+) -> ProgramResult {
+    let idempotent = match data {
+        [] | [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    let payer = account(accounts, 0)?;
+    let associated = account(accounts, 1)?;
+    let wallet = account(accounts, 2)?;
+    let mint = account(accounts, 3)?;
+    let token_program = account(accounts, 5)?;
+
+    let expected =
+        get_associated_token_address_with_program_id(wallet.key, mint.key, token_program.key);
+    if *associated.key != expected {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *associated.owner == *token_program.key && !associated.data_is_empty() {
+        let state = read_token_account(associated)?;
+        return if idempotent && state.owner == *wallet.key && state.mint == *mint.key {
+            Ok(())
+        } else {
+            Err(ProgramError::IllegalOwner)
+        };
+    }
+
+    require_signer(payer)?;
+    let required = Rent::default()
+        .minimum_balance(TokenState::LEN)
+        .saturating_sub(associated.lamports());
+    move_lamports(payer, associated, required)?;
+    replace_data(associated, &[0; TokenState::LEN])?;
+    associated.assign(token_program.key);
+    initialize_token_account(associated, mint, *wallet.key)
+}
+
+/// Removes `amount` from a token account directly, as a mocked program that
+/// owns the route's liquidity would. Native accounts give up the lamports too.
+pub fn debit_tokens(info: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut state = read_token_account(info)?;
+    state.amount = state
+        .amount
+        .checked_sub(amount)
+        .ok_or(ProgramError::from(TokenError::InsufficientFunds))?;
+    if state.is_native() {
+        **info.try_borrow_mut_lamports()? -= amount;
+    }
+    write_token_account(info, &state)
+}
+
+/// Adds `amount` to a token account directly; see [`debit_tokens`].
+pub fn credit_tokens(info: &AccountInfo, amount: u64) -> ProgramResult {
+    let mut state = read_token_account(info)?;
+    state.amount = state
+        .amount
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if state.is_native() {
+        **info.try_borrow_mut_lamports()? += amount;
+    }
+    write_token_account(info, &state)
+}
@@ -0,0 +1,609 @@
+//! Host-side runtime for exercising the RACESwap programs under `cargo test`.
+//!
+//! Programs run natively against accounts held by a [`Ledger`]. Syscalls are
+//! served through `solana_program::program_stubs`: the clock and rent
+//! sysvars, logs, events, return data, and CPIs. CPIs reach the built-in
+//! system, SPL Token, Token-2022 and associated token programs, or any program
+//! a test registers (typically a mocked Jupiter). Like the runtime, a failed
+//! transaction leaves every account as it was.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    instruction::Instruction,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    sysvar::{self, instructions::BorrowedAccountMeta, instructions::BorrowedInstruction},
+};
+use anchor_lang::{system_program, AccountDeserialize, Event};
+use anchor_spl::associated_token::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensionsMut,
+        ExtensionType, StateWithExtensionsMut,
+    },
+    state::{Account as TokenState, AccountState, Mint as MintState},
+};
+use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::sync::Once;
+
+mod builtins;
+
+pub use builtins::{credit_tokens, debit_tokens};
+
+/// A program's entrypoint, as generated by `#[program]` or written by a mock.
+pub type Processor = Rc<dyn for<'a> Fn(&Pubkey, &'a [AccountInfo<'a>], &[u8]) -> ProgramResult>;
+
+/// Default slot and timestamp of a fresh ledger's clock.
+pub const GENESIS_SLOT: u64 = 1_000;
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+
+struct Runtime {
+    programs: HashMap<Pubkey, Processor>,
+    clock: Clock,
+    /// Programs currently executing, outermost first
+    call_stack: Vec<Pubkey>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    events: Vec<Vec<u8>>,
+    logs: Vec<String>,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self {
+            programs: HashMap::new(),
+            clock: Clock {
+                slot: GENESIS_SLOT,
+                unix_timestamp: GENESIS_TIMESTAMP,
+                ..Clock::default()
+            },
+            call_stack: Vec::new(),
+            return_data: None,
+            events: Vec::new(),
+            logs: Vec::new(),
+        }
+    }
+}
+
+thread_local! {
+    // Each test runs on its own thread, so each gets its own runtime
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+fn with_runtime<R>(f: impl FnOnce(&mut Runtime) -> R) -> R {
+    RUNTIME.with(|runtime| f(&mut runtime.borrow_mut()))
+}
+
+pub(crate) fn current_clock() -> Clock {
+    with_runtime(|runtime| runtime.clock.clone())
+}
+
+struct HostStubs;
+
+impl SyscallStubs for HostStubs {
+    fn sol_log(&self, message: &str) {
+        println!("{message}");
+        with_runtime(|runtime| runtime.logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        with_runtime(|runtime| runtime.events.push(fields.concat()));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller =
+            with_runtime(|runtime| *runtime.call_stack.last().expect("CPI outside a program"));
+        // Callee privileges follow the runtime: a signer must have signed the
+        // caller or be a PDA of the caller signed for with `signers_seeds`, and
+        // writability can only be narrowed
+        let mut callee = Vec::with_capacity(instruction.accounts.len());
+        for meta in instruction.accounts.iter() {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let pda_signed = signers_seeds.iter().any(|seeds| {
+                Pubkey::create_program_address(seeds, &caller)
+                    .is_ok_and(|address| address == meta.pubkey)
+            });
+            if meta.is_signer && !info.is_signer && !pda_signed {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mut info = info.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            callee.push(info);
+        }
+
+        run_program(
+            &instruction.program_id,
+            Box::leak(callee.into_boxed_slice()),
+            &instruction.data,
+        )
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Clock) = current_clock() };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_runtime(|runtime| runtime.return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        with_runtime(|runtime| {
+            let program_id = *runtime
+                .call_stack
+                .last()
+                .expect("return data outside a program");
+            runtime.return_data = Some((program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        with_runtime(|runtime| runtime.call_stack.len() as u64)
+    }
+}
+
+/// Runs `program_id` on `accounts`. Return data is cleared on entry, as each
+/// invocation starts without any.
+fn run_program<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    data: &[u8],
+) -> ProgramResult {
+    let processor = with_runtime(|runtime| {
+        runtime.call_stack.push(*program_id);
+        runtime.return_data = None;
+        runtime.programs.get(program_id).cloned()
+    });
+    let result = match processor {
+        Some(processor) => processor(program_id, accounts, data),
+        None => Err(ProgramError::IncorrectProgramId),
+    };
+    with_runtime(|runtime| runtime.call_stack.pop());
+    result
+}
+
+/// Leaks a data buffer laid out like the runtime's serialized input: the
+/// length sits in the 8 bytes before the data and there is headroom after it,
+/// so `AccountInfo::realloc` works in place.
+pub(crate) fn leak_data(bytes: &[u8]) -> &'static mut [u8] {
+    let mut buffer = vec![0u8; 8 + bytes.len() + MAX_PERMITTED_DATA_INCREASE];
+    buffer[..8].copy_from_slice(&(bytes.len() as u64).to_le_bytes());
+    buffer[8..8 + bytes.len()].copy_from_slice(bytes);
+    let buffer = Box::leak(buffer.into_boxed_slice());
+    &mut buffer[8..8 + bytes.len()]
+}
+
+/// Replaces an account's data, e.g. when it is allocated or closed.
+pub(crate) fn replace_data(info: &AccountInfo, bytes: &[u8]) -> ProgramResult {
+    *info.try_borrow_mut_data()? = leak_data(bytes);
+    Ok(())
+}
+
+struct Snapshot {
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+}
+
+/// Accounts of a simulated cluster and the entrypoint to run transactions.
+pub struct Ledger {
+    accounts: BTreeMap<Pubkey, AccountInfo<'static>>,
+}
+
+impl Ledger {
+    /// A ledger with `program_id` deployed, alongside the system, token and
+    /// associated token programs and the instructions sysvar.
+    pub fn new<F>(program_id: Pubkey, entry: F) -> Self
+    where
+        F: for<'a> Fn(&Pubkey, &'a [AccountInfo<'a>], &[u8]) -> ProgramResult + 'static,
+    {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(HostStubs));
+        });
+        with_runtime(|runtime| *runtime = Runtime::default());
+
+        let mut ledger = Self {
+            accounts: BTreeMap::new(),
+        };
+        ledger.add_program(system_program::ID, Rc::new(builtins::process_system));
+        ledger.add_program(TOKEN_PROGRAM_ID, Rc::new(builtins::process_token));
+        ledger.add_program(TOKEN_2022_PROGRAM_ID, Rc::new(builtins::process_token));
+        ledger.add_program(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            Rc::new(builtins::process_associated_token),
+        );
+        ledger.add_program(program_id, Rc::new(entry));
+        ledger.set_account(sysvar::instructions::ID, 1, Vec::new(), sysvar::ID);
+        ledger
+    }
+
+    /// Deploys `processor` at `program_id`, e.g. a mocked Jupiter.
+    pub fn add_program(&mut self, program_id: Pubkey, processor: Processor) {
+        with_runtime(|runtime| runtime.programs.insert(program_id, processor));
+        let loader = anchor_lang::solana_program::bpf_loader_upgradeable::ID;
+        self.set_account(program_id, 1, Vec::new(), loader);
+        if let Some(info) = self.accounts.get_mut(&program_id) {
+            info.executable = true;
+        }
+    }
+
+    /// Gives a deployed program an upgrade authority: writes its program
+    /// account and ProgramData account in the upgradeable loader's bincode
+    /// layout. Returns the ProgramData address.
+    pub fn set_upgrade_authority(&mut self, program_id: Pubkey, authority: Option<Pubkey>) -> Pubkey {
+        let loader = anchor_lang::solana_program::bpf_loader_upgradeable::ID;
+        let program_data = Pubkey::find_program_address(&[program_id.as_ref()], &loader).0;
+
+        // UpgradeableLoaderState::Program { programdata_address }
+        let mut program = 2u32.to_le_bytes().to_vec();
+        program.extend_from_slice(program_data.as_ref());
+        self.set_account(program_id, 1, program, loader);
+
+        // UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        match authority {
+            Some(authority) => {
+                data.push(1);
+                data.extend_from_slice(authority.as_ref());
+            }
+            None => data.push(0),
+        }
+        self.set_account(program_data, 1, data, loader);
+        program_data
+    }
+
+    /// Creates or overwrites an account.
+    pub fn set_account(&mut self, key: Pubkey, lamports: u64, data: Vec<u8>, owner: Pubkey) {
+        if let Some(info) = self.accounts.get(&key) {
+            **info.lamports.borrow_mut() = lamports;
+            replace_data(info, &data).expect("account data borrowed");
+            info.assign(&owner);
+            return;
+        }
+        let info = AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(lamports)),
+            leak_data(&data),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        );
+        self.accounts.insert(key, info);
+    }
+
+    /// Adds `lamports` to a (new or existing) system account.
+    pub fn fund(&mut self, key: Pubkey, lamports: u64) {
+        let balance = self.lamports(&key);
+        let (data, owner) = match self.accounts.get(&key) {
+            Some(info) => (info.data.borrow().to_vec(), *info.owner),
+            None => (Vec::new(), system_program::ID),
+        };
+        self.set_account(key, balance + lamports, data, owner);
+    }
+
+    /// Adds an SPL Token mint.
+    pub fn add_mint(&mut self, key: Pubkey, decimals: u8) {
+        self.add_mint_with_program(key, decimals, TOKEN_PROGRAM_ID);
+    }
+
+    pub fn add_mint_with_program(&mut self, key: Pubkey, decimals: u8, token_program: Pubkey) {
+        let mut data = vec![0u8; MintState::LEN];
+        MintState {
+            decimals,
+            is_initialized: true,
+            ..MintState::default()
+        }
+        .pack_into_slice(&mut data);
+        self.set_account(
+            key,
+            Rent::default().minimum_balance(data.len()),
+            data,
+            token_program,
+        );
+    }
+
+    /// Adds a Token-2022 mint charging `fee_bps` (capped at `maximum_fee`) on
+    /// every transfer.
+    pub fn add_transfer_fee_mint(
+        &mut self,
+        key: Pubkey,
+        decimals: u8,
+        fee_bps: u16,
+        maximum_fee: u64,
+    ) {
+        let len = ExtensionType::try_calculate_account_len::<MintState>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .expect("mint length");
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).expect("mint");
+        let config = state
+            .init_extension::<TransferFeeConfig>(true)
+            .expect("transfer fee");
+        for fee in [
+            &mut config.older_transfer_fee,
+            &mut config.newer_transfer_fee,
+        ] {
+            fee.transfer_fee_basis_points = fee_bps.into();
+            fee.maximum_fee = maximum_fee.into();
+        }
+        state.base = MintState {
+            decimals,
+            is_initialized: true,
+            ..MintState::default()
+        };
+        state.pack_base();
+        state.init_account_type().expect("account type");
+        self.set_account(
+            key,
+            Rent::default().minimum_balance(len),
+            data,
+            TOKEN_2022_PROGRAM_ID,
+        );
+    }
+
+    /// Adds a Token-2022 mint whose transfers call `hook_program`.
+    pub fn add_transfer_hook_mint(&mut self, key: Pubkey, decimals: u8, hook_program: Pubkey) {
+        let len =
+            ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferHook])
+                .expect("mint length");
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).expect("mint");
+        let hook = state
+            .init_extension::<TransferHook>(true)
+            .expect("transfer hook");
+        hook.program_id = Some(hook_program).try_into().expect("hook program");
+        state.base = MintState {
+            decimals,
+            is_initialized: true,
+            ..MintState::default()
+        };
+        state.pack_base();
+        state.init_account_type().expect("account type");
+        self.set_account(
+            key,
+            Rent::default().minimum_balance(len),
+            data,
+            TOKEN_2022_PROGRAM_ID,
+        );
+    }
+
+    /// Adds a token account for `mint` (which must already exist) holding
+    /// `amount`. Native-mint accounts carry the matching lamports.
+    pub fn add_token_account(&mut self, key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) {
+        let token_program = self.owner(&mint);
+        let rent = Rent::default().minimum_balance(TokenState::LEN);
+        let is_native = mint == NATIVE_MINT;
+        let mut data = vec![0u8; TokenState::LEN];
+        TokenState {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            is_native: if is_native {
+                Some(rent).into()
+            } else {
+                None.into()
+            },
+            ..TokenState::default()
+        }
+        .pack_into_slice(&mut data);
+        let lamports = if is_native { rent + amount } else { rent };
+        self.set_account(key, lamports, data, token_program);
+    }
+
+    /// Lets `delegate` move up to `amount` out of a token account.
+    pub fn approve(&mut self, key: Pubkey, delegate: Pubkey, amount: u64) {
+        let mut data = self.data(&key);
+        let mut state =
+            TokenState::unpack_from_slice(&data[..TokenState::LEN]).expect("token account");
+        state.delegate = Some(delegate).into();
+        state.delegated_amount = amount;
+        state.pack_into_slice(&mut data[..TokenState::LEN]);
+        let (lamports, owner) = (self.lamports(&key), self.owner(&key));
+        self.set_account(key, lamports, data, owner);
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts.get(key).map_or(0, |info| info.lamports())
+    }
+
+    pub fn owner(&self, key: &Pubkey) -> Pubkey {
+        self.accounts
+            .get(key)
+            .map_or(system_program::ID, |info| *info.owner)
+    }
+
+    pub fn data(&self, key: &Pubkey) -> Vec<u8> {
+        self.accounts
+            .get(key)
+            .map_or_else(Vec::new, |info| info.data.borrow().to_vec())
+    }
+
+    /// Whether the account holds lamports or data.
+    pub fn exists(&self, key: &Pubkey) -> bool {
+        self.lamports(key) > 0 || !self.data(key).is_empty()
+    }
+
+    /// Base state of a token account (of either token program).
+    pub fn token_account(&self, key: &Pubkey) -> TokenState {
+        TokenState::unpack_from_slice(&self.data(key)[..TokenState::LEN]).expect("token account")
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        self.token_account(key).amount
+    }
+
+    /// Deserializes an Anchor account owned by any program.
+    pub fn anchor_account<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        T::try_deserialize(&mut self.data(key).as_slice()).expect("anchor account")
+    }
+
+    pub fn slot(&self) -> u64 {
+        current_clock().slot
+    }
+
+    pub fn set_slot(&mut self, slot: u64) {
+        with_runtime(|runtime| runtime.clock.slot = slot);
+    }
+
+    pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        with_runtime(|runtime| runtime.clock.unix_timestamp = unix_timestamp);
+    }
+
+    /// Runs `instruction` as a single-instruction transaction whose signers
+    /// are the accounts its metas mark as signers. On failure every account
+    /// is restored and no return data is left.
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        self.write_instructions_sysvar(instruction);
+        let accounts: Vec<AccountInfo<'static>> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                if !self.accounts.contains_key(&meta.pubkey) {
+                    self.set_account(meta.pubkey, 0, Vec::new(), system_program::ID);
+                }
+                let mut info = self.accounts[&meta.pubkey].clone();
+                info.is_signer = meta.is_signer;
+                info.is_writable = meta.is_writable;
+                info
+            })
+            .collect();
+
+        let snapshot: Vec<(Pubkey, Snapshot)> = self
+            .accounts
+            .iter()
+            .map(|(key, info)| {
+                let snapshot = Snapshot {
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    owner: *info.owner,
+                };
+                (*key, snapshot)
+            })
+            .collect();
+        with_runtime(|runtime| {
+            runtime.events.clear();
+            runtime.logs.clear();
+        });
+
+        let result = run_program(
+            &instruction.program_id,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction.data,
+        );
+        if result.is_err() {
+            for (key, snapshot) in snapshot {
+                self.set_account(key, snapshot.lamports, snapshot.data, snapshot.owner);
+            }
+            // A failed transaction reports no return data
+            with_runtime(|runtime| runtime.return_data = None);
+        }
+        result
+    }
+
+    /// Events of type `E` emitted by the last transaction, in order.
+    pub fn events<E: Event>(&self) -> Vec<E> {
+        with_runtime(|runtime| {
+            runtime
+                .events
+                .iter()
+                .filter(|data| data.starts_with(&E::DISCRIMINATOR))
+                .map(|data| E::deserialize(&mut &data[8..]).expect("event"))
+                .collect()
+        })
+    }
+
+    /// Return data left by the last transaction, with the program that set it.
+    pub fn return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_runtime(|runtime| runtime.return_data.clone())
+    }
+
+    /// Log lines of the last transaction.
+    pub fn logs(&self) -> Vec<String> {
+        with_runtime(|runtime| runtime.logs.clone())
+    }
+
+    fn write_instructions_sysvar(&mut self, instruction: &Instruction) {
+        let metas: Vec<BorrowedAccountMeta> = instruction
+            .accounts
+            .iter()
+            .map(|meta| BorrowedAccountMeta {
+                pubkey: &meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect();
+        let data = sysvar::instructions::construct_instructions_data(&[BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: metas,
+            data: &instruction.data,
+        }]);
+        self.set_account(sysvar::instructions::ID, 1, data, sysvar::ID);
+    }
+}
+
+/// Mocked Jupiter route for tests. Leg data is `[in_amount, out_amount]` as
+/// little-endian u64s: `in_amount` leaves the first account and `out_amount`
+/// lands in the second, and `out_amount` is returned like Jupiter's `route`.
+pub fn mock_route<'a>(
+    _program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    data: &[u8],
+) -> ProgramResult {
+    let (in_amount, out_amount) = decode_route(data)?;
+    let source = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let destination = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    debit_tokens(source, in_amount)?;
+    credit_tokens(destination, out_amount)?;
+    anchor_lang::solana_program::program::set_return_data(&out_amount.to_le_bytes());
+    Ok(())
+}
+
+/// Leg data for [`mock_route`].
+pub fn route_data(in_amount: u64, out_amount: u64) -> Vec<u8> {
+    [in_amount.to_le_bytes(), out_amount.to_le_bytes()].concat()
+}
+
+pub fn decode_route(data: &[u8]) -> core::result::Result<(u64, u64), ProgramError> {
+    let amount = |range: core::ops::Range<usize>| {
+        data.get(range)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    Ok((amount(0..8)?, amount(8..16)?))
+}
+
+/// Maps an Anchor error code to the `ProgramError` a failed instruction returns.
+pub fn anchor_error(code: impl Into<u32>) -> ProgramError {
+    ProgramError::Custom(code.into())
+}
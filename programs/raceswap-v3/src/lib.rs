@@ -1,4 +1,4 @@
-/**
+/*
  * Raceswap V3 - Index-Based Non-Custodial Swap Architecture
  * 
  * KEY IMPROVEMENT: Uses account INDICES instead of full metadata
@@ -8,7 +8,7 @@
  * Architecture:
  * - User owns all tokens throughout swap (non-custodial)
  * - User signs directly for Jupiter (no PDA conflicts)
- * - Simple 0.2% SOL fee collected via system transfer
+ * - SOL fee (config PDA rate, 0.2% by default) collected via system transfer
  * - Accounts passed as indices into remaining_accounts array
  */
 
@@ -17,9 +17,25 @@ use anchor_lang::solana_program::{
     instruction::Instruction,
     program::invoke,
 };
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use raceswap_common::validation::{
+    check_amount, check_deadline, check_instruction_data, check_min_out, compute_treasury_fee,
+    validate_jupiter_program, validate_treasury_account, JUPITER_PROGRAM_ID,
+};
+use raceswap_common::{
+    distribute_sol_fee, referrer_fee, split_fee_recipients, transfer_lamports, validate_fee_shares,
+    FeeMode, FeeShare, SwapStatus,
+};
 
 declare_id!("Cy63SzwBBCP5ywaByjUrLuUXQ4pXP9nR7e7kdQqp5uLk");
 
+const CONFIG_SEED: &[u8] = b"raceswap-config";
+const DEFAULT_TREASURY_FEE_BPS: u16 = 20;
+const V3_CONFIG_SEED: &[u8] = b"raceswap-v3-config";
+const MAX_BATCH_LEGS: usize = 4;
+
+const DEFAULT_TREASURY: Pubkey = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L");
+
 #[program]
 pub mod raceswap_v3 {
     use super::*;
@@ -32,46 +48,65 @@ pub mod raceswap_v3 {
         msg!("Amount: {} lamports", params.amount);
         msg!("Min out: {}", params.min_out);
         msg!("Jupiter accounts: {} (index+writable pairs)", params.jupiter_account_infos.len());
+        check_amount(params.amount)?;
+        check_instruction_data(&params.jupiter_data)?;
+        check_deadline(params.deadline_unix_ts)?;
 
-        // 1. Collect 0.2% SOL fee to treasury
-        let treasury_fee_lamports = (params.amount as u128)
-            .checked_mul(20)
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap() as u64;
-
-        if treasury_fee_lamports > 0 {
-            anchor_lang::system_program::transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    anchor_lang::system_program::Transfer {
-                        from: ctx.accounts.user.to_account_info(),
-                        to: ctx.accounts.treasury.to_account_info(),
-                    },
-                ),
-                treasury_fee_lamports,
-            )?;
-            msg!("Treasury fee paid: {} lamports", treasury_fee_lamports);
-        }
+        // 1. Size the fee to treasury in SOL or the input token (config rate, 0.2% by default)
+        let fee_bps = treasury_fee_bps(&ctx.accounts.config)?;
+        let total_fee = compute_treasury_fee(params.amount, fee_bps)?;
+
+        let (treasury, jupiter_program) = configured_addresses(&ctx.accounts.v3_config)?;
+        require_keys_eq!(ctx.accounts.treasury.key(), treasury, RaceswapError::InvalidTreasuryAccount);
+        validate_treasury_account(&ctx.accounts.treasury)?;
+        validate_jupiter_program(&ctx.accounts.jupiter_program, &jupiter_program)?;
+
+        // Leading remaining_accounts are the recipients of the config's fee
+        // split (SOL fees only); Jupiter indices are relative to the accounts
+        // that follow them
+        let fee_shares = match params.fee_mode {
+            FeeMode::Sol => fee_shares(&ctx.accounts.config)?,
+            FeeMode::InputToken => Vec::new(),
+        };
+        let (fee_recipients, jupiter_remaining) =
+            split_fee_recipients(ctx.remaining_accounts, fee_shares.len())?;
+
+        // Catch routes truncated during serialization before paying anything;
+        // indices past the remaining accounts fail in resolve_jupiter_accounts
+        require!(
+            params.jupiter_account_infos.len() >= params.min_accounts as usize,
+            RaceswapError::InsufficientAccounts
+        );
+        check_alt_hint(&params.alt_hint, jupiter_remaining)?;
+
+        // Partner front-ends take `referrer_fee_bps` of the amount out of the
+        // treasury fee; only supported for SOL fees
+        let referrer_account = match params.referrer {
+            Some(referrer) => {
+                require!(params.fee_mode == FeeMode::Sol, RaceswapError::InvalidFeeMode);
+                let referrer_account = ctx
+                    .accounts
+                    .referrer
+                    .as_ref()
+                    .ok_or(RaceswapError::InvalidReferrer)?;
+                require_keys_eq!(referrer_account.key(), referrer, RaceswapError::InvalidReferrer);
+                Some(referrer_account)
+            }
+            None => None,
+        };
+        let referrer_fee_lamports = match referrer_account {
+            Some(_) => referrer_fee(params.amount, params.referrer_fee_bps, fee_bps)?,
+            None => 0,
+        };
+        let treasury_fee = total_fee
+            .checked_sub(referrer_fee_lamports)
+            .ok_or(RaceswapError::InvalidReferrer)?;
 
         // 2. Reconstruct Jupiter AccountMeta from account info structs
-        let jupiter_accounts: Vec<AccountMeta> = params.jupiter_account_infos
-            .iter()
-            .map(|info| {
-                let acc_info = &ctx.remaining_accounts[info.index as usize];
-                // CRITICAL: Only use the permissions we actually have!
-                // Ignore Jupiter's desired writable flag - use only what the transaction gave us
-                AccountMeta {
-                    pubkey: *acc_info.key,
-                    is_signer: acc_info.is_signer,
-                    is_writable: acc_info.is_writable,  // Use actual permission only!
-                }
-            })
-            .collect();
+        let jupiter_accounts = resolve_jupiter_accounts(&params.jupiter_account_infos, jupiter_remaining)?;
 
         msg!("Reconstructed {} AccountMetas from indices", jupiter_accounts.len());
 
-        // 3. Execute Jupiter swap via CPI
         let jupiter_ix = Instruction {
             program_id: ctx.accounts.jupiter_program.key(),
             accounts: jupiter_accounts,
@@ -80,32 +115,649 @@ pub mod raceswap_v3 {
 
         // Collect all account infos for the CPI
         let mut account_infos: Vec<AccountInfo<'info>> = vec![ctx.accounts.jupiter_program.to_account_info()];
-        for acc in ctx.remaining_accounts.iter() {
+        for acc in jupiter_remaining.iter() {
             account_infos.push(acc.clone());
         }
 
+        // Everything above is validation only. A dry run stops here, before
+        // the first side effect, so simulation never moves lamports or tokens.
+        if params.dry_run {
+            msg!("Dry run: validated, fee would be {}", total_fee);
+            SwapStatus::ok(0, total_fee).set()?;
+            return Ok(());
+        }
+
+        // 3. Collect the fee. This is the last step before the Jupiter invoke
+        // and there is no early `return Ok(())` between them, so any CPI error
+        // reverts the fee.
+        if let Some(referrer_account) = referrer_account {
+            transfer_lamports(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                &referrer_account.to_account_info(),
+                referrer_fee_lamports,
+            )?;
+            msg!("Referrer fee paid: {} lamports to {}", referrer_fee_lamports, referrer_account.key());
+        }
+
+        let treasury_fee_lamports = match params.fee_mode {
+            FeeMode::Sol => {
+                if treasury_fee > 0 {
+                    distribute_sol_fee(
+                        &ctx.accounts.system_program.to_account_info(),
+                        &ctx.accounts.user.to_account_info(),
+                        &ctx.accounts.treasury.to_account_info(),
+                        fee_recipients,
+                        &fee_shares,
+                        treasury_fee,
+                    )?;
+                    msg!("Treasury fee paid: {} lamports", treasury_fee);
+                }
+                treasury_fee
+            }
+            FeeMode::InputToken => {
+                collect_input_token_fee(ctx.accounts, treasury_fee)?;
+                msg!("Treasury fee paid: {} input tokens", treasury_fee);
+                0  // SwapExecuted.treasury_fee_lamports stays lamports-only
+            }
+        };
+
+        // 4. Execute Jupiter swap via CPI
         msg!("Invoking Jupiter with {} accounts", account_infos.len());
+        let destination_before = ctx.accounts.user_destination.amount;
         invoke(&jupiter_ix, &account_infos)?;
+        ctx.accounts.user_destination.reload()?;
+        let main_out = check_min_out(
+            destination_before,
+            ctx.accounts.user_destination.amount,
+            params.min_out,
+        )?;
 
         msg!("V3 swap completed successfully!");
+        emit!(SwapExecuted {
+            user: ctx.accounts.user.key(),
+            amount: params.amount,
+            min_out: params.min_out,
+            treasury_fee_lamports,
+            jupiter_program: ctx.accounts.jupiter_program.key(),
+            referrer: params.referrer,
+            referrer_fee_lamports,
+        });
+        SwapStatus::ok(main_out, total_fee).set()?;
+        Ok(())
+    }
+
+    /// Runs up to `MAX_BATCH_LEGS` swaps for one consolidated SOL treasury fee
+    /// sized on the summed amounts. `remaining_accounts` is consumed leg by
+    /// leg: each leg's slice is its destination token account followed by the
+    /// accounts its `jupiter_account_infos` index into (highest index + 1).
+    /// Every leg enforces its own `min_out` on its destination's delta.
+    pub fn execute_swap_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSwapBatch<'info>>,
+        params: Vec<ExecuteSwapParams>,
+    ) -> Result<()> {
+        msg!("Raceswap V3: batch of {} swaps", params.len());
+        require!(
+            !params.is_empty() && params.len() <= MAX_BATCH_LEGS,
+            RaceswapError::InvalidBatch
+        );
+        let (treasury, jupiter_program) = configured_addresses(&ctx.accounts.v3_config)?;
+        require_keys_eq!(ctx.accounts.treasury.key(), treasury, RaceswapError::InvalidTreasuryAccount);
+        validate_treasury_account(&ctx.accounts.treasury)?;
+        validate_jupiter_program(&ctx.accounts.jupiter_program, &jupiter_program)?;
+
+        let mut total_amount: u64 = 0;
+        let mut rest = ctx.remaining_accounts;
+        let mut legs = Vec::with_capacity(params.len());
+        for leg in params.iter() {
+            let jupiter_len = check_batch_leg(leg)?;
+            total_amount = total_amount
+                .checked_add(leg.amount)
+                .ok_or(RaceswapError::InvalidBatch)?;
+
+            // A short slice surfaces as IndexOutOfRange when the leg is built
+            let slice_len = (jupiter_len + 1).min(rest.len());
+            require!(slice_len > 0, RaceswapError::InsufficientAccounts);
+            let (slice, tail) = rest.split_at(slice_len);
+            rest = tail;
+            let (destination, jupiter_remaining) = (&slice[0], &slice[1..]);
+
+            let destination_before = destination_amount(destination, &ctx.accounts.user.key())?;
+            let (jupiter_ix, account_infos) =
+                build_jupiter_leg(&ctx.accounts.jupiter_program, leg, jupiter_remaining)?;
+            legs.push((jupiter_ix, account_infos, destination, destination_before));
+        }
+        require!(rest.is_empty(), RaceswapError::InvalidBatch);
+
+        // One fee for the whole batch, collected before the first invoke so
+        // any leg failing reverts it
+        let fee_bps = treasury_fee_bps(&ctx.accounts.config)?;
+        let total_fee = compute_treasury_fee(total_amount, fee_bps)?;
+        if total_fee > 0 {
+            distribute_sol_fee(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.treasury.to_account_info(),
+                &[],
+                &[],
+                total_fee,
+            )?;
+            msg!("Batch treasury fee paid: {} lamports", total_fee);
+        }
+
+        let mut outs = Vec::with_capacity(legs.len());
+        for ((jupiter_ix, account_infos, destination, destination_before), leg) in
+            legs.into_iter().zip(params.iter())
+        {
+            invoke(&jupiter_ix, &account_infos)?;
+            msg!("Leg {}", outs.len());
+            outs.push(check_min_out(
+                destination_before,
+                destination_amount(destination, &ctx.accounts.user.key())?,
+                leg.min_out,
+            )?);
+        }
+
+        emit!(BatchSwapExecuted {
+            user: ctx.accounts.user.key(),
+            total_amount,
+            treasury_fee_lamports: total_fee,
+            jupiter_program: ctx.accounts.jupiter_program.key(),
+            outs,
+        });
+        // Outputs are in different mints, so per-leg amounts are only in the event
+        SwapStatus::ok(0, total_fee).set()?;
+        Ok(())
+    }
+
+    /// Splits one order across two Jupiter routes into the same destination.
+    /// remaining_accounts hold the shared destination token account, then the
+    /// first route's accounts, then the second's (each through its highest
+    /// index). Each leg keeps its own `min_out`; the summed output must also
+    /// reach `min_out`. One SOL fee is charged on the combined amount.
+    pub fn execute_split_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSwapBatch<'info>>,
+        first: ExecuteSwapParams,
+        second: ExecuteSwapParams,
+        min_out: u64,
+    ) -> Result<()> {
+        msg!("Raceswap V3: split swap {} + {}", first.amount, second.amount);
+        let (treasury, jupiter_program) = configured_addresses(&ctx.accounts.v3_config)?;
+        require_keys_eq!(ctx.accounts.treasury.key(), treasury, RaceswapError::InvalidTreasuryAccount);
+        validate_treasury_account(&ctx.accounts.treasury)?;
+        validate_jupiter_program(&ctx.accounts.jupiter_program, &jupiter_program)?;
+
+        let (destination, mut rest) = ctx
+            .remaining_accounts
+            .split_first()
+            .ok_or(RaceswapError::InsufficientAccounts)?;
+        let mut legs = Vec::with_capacity(2);
+        for leg in [&first, &second] {
+            let jupiter_len = check_batch_leg(leg)?.min(rest.len());
+            let (jupiter_remaining, tail) = rest.split_at(jupiter_len);
+            rest = tail;
+            legs.push(build_jupiter_leg(&ctx.accounts.jupiter_program, leg, jupiter_remaining)?);
+        }
+        require!(rest.is_empty(), RaceswapError::InvalidBatch);
+        let total_amount = first
+            .amount
+            .checked_add(second.amount)
+            .ok_or(RaceswapError::InvalidBatch)?;
+
+        let fee_bps = treasury_fee_bps(&ctx.accounts.config)?;
+        let total_fee = compute_treasury_fee(total_amount, fee_bps)?;
+        if total_fee > 0 {
+            distribute_sol_fee(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.treasury.to_account_info(),
+                &[],
+                &[],
+                total_fee,
+            )?;
+            msg!("Split treasury fee paid: {} lamports", total_fee);
+        }
+
+        let user = ctx.accounts.user.key();
+        let start = destination_amount(destination, &user)?;
+        let mut before = start;
+        let mut outs = Vec::with_capacity(2);
+        for ((jupiter_ix, account_infos), leg) in legs.into_iter().zip([&first, &second]) {
+            invoke(&jupiter_ix, &account_infos)?;
+            let after = destination_amount(destination, &user)?;
+            msg!("Route {}", outs.len());
+            outs.push(check_min_out(before, after, leg.min_out)?);
+            before = after;
+        }
+        msg!("Combined");
+        let total_out = check_min_out(start, before, min_out)?;
+
+        emit!(SplitSwapExecuted {
+            user,
+            total_amount,
+            treasury_fee_lamports: total_fee,
+            jupiter_program: ctx.accounts.jupiter_program.key(),
+            outs,
+            total_out,
+        });
+        SwapStatus::ok(total_out, total_fee).set()?;
+        Ok(())
+    }
+
+    /// Returns the exact fee `execute_swap` would charge for `amount` at the
+    /// current config rate, so wallets can preview it before signing.
+    pub fn quote_fee(ctx: Context<QuoteFee>, amount: u64) -> Result<u64> {
+        let fee = compute_treasury_fee(amount, treasury_fee_bps(&ctx.accounts.config)?)?;
+        msg!("Quoted fee: {} for amount {}", fee, amount);
+        Ok(fee)
+    }
+
+    /// One-time creation of the fee config. Only the program's upgrade
+    /// authority can run it, so nobody can front-run the deploy and pick the
+    /// config authority.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, authority: Pubkey, treasury_fee_bps: u16) -> Result<()> {
+        require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+
+        let config = &mut ctx.accounts.config;
+        config.authority = authority;
+        config.treasury_fee_bps = treasury_fee_bps;
+        config.bump = ctx.bumps.config;
+        config.fee_shares = Vec::new();
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            config.authority,
+            RaceswapError::Unauthorized
+        );
+
+        if let Some(new_authority) = params.new_authority {
+            config.authority = new_authority;
+        }
+
+        if let Some(treasury_fee_bps) = params.treasury_fee_bps {
+            require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+            config.treasury_fee_bps = treasury_fee_bps;
+        }
+
+        if let Some(fee_shares) = params.fee_shares {
+            validate_fee_shares(&fee_shares)?;
+            config.fee_shares = fee_shares;
+        }
+
+        msg!("Config updated: treasury_fee_bps={}", config.treasury_fee_bps);
+        Ok(())
+    }
+
+    /// One-time setup of the treasury and Jupiter addresses that the swap
+    /// instructions check in `config-addresses` builds, replacing the
+    /// built-in mainnet ones. Only the program's upgrade authority can run it.
+    pub fn initialize_v3_config(
+        ctx: Context<InitializeV3Config>,
+        treasury: Pubkey,
+        jupiter_program: Pubkey,
+    ) -> Result<()> {
+        let v3_config = &mut ctx.accounts.v3_config;
+        v3_config.treasury = treasury;
+        v3_config.jupiter_program = jupiter_program;
+        v3_config.bump = ctx.bumps.v3_config;
+        msg!("V3 config initialized: treasury={} jupiter={}", treasury, jupiter_program);
         Ok(())
     }
 }
 
+/// Maps index-based account infos onto `remaining_accounts`, rejecting indices
+/// past the end and duplicate indices whose writable flags disagree.
+fn resolve_jupiter_accounts(
+    infos: &[JupiterAccountInfo],
+    remaining: &[AccountInfo],
+) -> Result<Vec<AccountMeta>> {
+    let mut requested_writable: Vec<Option<bool>> = vec![None; remaining.len()];
+    let mut metas = Vec::with_capacity(infos.len());
+
+    for info in infos {
+        let index = info.index as usize;
+        let Some(acc_info) = remaining.get(index) else {
+            msg!("Jupiter account index {} out of range ({} accounts)", index, remaining.len());
+            return err!(RaceswapError::IndexOutOfRange);
+        };
+        match requested_writable[index] {
+            Some(writable) if writable != info.is_writable => {
+                msg!("Jupiter account index {} repeated with conflicting writable flags", index);
+                return err!(RaceswapError::ConflictingAccountIndex);
+            }
+            _ => requested_writable[index] = Some(info.is_writable),
+        }
+
+        // CRITICAL: Only use the permissions we actually have!
+        // Ignore Jupiter's desired writable flag - use only what the transaction gave us
+        metas.push(AccountMeta {
+            pubkey: *acc_info.key,
+            is_signer: acc_info.is_signer,
+            is_writable: acc_info.is_writable,  // Use actual permission only!
+        });
+    }
+
+    Ok(metas)
+}
+
+/// Checks the client's lookup-table hint: indices must be strictly ascending
+/// (loaded addresses keep table order), present in `remaining`, and never
+/// signers, since a lookup table cannot supply a signature.
+fn check_alt_hint(alt_hint: &[u8], remaining: &[AccountInfo]) -> Result<()> {
+    let mut previous: Option<u8> = None;
+    for &index in alt_hint {
+        let Some(acc_info) = remaining.get(index as usize) else {
+            msg!("ALT hint index {} missing ({} accounts)", index, remaining.len());
+            return err!(RaceswapError::AltHintMismatch);
+        };
+        require!(
+            !matches!(previous, Some(prev) if prev >= index) && !acc_info.is_signer,
+            RaceswapError::AltHintMismatch
+        );
+        previous = Some(index);
+    }
+    Ok(())
+}
+
+/// Validates a batch or split leg and returns how many remaining accounts its
+/// route spans (through its highest index).
+fn check_batch_leg(leg: &ExecuteSwapParams) -> Result<usize> {
+    check_amount(leg.amount)?;
+    check_instruction_data(&leg.jupiter_data)?;
+    check_deadline(leg.deadline_unix_ts)?;
+    // Input-token fees, referrers and dry runs stay single-swap features; the
+    // batch fee always goes to the treasury whole
+    require!(
+        leg.fee_mode == FeeMode::Sol && !leg.dry_run,
+        RaceswapError::InvalidFeeMode
+    );
+    require!(leg.referrer.is_none(), RaceswapError::InvalidReferrer);
+
+    let highest_index = leg
+        .jupiter_account_infos
+        .iter()
+        .map(|info| info.index as usize)
+        .max()
+        .ok_or(RaceswapError::InsufficientAccounts)?;
+    require!(
+        leg.jupiter_account_infos.len() >= leg.min_accounts as usize,
+        RaceswapError::InsufficientAccounts
+    );
+    Ok(highest_index + 1)
+}
+
+/// Builds a leg's Jupiter CPI over its own slice of remaining_accounts.
+fn build_jupiter_leg<'info>(
+    jupiter_program: &AccountInfo<'info>,
+    leg: &ExecuteSwapParams,
+    jupiter_remaining: &[AccountInfo<'info>],
+) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+    check_alt_hint(&leg.alt_hint, jupiter_remaining)?;
+    let jupiter_ix = Instruction {
+        program_id: jupiter_program.key(),
+        accounts: resolve_jupiter_accounts(&leg.jupiter_account_infos, jupiter_remaining)?,
+        data: leg.jupiter_data.clone(),
+    };
+    let mut account_infos = vec![jupiter_program.clone()];
+    account_infos.extend(jupiter_remaining.iter().cloned());
+    Ok((jupiter_ix, account_infos))
+}
+
+/// Reads the balance of a batch leg's destination, which must be a token
+/// account owned by `user`.
+fn destination_amount(destination: &AccountInfo, user: &Pubkey) -> Result<u64> {
+    let token_program = *destination.owner;
+    require!(
+        token_program == anchor_spl::token::ID || token_program == anchor_spl::token_2022::ID,
+        RaceswapError::InvalidBatch
+    );
+    let account = TokenAccount::try_deserialize(&mut &destination.try_borrow_data()?[..])?;
+    require_keys_eq!(account.owner, *user, RaceswapError::InvalidBatch);
+    Ok(account.amount)
+}
+
+/// Transfers `fee` input tokens from the user to the treasury's token account.
+fn collect_input_token_fee(accounts: &ExecuteSwap, fee: u64) -> Result<()> {
+    let (Some(user_input), Some(input_mint), Some(treasury_token_account), Some(token_program)) = (
+        accounts.user_input.as_ref(),
+        accounts.input_mint.as_ref(),
+        accounts.treasury_token_account.as_ref(),
+        accounts.token_program.as_ref(),
+    ) else {
+        return err!(RaceswapError::MissingFeeTokenAccounts);
+    };
+    require_keys_eq!(
+        treasury_token_account.owner,
+        accounts.treasury.key(),
+        RaceswapError::InvalidTreasuryAccount
+    );
+    require_keys_eq!(
+        treasury_token_account.mint,
+        input_mint.key(),
+        RaceswapError::InvalidTreasuryAccount
+    );
+    if fee == 0 {
+        return Ok(());
+    }
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: user_input.to_account_info(),
+                mint: input_mint.to_account_info(),
+                to: treasury_token_account.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        fee,
+        input_mint.decimals,
+    )
+}
+
+/// Treasury and Jupiter program swaps are checked against: the V3Config
+/// entries, or the mainnet addresses until it is initialized
+#[cfg(feature = "config-addresses")]
+fn configured_addresses(v3_config: &UncheckedAccount) -> Result<(Pubkey, Pubkey)> {
+    if v3_config.data_is_empty() {
+        return Ok((DEFAULT_TREASURY, JUPITER_PROGRAM_ID));
+    }
+    require_keys_eq!(*v3_config.owner, crate::ID, RaceswapError::InvalidFeeConfig);
+    let v3_config = V3Config::try_deserialize(&mut &v3_config.try_borrow_data()?[..])?;
+    Ok((v3_config.treasury, v3_config.jupiter_program))
+}
+
+/// Without the `config-addresses` feature swaps always check the built-in
+/// mainnet treasury and Jupiter program
+#[cfg(not(feature = "config-addresses"))]
+fn configured_addresses(_v3_config: &UncheckedAccount) -> Result<(Pubkey, Pubkey)> {
+    Ok((DEFAULT_TREASURY, JUPITER_PROGRAM_ID))
+}
+
+/// The config PDA's contents, or `None` while it is uninitialized
+fn load_config(config: &UncheckedAccount) -> Result<Option<RaceswapConfig>> {
+    if config.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*config.owner, crate::ID, RaceswapError::InvalidFeeConfig);
+    Ok(Some(RaceswapConfig::try_deserialize(&mut &config.try_borrow_data()?[..])?))
+}
+
+/// Treasury fee rate, read from the config PDA; 20 bps until it is initialized
+fn treasury_fee_bps(config: &UncheckedAccount) -> Result<u16> {
+    Ok(load_config(config)?.map_or(DEFAULT_TREASURY_FEE_BPS, |config| config.treasury_fee_bps))
+}
+
+/// SOL fee split set by the config authority; none (all to the treasury)
+/// until the config is initialized
+fn fee_shares(config: &UncheckedAccount) -> Result<Vec<FeeShare>> {
+    Ok(load_config(config)?.map_or_else(Vec::new, |config| config.fee_shares))
+}
+
 #[derive(Accounts)]
-pub struct ExecuteSwap<'info> {
+pub struct QuoteFee<'info> {
+    /// CHECK: Config PDA, may be uninitialized (the fee then falls back to 20 bps)
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [CONFIG_SEED],
+        bump,
+        space = 8 + RaceswapConfig::LEN
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::RaceswapV3>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ RaceswapError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-    /// CHECK: Treasury wallet - receives SOL fees
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
     #[account(
         mut,
-        address = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L")
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct RaceswapConfig {
+    pub authority: Pubkey,
+    pub treasury_fee_bps: u16,
+    pub bump: u8,
+    /// SOL fee split for `execute_swap`, treasury first; empty sends the
+    /// whole fee to the treasury
+    pub fee_shares: Vec<FeeShare>,
+}
+
+impl RaceswapConfig {
+    pub const LEN: usize = 32 + 2 + 1 + FeeShare::VEC_LEN;
+}
+
+#[derive(Accounts)]
+pub struct InitializeV3Config<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [V3_CONFIG_SEED],
+        bump,
+        space = 8 + V3Config::LEN
+    )]
+    pub v3_config: Account<'info, V3Config>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::RaceswapV3>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ RaceswapError::Unauthorized
     )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Treasury and Jupiter addresses the swap instructions check at runtime in
+/// `config-addresses` builds
+#[account]
+pub struct V3Config {
+    pub treasury: Pubkey,
+    pub jupiter_program: Pubkey,
+    pub bump: u8,
+}
+
+impl V3Config {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct UpdateConfigParams {
+    pub new_authority: Option<Pubkey>,
+    pub treasury_fee_bps: Option<u16>,
+    pub fee_shares: Option<Vec<FeeShare>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// User's output-mint token account; slippage is enforced on its balance delta
+    #[account(mut, token::authority = user)]
+    pub user_destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Config PDA, may be uninitialized (the fee then falls back to 20 bps)
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: V3Config PDA with the treasury and Jupiter addresses, read only
+    /// in `config-addresses` builds; may be uninitialized (the built-in
+    /// mainnet addresses then apply)
+    #[account(seeds = [V3_CONFIG_SEED], bump)]
+    pub v3_config: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet - receives SOL fees; its address and
+    /// system-program owner are checked in the handler
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Jupiter program, checked against the configured one in the handler
+    pub jupiter_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    // Required only with `FeeMode::InputToken`: the user's input account, its
+    // mint, the treasury wallet's token account for that mint and the token program
+    #[account(mut)]
+    pub user_input: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub input_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Referrer wallet, must match `params.referrer` when set
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSwapBatch<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Config PDA, may be uninitialized (the fee then falls back to 20 bps)
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: V3Config PDA with the treasury and Jupiter addresses, read only
+    /// in `config-addresses` builds; may be uninitialized (the built-in
+    /// mainnet addresses then apply)
+    #[account(seeds = [V3_CONFIG_SEED], bump)]
+    pub v3_config: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet - receives SOL fees; its address and
+    /// system-program owner are checked in the handler
+    #[account(mut)]
     pub treasury: UncheckedAccount<'info>,
 
-    /// CHECK: Jupiter v6 program
-    #[account(address = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"))]
+    /// CHECK: Jupiter program, checked against the configured one in the handler
     pub jupiter_program: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
@@ -123,4 +775,116 @@ pub struct ExecuteSwapParams {
     pub min_out: u64,
     pub jupiter_account_infos: Vec<JupiterAccountInfo>,  // 2 bytes per account (94% savings!)
     pub jupiter_data: Vec<u8>,
+    pub deadline_unix_ts: Option<i64>,  // Checked before the fee transfer; None = no deadline
+    pub fee_mode: FeeMode,  // Sol (default) or InputToken; InputToken routes must be built for amount - fee
+    pub referrer: Option<Pubkey>,  // Partner wallet (named `referrer` account), paid out of the treasury fee
+    pub referrer_fee_bps: u16,  // Of the amount; must not exceed the treasury fee bps
+    pub min_accounts: u8,  // Minimum jupiter_account_infos the route is expected to carry
+    pub dry_run: bool,  // Validate and report the fee via return data without moving funds or calling Jupiter
+    pub alt_hint: Vec<u8>,  // Ascending remaining_accounts indices (after fee recipients) expected from lookup tables
+}
+
+// Append-only: existing fields keep their order so older decoders still read the prefix
+#[event]
+pub struct SwapExecuted {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub min_out: u64,
+    pub treasury_fee_lamports: u64,
+    pub jupiter_program: Pubkey,
+    pub referrer: Option<Pubkey>,
+    pub referrer_fee_lamports: u64,
+}
+
+#[event]
+pub struct BatchSwapExecuted {
+    pub user: Pubkey,
+    pub total_amount: u64,
+    pub treasury_fee_lamports: u64,
+    pub jupiter_program: Pubkey,
+    pub outs: Vec<u64>,  // Per leg, in batch order
+}
+
+#[event]
+pub struct SplitSwapExecuted {
+    pub user: Pubkey,
+    pub total_amount: u64,
+    pub treasury_fee_lamports: u64,
+    pub jupiter_program: Pubkey,
+    pub outs: Vec<u64>,  // Per route, in execution order
+    pub total_out: u64,
+}
+
+#[error_code]
+pub enum RaceswapError {
+    #[msg("Treasury must be a system-owned account")]
+    InvalidTreasuryAccount,
+    #[msg("Jupiter account index is past the end of remaining_accounts")]
+    IndexOutOfRange,
+    #[msg("Jupiter account index repeated with conflicting writable flags")]
+    ConflictingAccountIndex,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
+    #[msg("Input-token fee mode requires the user input, mint, treasury token account and token program")]
+    MissingFeeTokenAccounts,
+    #[msg("Input-token fees, referrers and dry runs are not supported here")]
+    InvalidFeeMode,
+    #[msg("Referrer account missing or does not match params.referrer")]
+    InvalidReferrer,
+    #[msg("Route carries fewer accounts than expected")]
+    InsufficientAccounts,
+    #[msg("Lookup-table hint indices are missing, out of order, or point at signers")]
+    AltHintMismatch,
+    #[msg("Batch must hold 1-4 swaps, consume every remaining account and use user-owned destinations")]
+    InvalidBatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(index: u8, is_writable: bool) -> JupiterAccountInfo {
+        JupiterAccountInfo { index, is_writable }
+    }
+
+    /// Runs `resolve_jupiter_accounts` against five writable accounts.
+    fn resolve(infos: &[JupiterAccountInfo]) -> Result<Vec<AccountMeta>> {
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let owner = Pubkey::default();
+        let mut lamports = [0u64; 5];
+        let mut data = [[0u8; 0]; 5];
+        let remaining: Vec<AccountInfo> = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, true, lamports, data, &owner, false, 0)
+            })
+            .collect();
+        resolve_jupiter_accounts(infos, &remaining)
+    }
+
+    #[test]
+    fn index_past_the_accounts_is_out_of_range() {
+        assert_eq!(
+            resolve(&[info(0, true), info(255, true)]).unwrap_err(),
+            error!(RaceswapError::IndexOutOfRange)
+        );
+        assert_eq!(
+            resolve(&[info(5, false)]).unwrap_err(),
+            error!(RaceswapError::IndexOutOfRange)
+        );
+        assert_eq!(resolve(&[info(4, false)]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn duplicate_index_with_conflicting_flags_is_rejected() {
+        assert_eq!(
+            resolve(&[info(2, true), info(2, false)]).unwrap_err(),
+            error!(RaceswapError::ConflictingAccountIndex)
+        );
+        assert_eq!(resolve(&[info(2, true), info(2, true)]).unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,48 @@
+use crate::fixture::{error, Fixture};
+use raceswap_v3::RaceswapError;
+
+#[test]
+fn ascending_hint_within_the_route_passes() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.alt_hint = vec![0, 1];
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn hint_past_the_remaining_accounts_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.alt_hint = vec![0, 2];
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::AltHintMismatch)
+    );
+}
+
+#[test]
+fn out_of_order_hint_is_rejected() {
+    let mut fixture = Fixture::new();
+    for alt_hint in [vec![1, 0], vec![1, 1]] {
+        let mut swap = fixture.swap(1_000_000, 5_000);
+        swap.params.alt_hint = alt_hint;
+        assert_eq!(
+            fixture.execute(&swap),
+            error(RaceswapError::AltHintMismatch)
+        );
+    }
+}
+
+#[test]
+fn hint_naming_a_signer_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    // Signers cannot be loaded from a lookup table
+    swap.remaining[0].is_signer = true;
+    swap.params.alt_hint = vec![0];
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::AltHintMismatch)
+    );
+}
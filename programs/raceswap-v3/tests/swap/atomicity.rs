@@ -0,0 +1,38 @@
+use crate::fixture::{Fixture, SOURCE_BALANCE, TREASURY, USER_LAMPORTS};
+use raceswap_harness::route_data;
+
+#[test]
+fn successful_swap_pays_the_treasury() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+
+    // 20 bps of the amount by default
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 2_000);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.user),
+        USER_LAMPORTS - 2_000
+    );
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn failed_jupiter_cpi_leaves_the_treasury_unchanged() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    // The route tries to spend more than the source holds, so the CPI fails
+    // after the fee transfer
+    swap.params.jupiter_data = route_data(SOURCE_BALANCE + 1, 5_000);
+
+    assert!(fixture.execute(&swap).is_err());
+    // The fee did move inside the transaction, and was rolled back with it
+    assert!(fixture
+        .ledger
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("Treasury fee paid")));
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+    assert_eq!(fixture.ledger.lamports(&fixture.user), USER_LAMPORTS);
+    assert_eq!(fixture.token_amount(&fixture.source), SOURCE_BALANCE);
+}
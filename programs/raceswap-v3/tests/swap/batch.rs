@@ -0,0 +1,119 @@
+use crate::fixture::{error, instruction, Fixture, SOURCE_BALANCE, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::system_program;
+use raceswap_common::validation::{ValidationError, JUPITER_PROGRAM_ID};
+use raceswap_v3::{accounts, BatchSwapExecuted, ExecuteSwapParams, RaceswapError};
+
+/// One batch leg swapping `amount` from `source` into a fresh destination
+/// for `out`: its params, its remaining-account slice (destination, then
+/// route) and the destination.
+fn leg(
+    fixture: &mut Fixture,
+    amount: u64,
+    out: u64,
+) -> (ExecuteSwapParams, Vec<AccountMeta>, Pubkey) {
+    let (input_mint, output_mint, user) = (fixture.input_mint, fixture.output_mint, fixture.user);
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    fixture
+        .ledger
+        .add_token_account(source, input_mint, user, SOURCE_BALANCE);
+    fixture
+        .ledger
+        .add_token_account(destination, output_mint, user, 0);
+
+    let params = fixture.swap(amount, out).params;
+    let remaining = vec![
+        AccountMeta::new(destination, false),
+        AccountMeta::new(source, false),
+        AccountMeta::new(destination, false),
+    ];
+    (params, remaining, destination)
+}
+
+fn execute_batch(
+    fixture: &mut Fixture,
+    legs: &[(ExecuteSwapParams, Vec<AccountMeta>, Pubkey)],
+) -> ProgramResult {
+    let params = legs.iter().map(|(params, _, _)| params.clone()).collect();
+    let remaining: Vec<AccountMeta> = legs
+        .iter()
+        .flat_map(|(_, remaining, _)| remaining.clone())
+        .collect();
+    let ix = instruction(
+        &accounts::ExecuteSwapBatch {
+            user: fixture.user,
+            config: fixture.config,
+            v3_config: fixture.v3_config,
+            treasury: TREASURY,
+            jupiter_program: JUPITER_PROGRAM_ID,
+            system_program: system_program::ID,
+        },
+        raceswap_v3::instruction::ExecuteSwapBatch { params },
+        &remaining,
+    );
+    fixture.ledger.process(&ix)
+}
+
+#[test]
+fn batch_fills_every_leg_for_one_fee() {
+    let mut fixture = Fixture::new();
+    let legs = [
+        leg(&mut fixture, 1_000_000, 5_000),
+        leg(&mut fixture, 500_000, 2_500),
+    ];
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    execute_batch(&mut fixture, &legs).unwrap();
+
+    assert_eq!(fixture.token_amount(&legs[0].2), 5_000);
+    assert_eq!(fixture.token_amount(&legs[1].2), 2_500);
+    // 0.2% of the combined 1_500_000
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 3_000);
+    let events = fixture.ledger.events::<BatchSwapExecuted>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].total_amount, 1_500_000);
+    assert_eq!(events[0].treasury_fee_lamports, 3_000);
+    assert_eq!(events[0].outs, vec![5_000, 2_500]);
+}
+
+#[test]
+fn leg_below_its_min_out_reverts_the_batch() {
+    let mut fixture = Fixture::new();
+    let mut legs = [
+        leg(&mut fixture, 1_000_000, 5_000),
+        leg(&mut fixture, 500_000, 2_500),
+    ];
+    legs[1].0.min_out = 2_501;
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    assert_eq!(
+        execute_batch(&mut fixture, &legs),
+        error(ValidationError::BelowMinOut)
+    );
+    assert_eq!(fixture.token_amount(&legs[0].2), 0);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+}
+
+#[test]
+fn batch_above_four_legs_is_rejected() {
+    let mut fixture = Fixture::new();
+    let legs: Vec<_> = (0..5).map(|_| leg(&mut fixture, 100_000, 500)).collect();
+    assert_eq!(
+        execute_batch(&mut fixture, &legs),
+        error(RaceswapError::InvalidBatch)
+    );
+}
+
+#[test]
+fn unconsumed_trailing_accounts_are_rejected() {
+    let mut fixture = Fixture::new();
+    let mut legs = [leg(&mut fixture, 1_000_000, 5_000)];
+    legs[0]
+        .1
+        .push(AccountMeta::new(Pubkey::new_unique(), false));
+    assert_eq!(
+        execute_batch(&mut fixture, &legs),
+        error(RaceswapError::InvalidBatch)
+    );
+}
@@ -0,0 +1,105 @@
+//! Runs against the `config-addresses` build, which the crate's
+//! dev-dependency on itself enables for tests.
+
+use crate::fixture::{error, instruction, Fixture, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::system_program;
+use raceswap_common::validation::ValidationError;
+use raceswap_harness::mock_route;
+use raceswap_v3::{accounts, RaceswapError};
+use std::rc::Rc;
+
+fn initialize_v3_config(
+    fixture: &mut Fixture,
+    payer: Pubkey,
+    treasury: Pubkey,
+    jupiter_program: Pubkey,
+) -> ProgramResult {
+    let ix = instruction(
+        &accounts::InitializeV3Config {
+            v3_config: fixture.v3_config,
+            program: raceswap_v3::ID,
+            program_data: fixture.program_data,
+            payer,
+            system_program: system_program::ID,
+        },
+        raceswap_v3::instruction::InitializeV3Config {
+            treasury,
+            jupiter_program,
+        },
+        &[],
+    );
+    fixture.ledger.process(&ix)
+}
+
+/// Points the program at a fresh treasury and a second deployment of the
+/// mocked Jupiter, returning both.
+fn configure(fixture: &mut Fixture) -> (Pubkey, Pubkey) {
+    let treasury = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(treasury, Rent::default().minimum_balance(0));
+    let jupiter_program = Pubkey::new_unique();
+    fixture
+        .ledger
+        .add_program(jupiter_program, Rc::new(mock_route));
+    let authority = fixture.authority;
+    initialize_v3_config(fixture, authority, treasury, jupiter_program).unwrap();
+    (treasury, jupiter_program)
+}
+
+#[test]
+fn swap_uses_the_configured_addresses() {
+    let mut fixture = Fixture::new();
+    let (treasury, jupiter_program) = configure(&mut fixture);
+    let treasury_before = fixture.ledger.lamports(&treasury);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.treasury = treasury;
+    swap.accounts.jupiter_program = jupiter_program;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.ledger.lamports(&treasury), treasury_before + 2_000);
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn built_in_treasury_is_rejected_once_configured() {
+    let mut fixture = Fixture::new();
+    let (_, jupiter_program) = configure(&mut fixture);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.treasury = TREASURY;
+    swap.accounts.jupiter_program = jupiter_program;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+}
+
+#[test]
+fn built_in_jupiter_is_rejected_once_configured() {
+    let mut fixture = Fixture::new();
+    let (treasury, _) = configure(&mut fixture);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.treasury = treasury;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::InvalidJupiterProgram)
+    );
+}
+
+#[test]
+fn only_the_upgrade_authority_can_configure_addresses() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    assert_eq!(
+        initialize_v3_config(&mut fixture, user, user, Pubkey::new_unique()),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.ledger.exists(&fixture.v3_config));
+
+    // The built-in addresses still apply
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+}
@@ -0,0 +1,24 @@
+use crate::fixture::{error, Fixture};
+use raceswap_common::validation::ValidationError;
+use raceswap_common::MAX_INSTRUCTION_DATA_LEN;
+
+#[test]
+fn jupiter_data_at_the_cap_is_accepted() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.jupiter_data.resize(MAX_INSTRUCTION_DATA_LEN, 0);
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn jupiter_data_past_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params
+        .jupiter_data
+        .resize(MAX_INSTRUCTION_DATA_LEN + 1, 0);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::InstructionDataTooLarge)
+    );
+}
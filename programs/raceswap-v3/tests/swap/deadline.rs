@@ -0,0 +1,44 @@
+use crate::fixture::{error, Fixture, TREASURY};
+use raceswap_common::validation::ValidationError;
+
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn swap_before_its_deadline_passes() {
+    let mut fixture = Fixture::new();
+    fixture.ledger.set_unix_timestamp(NOW);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.deadline_unix_ts = Some(NOW);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn stale_swap_is_rejected_before_paying_fees() {
+    let mut fixture = Fixture::new();
+    fixture.ledger.set_unix_timestamp(NOW);
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.deadline_unix_ts = Some(NOW - 1);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::DeadlineExceeded)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+    assert!(!fixture
+        .ledger
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("Treasury fee paid")));
+}
+
+#[test]
+fn no_deadline_never_expires() {
+    let mut fixture = Fixture::new();
+    fixture.ledger.set_unix_timestamp(i64::MAX);
+
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
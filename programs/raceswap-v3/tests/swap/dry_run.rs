@@ -0,0 +1,41 @@
+use crate::fixture::{Fixture, SOURCE_BALANCE, TREASURY, USER_LAMPORTS};
+use anchor_lang::prelude::*;
+
+#[test]
+fn dry_run_moves_no_lamports_or_tokens() {
+    let mut fixture = Fixture::new();
+    let referrer = Pubkey::new_unique();
+    let rent_exempt = Rent::default().minimum_balance(0);
+    fixture.ledger.fund(referrer, rent_exempt);
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    // Every validation passes, including the referrer's
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.dry_run = true;
+    swap.params.referrer = Some(referrer);
+    swap.params.referrer_fee_bps = 5;
+    swap.accounts.referrer = Some(referrer);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.ledger.lamports(&fixture.user), USER_LAMPORTS);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+    assert_eq!(fixture.ledger.lamports(&referrer), rent_exempt);
+    assert_eq!(fixture.token_amount(&fixture.source), SOURCE_BALANCE);
+    assert_eq!(fixture.token_amount(&fixture.destination), 0);
+    assert!(!fixture
+        .ledger
+        .logs()
+        .iter()
+        .any(|log| log.starts_with("Invoking Jupiter")));
+}
+
+#[test]
+fn same_swap_without_dry_run_pays_and_swaps() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    assert_eq!(fixture.ledger.lamports(&fixture.user), USER_LAMPORTS - 2_000);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 2_000);
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
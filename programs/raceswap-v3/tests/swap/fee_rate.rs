@@ -0,0 +1,74 @@
+use crate::fixture::{error, Fixture, TREASURY};
+use raceswap_v3::{RaceswapError, UpdateConfigParams};
+
+/// Lamports the treasury receives for a 1_000_000 swap.
+fn treasury_fee(fixture: &mut Fixture) -> u64 {
+    let before = fixture.ledger.lamports(&TREASURY);
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    fixture.ledger.lamports(&TREASURY) - before
+}
+
+#[test]
+fn fee_defaults_to_20_bps_without_a_config() {
+    let mut fixture = Fixture::new();
+    assert_eq!(treasury_fee(&mut fixture), 2_000);
+}
+
+#[test]
+fn fee_follows_the_configured_rate() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(10).unwrap();
+    assert_eq!(treasury_fee(&mut fixture), 1_000);
+
+    let authority = fixture.authority;
+    fixture
+        .update_config(
+            authority,
+            UpdateConfigParams {
+                treasury_fee_bps: Some(0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(treasury_fee(&mut fixture), 0);
+}
+
+#[test]
+fn only_the_authority_can_change_the_rate() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(20).unwrap();
+
+    let user = fixture.user;
+    assert_eq!(
+        fixture.update_config(
+            user,
+            UpdateConfigParams {
+                treasury_fee_bps: Some(0),
+                ..Default::default()
+            },
+        ),
+        error(RaceswapError::Unauthorized)
+    );
+    assert_eq!(treasury_fee(&mut fixture), 2_000);
+}
+
+#[test]
+fn rate_above_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        fixture.initialize_config(1_001),
+        error(RaceswapError::InvalidFeeConfig)
+    );
+}
+
+#[test]
+fn quoted_fee_matches_the_charged_fee() {
+    let mut fixture = Fixture::new();
+    assert_eq!(fixture.quote_fee(1_000_000), treasury_fee(&mut fixture));
+
+    fixture.initialize_config(7).unwrap();
+    // 7 bps of 1_234_567 is 864.19..., rounded down like the swap itself
+    assert_eq!(fixture.quote_fee(1_000_000), 700);
+    assert_eq!(fixture.quote_fee(1_234_567), 864);
+    assert_eq!(fixture.quote_fee(1_000_000), treasury_fee(&mut fixture));
+}
@@ -0,0 +1,170 @@
+use crate::fixture::{error, writable, Fixture, Swap, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use raceswap_common::{FeeShare, FeeSplitError};
+use raceswap_v3::{RaceswapError, UpdateConfigParams};
+
+/// Two funded partner wallets besides the treasury.
+fn partners(fixture: &mut Fixture) -> [Pubkey; 2] {
+    let partners = [Pubkey::new_unique(), Pubkey::new_unique()];
+    for partner in partners {
+        fixture
+            .ledger
+            .fund(partner, Rent::default().minimum_balance(0));
+    }
+    partners
+}
+
+fn shares(recipients: &[Pubkey], weights: &[u16]) -> Vec<FeeShare> {
+    recipients
+        .iter()
+        .zip(weights)
+        .map(|(&recipient, &weight)| FeeShare { recipient, weight })
+        .collect()
+}
+
+/// Stores the split in the config as its authority.
+fn set_split(fixture: &mut Fixture, recipients: &[Pubkey], weights: &[u16]) -> ProgramResult {
+    let authority = fixture.authority;
+    fixture.update_config(
+        authority,
+        UpdateConfigParams {
+            fee_shares: Some(shares(recipients, weights)),
+            ..Default::default()
+        },
+    )
+}
+
+/// A 1_000_000 swap (2_000 lamport fee) passing `recipients` ahead of the route.
+fn split_swap(fixture: &Fixture, recipients: &[Pubkey]) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    let mut remaining: Vec<AccountMeta> = recipients
+        .iter()
+        .map(|recipient| AccountMeta::new(*recipient, false))
+        .collect();
+    remaining.append(&mut swap.remaining);
+    swap.remaining = remaining;
+    swap
+}
+
+#[test]
+fn fee_is_split_three_ways_by_weight() {
+    let mut fixture = Fixture::new();
+    let [first, second] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    set_split(&mut fixture, &[TREASURY, first, second], &[5_000, 3_000, 2_000]).unwrap();
+    let before: Vec<u64> = [TREASURY, first, second]
+        .iter()
+        .map(|key| fixture.ledger.lamports(key))
+        .collect();
+
+    let swap = split_swap(&fixture, &[TREASURY, first, second]);
+    fixture.execute(&swap).unwrap();
+
+    // The 2_000 lamport fee is split 50/30/20
+    assert_eq!(fixture.ledger.lamports(&TREASURY), before[0] + 1_000);
+    assert_eq!(fixture.ledger.lamports(&first), before[1] + 600);
+    assert_eq!(fixture.ledger.lamports(&second), before[2] + 400);
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn last_recipient_takes_the_rounding_remainder() {
+    let mut fixture = Fixture::new();
+    let [first, second] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    set_split(&mut fixture, &[TREASURY, first, second], &[3_333, 3_333, 3_334]).unwrap();
+    let before = fixture.ledger.lamports(&second);
+
+    // 2_000 * 3_333 / 10_000 rounds down to 666 for the first two shares
+    let swap = split_swap(&fixture, &[TREASURY, first, second]);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.ledger.lamports(&second), before + 668);
+}
+
+#[test]
+fn weights_must_sum_to_ten_thousand() {
+    let mut fixture = Fixture::new();
+    let [first, second] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    for weights in [[5_000, 3_000, 1_999], [5_000, 3_000, 2_001]] {
+        assert_eq!(
+            set_split(&mut fixture, &[TREASURY, first, second], &weights),
+            error(FeeSplitError::InvalidFeeWeights)
+        );
+    }
+}
+
+#[test]
+fn zero_treasury_share_is_rejected() {
+    let mut fixture = Fixture::new();
+    let [first, _] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    assert_eq!(
+        set_split(&mut fixture, &[TREASURY, first], &[0, 10_000]),
+        error(FeeSplitError::InvalidFeeWeights)
+    );
+}
+
+#[test]
+fn treasury_must_be_the_first_recipient() {
+    let mut fixture = Fixture::new();
+    let [first, second] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    set_split(&mut fixture, &[first, TREASURY, second], &[5_000, 3_000, 2_000]).unwrap();
+    let swap = split_swap(&fixture, &[first, TREASURY, second]);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+}
+
+#[test]
+fn only_the_authority_sets_the_split() {
+    let mut fixture = Fixture::new();
+    let [first, _] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    let user = fixture.user;
+    assert_eq!(
+        fixture.update_config(
+            user,
+            UpdateConfigParams {
+                fee_shares: Some(shares(&[user, first], &[5_000, 5_000])),
+                ..Default::default()
+            },
+        ),
+        error(RaceswapError::Unauthorized)
+    );
+}
+
+#[test]
+fn caller_cannot_swap_in_their_own_recipient() {
+    let mut fixture = Fixture::new();
+    let [first, _] = partners(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    set_split(&mut fixture, &[TREASURY, first], &[8_000, 2_000]).unwrap();
+
+    let user = fixture.user;
+    let swap = split_swap(&fixture, &[TREASURY, user]);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+}
+
+#[test]
+fn leading_caller_accounts_take_no_share_without_a_split() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    let (treasury_before, user_before) = (
+        fixture.ledger.lamports(&TREASURY),
+        fixture.ledger.lamports(&user),
+    );
+
+    // With no configured split a leading wallet is just another route account
+    let mut swap = split_swap(&fixture, &[user]);
+    swap.params.jupiter_account_infos = writable(&[1, 2]);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 2_000);
+    assert_eq!(fixture.ledger.lamports(&user), user_before - 2_000);
+}
@@ -0,0 +1,216 @@
+#![allow(dead_code)]
+
+//! A deployed program with a funded user holding an input token account and
+//! an empty destination, plus builders for `execute_swap`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use raceswap_common::validation::JUPITER_PROGRAM_ID;
+use raceswap_common::FeeMode;
+use raceswap_harness::{mock_route, route_data, Ledger};
+use raceswap_v3::{
+    accounts, instruction, ExecuteSwapParams, JupiterAccountInfo, UpdateConfigParams, ID,
+};
+use std::rc::Rc;
+
+pub const TREASURY: Pubkey = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L");
+pub const USER_LAMPORTS: u64 = 10_000_000_000;
+pub const SOURCE_BALANCE: u64 = 10_000_000;
+
+pub struct Fixture {
+    pub ledger: Ledger,
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub program_data: Pubkey,
+    pub config: Pubkey,
+    pub v3_config: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+}
+
+/// One `execute_swap` call: params, named accounts and remaining accounts.
+pub struct Swap {
+    pub params: ExecuteSwapParams,
+    pub accounts: accounts::ExecuteSwap,
+    pub remaining: Vec<AccountMeta>,
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &ID).0
+}
+
+pub fn error(code: impl Into<u32>) -> ProgramResult {
+    Err(raceswap_harness::anchor_error(code))
+}
+
+pub fn instruction(
+    accounts: &impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: &[AccountMeta],
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// Route accounts at `indices`, each asking to be writable.
+pub fn writable(indices: &[u8]) -> Vec<JupiterAccountInfo> {
+    indices
+        .iter()
+        .map(|&index| JupiterAccountInfo {
+            index,
+            is_writable: true,
+        })
+        .collect()
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let mut ledger = Ledger::new(ID, raceswap_v3::entry);
+        ledger.add_program(JUPITER_PROGRAM_ID, Rc::new(mock_route));
+
+        let authority = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let program_data = ledger.set_upgrade_authority(ID, Some(authority));
+        ledger.fund(authority, USER_LAMPORTS);
+        ledger.fund(user, USER_LAMPORTS);
+        ledger.fund(TREASURY, Rent::default().minimum_balance(0));
+        let input_mint = Pubkey::new_unique();
+        let output_mint = Pubkey::new_unique();
+        ledger.add_mint(input_mint, 6);
+        ledger.add_mint(output_mint, 6);
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        ledger.add_token_account(source, input_mint, user, SOURCE_BALANCE);
+        ledger.add_token_account(destination, output_mint, user, 0);
+
+        Self {
+            ledger,
+            authority,
+            user,
+            program_data,
+            config: pda(&[b"raceswap-config"]),
+            v3_config: pda(&[b"raceswap-v3-config"]),
+            input_mint,
+            output_mint,
+            source,
+            destination,
+        }
+    }
+
+    /// Creates the fee config with `authority` (also the program's upgrade
+    /// authority) as its authority.
+    pub fn initialize_config(&mut self, treasury_fee_bps: u16) -> ProgramResult {
+        self.initialize_config_as(self.authority, treasury_fee_bps)
+    }
+
+    /// Creates the fee config with `payer` signing as the upgrade authority.
+    pub fn initialize_config_as(&mut self, payer: Pubkey, treasury_fee_bps: u16) -> ProgramResult {
+        let ix = instruction(
+            &accounts::InitializeConfig {
+                config: self.config,
+                program: ID,
+                program_data: self.program_data,
+                payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeConfig {
+                authority: self.authority,
+                treasury_fee_bps,
+            },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn update_config(&mut self, signer: Pubkey, params: UpdateConfigParams) -> ProgramResult {
+        let ix = instruction(
+            &accounts::UpdateConfig {
+                config: self.config,
+                authority: signer,
+            },
+            instruction::UpdateConfig { params },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    /// The fee `quote_fee` reports for `amount`, read from the return data.
+    pub fn quote_fee(&mut self, amount: u64) -> u64 {
+        let ix = instruction(
+            &accounts::QuoteFee {
+                config: self.config,
+            },
+            instruction::QuoteFee { amount },
+            &[],
+        );
+        self.ledger.process(&ix).expect("quote_fee");
+        let (_, data) = self.ledger.return_data().expect("quote_fee return data");
+        u64::from_le_bytes(data.try_into().unwrap())
+    }
+
+    pub fn swap_accounts(&self) -> accounts::ExecuteSwap {
+        accounts::ExecuteSwap {
+            user: self.user,
+            user_destination: self.destination,
+            config: self.config,
+            v3_config: self.v3_config,
+            treasury: TREASURY,
+            jupiter_program: JUPITER_PROGRAM_ID,
+            system_program: system_program::ID,
+            user_input: None,
+            input_mint: None,
+            treasury_token_account: None,
+            token_program: None,
+            referrer: None,
+        }
+    }
+
+    /// A swap of `amount` whose route takes `amount` from the source and
+    /// delivers `out` to the destination, with `min_out == out`.
+    pub fn swap(&self, amount: u64, out: u64) -> Swap {
+        Swap {
+            params: ExecuteSwapParams {
+                amount,
+                min_out: out,
+                jupiter_account_infos: writable(&[0, 1]),
+                jupiter_data: route_data(amount, out),
+                deadline_unix_ts: None,
+                fee_mode: FeeMode::Sol,
+                referrer: None,
+                referrer_fee_bps: 0,
+                min_accounts: 0,
+                dry_run: false,
+                alt_hint: Vec::new(),
+            },
+            accounts: self.swap_accounts(),
+            remaining: vec![
+                AccountMeta::new(self.source, false),
+                AccountMeta::new(self.destination, false),
+            ],
+        }
+    }
+
+    pub fn execute(&mut self, swap: &Swap) -> ProgramResult {
+        let ix = instruction(
+            &swap.accounts,
+            instruction::ExecuteSwap {
+                params: swap.params.clone(),
+            },
+            &swap.remaining,
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        self.ledger.token_amount(key)
+    }
+}
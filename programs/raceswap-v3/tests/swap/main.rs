@@ -0,0 +1,23 @@
+//! Behavior tests for `raceswap_v3`, driven through the in-process ledger from
+//! `raceswap-harness` with a mocked Jupiter route.
+
+mod fixture;
+
+mod alt_hint;
+mod atomicity;
+mod batch;
+mod config_addresses;
+mod config_init;
+mod data_cap;
+mod deadline;
+mod dry_run;
+mod fee_rate;
+mod fee_split;
+mod input_token_fee;
+mod min_accounts;
+mod min_out;
+mod referrer;
+mod split_swap;
+mod status;
+mod swap_event;
+mod treasury_account;
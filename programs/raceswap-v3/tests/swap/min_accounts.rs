@@ -0,0 +1,38 @@
+use crate::fixture::{error, writable, Fixture, TREASURY};
+use raceswap_v3::RaceswapError;
+
+#[test]
+fn route_with_the_expected_accounts_passes() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_accounts = 2;
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn truncated_route_is_rejected_before_paying() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_accounts = 3;
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InsufficientAccounts)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+}
+
+#[test]
+fn index_past_the_remaining_accounts_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.jupiter_account_infos = writable(&[0, 1, 2]);
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::IndexOutOfRange)
+    );
+}
@@ -0,0 +1,56 @@
+use crate::fixture::{error, Fixture, Swap, TREASURY};
+use anchor_lang::prelude::*;
+use raceswap_common::FeeSplitError;
+use raceswap_v3::{RaceswapError, SwapExecuted};
+
+fn referred_swap(fixture: &mut Fixture, referrer_fee_bps: u16) -> (Swap, Pubkey) {
+    let referrer = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(referrer, Rent::default().minimum_balance(0));
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.referrer = Some(referrer);
+    swap.params.referrer_fee_bps = referrer_fee_bps;
+    swap.accounts.referrer = Some(referrer);
+    (swap, referrer)
+}
+
+#[test]
+fn referrer_takes_its_share_of_the_treasury_fee() {
+    let mut fixture = Fixture::new();
+    let (swap, referrer) = referred_swap(&mut fixture, 5);
+    let referrer_before = fixture.ledger.lamports(&referrer);
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.ledger.lamports(&referrer) - referrer_before, 500);
+    assert_eq!(fixture.ledger.lamports(&TREASURY) - treasury_before, 1_500);
+
+    let event = &fixture.ledger.events::<SwapExecuted>()[0];
+    assert_eq!(event.referrer, Some(referrer));
+    assert_eq!(event.referrer_fee_lamports, 500);
+    assert_eq!(event.treasury_fee_lamports, 1_500);
+}
+
+#[test]
+fn referrer_fee_above_the_treasury_rate_is_rejected() {
+    let mut fixture = Fixture::new();
+    let (swap, _) = referred_swap(&mut fixture, 21);
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(FeeSplitError::ReferrerFeeTooHigh)
+    );
+}
+
+#[test]
+fn referrer_account_must_match_the_param() {
+    let mut fixture = Fixture::new();
+    let (mut swap, _) = referred_swap(&mut fixture, 5);
+    swap.accounts.referrer = Some(fixture.user);
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReferrer)
+    );
+}
@@ -0,0 +1,107 @@
+use crate::fixture::{error, instruction, Fixture, SOURCE_BALANCE, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::system_program;
+use raceswap_common::validation::{ValidationError, JUPITER_PROGRAM_ID};
+use raceswap_v3::{accounts, ExecuteSwapParams, RaceswapError, SplitSwapExecuted};
+
+/// Splits 1_500_000 as 1_000_000 -> 5_000 and 500_000 -> 2_600 through the
+/// fixture's source and destination, requiring `min_out` combined.
+fn split(fixture: &Fixture, min_out: u64) -> (ExecuteSwapParams, ExecuteSwapParams, u64) {
+    let first = fixture.swap(1_000_000, 5_000).params;
+    let second = fixture.swap(500_000, 2_600).params;
+    (first, second, min_out)
+}
+
+fn execute_split(
+    fixture: &mut Fixture,
+    (first, second, min_out): (ExecuteSwapParams, ExecuteSwapParams, u64),
+    trailing: &[AccountMeta],
+) -> ProgramResult {
+    let route = [
+        AccountMeta::new(fixture.source, false),
+        AccountMeta::new(fixture.destination, false),
+    ];
+    let mut remaining = vec![AccountMeta::new(fixture.destination, false)];
+    remaining.extend_from_slice(&route);
+    remaining.extend_from_slice(&route);
+    remaining.extend_from_slice(trailing);
+    let ix = instruction(
+        &accounts::ExecuteSwapBatch {
+            user: fixture.user,
+            config: fixture.config,
+            v3_config: fixture.v3_config,
+            treasury: TREASURY,
+            jupiter_program: JUPITER_PROGRAM_ID,
+            system_program: system_program::ID,
+        },
+        raceswap_v3::instruction::ExecuteSplitSwap {
+            first,
+            second,
+            min_out,
+        },
+        &remaining,
+    );
+    fixture.ledger.process(&ix)
+}
+
+#[test]
+fn both_routes_fill_for_one_fee_on_the_combined_input() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    let legs = split(&fixture, 7_600);
+    execute_split(&mut fixture, legs, &[]).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.destination), 7_600);
+    assert_eq!(
+        fixture.token_amount(&fixture.source),
+        SOURCE_BALANCE - 1_500_000
+    );
+    // 0.2% of the combined 1_500_000
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 3_000);
+    let events = fixture.ledger.events::<SplitSwapExecuted>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].total_amount, 1_500_000);
+    assert_eq!(events[0].outs, vec![5_000, 2_600]);
+    assert_eq!(events[0].total_out, 7_600);
+}
+
+#[test]
+fn combined_output_below_min_out_reverts_both_routes() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    let legs = split(&fixture, 7_601);
+    assert_eq!(
+        execute_split(&mut fixture, legs, &[]),
+        error(ValidationError::BelowMinOut)
+    );
+    assert_eq!(fixture.token_amount(&fixture.destination), 0);
+    assert_eq!(fixture.token_amount(&fixture.source), SOURCE_BALANCE);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+}
+
+#[test]
+fn each_route_keeps_its_own_min_out() {
+    let mut fixture = Fixture::new();
+    let mut legs = split(&fixture, 0);
+    legs.1.min_out = 2_601;
+    assert_eq!(
+        execute_split(&mut fixture, legs, &[]),
+        error(ValidationError::BelowMinOut)
+    );
+    assert_eq!(fixture.token_amount(&fixture.destination), 0);
+}
+
+#[test]
+fn unconsumed_trailing_accounts_are_rejected() {
+    let mut fixture = Fixture::new();
+    let legs = split(&fixture, 7_600);
+    assert_eq!(
+        execute_split(
+            &mut fixture,
+            legs,
+            &[AccountMeta::new(Pubkey::new_unique(), false)]
+        ),
+        error(RaceswapError::InvalidBatch)
+    );
+}
@@ -0,0 +1,41 @@
+use crate::fixture::Fixture;
+use anchor_lang::AnchorDeserialize;
+use raceswap_common::SwapStatus;
+use raceswap_v3::ID;
+
+#[test]
+fn swap_returns_its_status() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+
+    let (program_id, data) = fixture.ledger.return_data().unwrap();
+    assert_eq!(program_id, ID);
+    assert_eq!(
+        SwapStatus::try_from_slice(&data).unwrap(),
+        SwapStatus::ok(5_000, 2_000)
+    );
+}
+
+#[test]
+fn dry_run_returns_the_fee_only() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.dry_run = true;
+    fixture.execute(&swap).unwrap();
+
+    let (_, data) = fixture.ledger.return_data().unwrap();
+    assert_eq!(
+        SwapStatus::try_from_slice(&data).unwrap(),
+        SwapStatus::ok(0, 2_000)
+    );
+}
+
+#[test]
+fn reverted_swap_leaves_no_return_data() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_out = 5_001;
+
+    assert!(fixture.execute(&swap).is_err());
+    assert_eq!(fixture.ledger.return_data(), None);
+}
@@ -0,0 +1,31 @@
+use crate::fixture::Fixture;
+use raceswap_common::validation::JUPITER_PROGRAM_ID;
+use raceswap_v3::SwapExecuted;
+
+#[test]
+fn swap_emits_swap_executed() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_out = 4_000;
+    fixture.execute(&swap).unwrap();
+
+    let events = fixture.ledger.events::<SwapExecuted>();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.user, fixture.user);
+    assert_eq!(event.amount, 1_000_000);
+    assert_eq!(event.min_out, 4_000);
+    assert_eq!(event.treasury_fee_lamports, 2_000);
+    assert_eq!(event.jupiter_program, JUPITER_PROGRAM_ID);
+    assert_eq!(event.referrer, None);
+}
+
+#[test]
+fn reverted_swap_emits_nothing() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_out = 5_001;
+
+    assert!(fixture.execute(&swap).is_err());
+    assert!(fixture.ledger.events::<SwapExecuted>().is_empty());
+}
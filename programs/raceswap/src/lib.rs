@@ -3,37 +3,78 @@ use anchor_lang::solana_program::{
     instruction::{AccountMeta, Instruction},
     program::invoke,
 };
-use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use raceswap_common::validation::{
+    check_amount, check_deadline, check_instruction_data, check_min_out, compute_treasury_fee,
+    validate_jupiter_program, validate_treasury_account, JUPITER_PROGRAM_ID,
+};
+use raceswap_common::{
+    distribute_sol_fee, referrer_fee, split_fee_recipients, transfer_lamports, validate_fee_shares,
+    FeeMode, FeeShare, SwapStatus,
+};
 
 declare_id!("Cy63SzwBBCP5ywaByjUrLuUXQ4pXP9nR7e7kdQqp5uLk");
 
+const CONFIG_SEED: &[u8] = b"raceswap-config";
+const DEFAULT_TREASURY_FEE_BPS: u16 = 20;
+const TREASURY_CONFIG_SEED: &[u8] = b"raceswap-treasury-config";
+/// Fee treasury until a treasury config is initialized
+const DEFAULT_TREASURY: Pubkey = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L");
+
 #[program]
 pub mod raceswap {
     use super::*;
 
     pub fn execute_swap<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteSwap<'info>>, params: ExecuteSwapParams) -> Result<()> {
         msg!("ExecuteSwap: amount={}, min_out={}", params.amount, params.min_out);
+        check_amount(params.amount)?;
+        check_instruction_data(&params.jupiter_data)?;
+        check_deadline(params.deadline_unix_ts)?;
 
-        // 1. Take treasury fee in SOL (0.2% = 20 bps)
-        let treasury_fee_lamports = (params.amount as u64)
-            .checked_mul(20)
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap();
-
-        if treasury_fee_lamports > 0 {
-            system_program::transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.user.to_account_info(),
-                        to: ctx.accounts.treasury.to_account_info(),
-                    },
-                ),
-                treasury_fee_lamports,
-            )?;
-            msg!("Treasury fee paid: {} lamports", treasury_fee_lamports);
-        }
+        // 1. Size the treasury fee in SOL or the input token (config rate, 0.2% = 20 bps by default)
+        let fee_bps = treasury_fee_bps(&ctx.accounts.config)?;
+        let total_fee = compute_treasury_fee(params.amount, fee_bps)?;
+
+        require_keys_eq!(
+            ctx.accounts.treasury.key(),
+            treasury_address(&ctx.accounts.treasury_config)?,
+            RaceswapError::InvalidTreasuryAccount
+        );
+        validate_treasury_account(&ctx.accounts.treasury)?;
+        let jupiter_program = jupiter_program_id(&ctx.accounts.config)?;
+        validate_jupiter_program(&ctx.accounts.jupiter_program, &jupiter_program)?;
+
+        // Leading remaining_accounts are the configured fee split's recipients
+        // for SOL fees; the split itself only ever comes from the config
+        let fee_shares = match params.fee_mode {
+            FeeMode::Sol => fee_shares(&ctx.accounts.config)?,
+            FeeMode::InputToken => Vec::new(),
+        };
+        let (fee_recipients, jupiter_remaining) =
+            split_fee_recipients(ctx.remaining_accounts, fee_shares.len())?;
+
+        // Partner front-ends take `referrer_fee_bps` of the amount out of the
+        // treasury fee; only supported for SOL fees
+        let referrer_account = match params.referrer {
+            Some(referrer) => {
+                require!(params.fee_mode == FeeMode::Sol, RaceswapError::InvalidFeeMode);
+                let referrer_account = ctx
+                    .accounts
+                    .referrer
+                    .as_ref()
+                    .ok_or(RaceswapError::InvalidReferrer)?;
+                require_keys_eq!(referrer_account.key(), referrer, RaceswapError::InvalidReferrer);
+                Some(referrer_account)
+            }
+            None => None,
+        };
+        let referrer_fee_lamports = match referrer_account {
+            Some(_) => referrer_fee(params.amount, params.referrer_fee_bps, fee_bps)?,
+            None => 0,
+        };
+        let treasury_fee = total_fee
+            .checked_sub(referrer_fee_lamports)
+            .ok_or(RaceswapError::InvalidReferrer)?;
 
         // 2. Convert serializable account metas to AccountMeta
         let jupiter_accounts: Vec<AccountMeta> = params.jupiter_accounts
@@ -45,7 +86,6 @@ pub mod raceswap {
             })
             .collect();
 
-        // 3. Execute Jupiter swap via CPI
         let jupiter_ix = Instruction {
             program_id: ctx.accounts.jupiter_program.key(),
             accounts: jupiter_accounts,
@@ -54,33 +94,381 @@ pub mod raceswap {
 
         // Collect all account infos for the CPI
         let mut account_infos = vec![ctx.accounts.jupiter_program.to_account_info()];
-        account_infos.extend(ctx.remaining_accounts.iter().cloned());
+        account_infos.extend(jupiter_remaining.iter().cloned());
 
+        // Nothing above moves funds. A dry run returns here, before the first
+        // side effect, so simulating it never transfers lamports or tokens.
+        if params.dry_run {
+            msg!("Dry run: validated, fee would be {}", total_fee);
+            SwapStatus::ok(0, total_fee).set()?;
+            return Ok(());
+        }
+
+        // 3. Take the fee as the last side effect before the CPI. It is not
+        // final until the whole instruction succeeds: if Jupiter fails, `invoke`
+        // returns Err, the `?` below propagates it and the runtime rolls the
+        // transfer back.
+        if let Some(referrer_account) = referrer_account {
+            transfer_lamports(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                &referrer_account.to_account_info(),
+                referrer_fee_lamports,
+            )?;
+            msg!("Referrer fee paid: {} lamports to {}", referrer_fee_lamports, referrer_account.key());
+        }
+
+        match params.fee_mode {
+            FeeMode::Sol if treasury_fee > 0 => {
+                distribute_sol_fee(
+                    &ctx.accounts.system_program.to_account_info(),
+                    &ctx.accounts.user.to_account_info(),
+                    &ctx.accounts.treasury.to_account_info(),
+                    fee_recipients,
+                    &fee_shares,
+                    treasury_fee,
+                )?;
+                msg!("Treasury fee paid: {} lamports", treasury_fee);
+            }
+            FeeMode::InputToken => {
+                collect_input_token_fee(ctx.accounts, treasury_fee)?;
+                msg!("Treasury fee paid: {} input tokens", treasury_fee);
+            }
+            FeeMode::Sol => {}
+        }
+
+        // 4. Execute Jupiter swap via CPI
         msg!("Invoking Jupiter with {} accounts", account_infos.len());
+        let destination_before = ctx.accounts.user_destination.amount;
         invoke(&jupiter_ix, &account_infos)?;
+        ctx.accounts.user_destination.reload()?;
+        let main_out = check_min_out(
+            destination_before,
+            ctx.accounts.user_destination.amount,
+            params.min_out,
+        )?;
 
         msg!("Swap completed successfully!");
+        emit!(SwapExecuted {
+            user: ctx.accounts.user.key(),
+            amount: params.amount,
+            min_out: params.min_out,
+            fee_mode: params.fee_mode,
+            treasury_fee,
+            referrer: params.referrer,
+            referrer_fee_lamports,
+        });
+        SwapStatus::ok(main_out, total_fee).set()?;
+        Ok(())
+    }
+
+    /// Returns the exact fee `execute_swap` would charge for `amount` at the
+    /// current config rate, so wallets can preview it before signing.
+    pub fn quote_fee(ctx: Context<QuoteFee>, amount: u64) -> Result<u64> {
+        let fee = compute_treasury_fee(amount, treasury_fee_bps(&ctx.accounts.config)?)?;
+        msg!("Quoted fee: {} for amount {}", fee, amount);
+        Ok(fee)
+    }
+
+    /// One-time creation of the fee config. Only the program's upgrade
+    /// authority can run it, so nobody can front-run the deploy and pick the
+    /// config authority.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, authority: Pubkey, treasury_fee_bps: u16) -> Result<()> {
+        require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+
+        let config = &mut ctx.accounts.config;
+        config.authority = authority;
+        config.treasury_fee_bps = treasury_fee_bps;
+        config.bump = ctx.bumps.config;
+        config.jupiter_program = JUPITER_PROGRAM_ID;
+        config.fee_shares = Vec::new();
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            config.authority,
+            RaceswapError::Unauthorized
+        );
+
+        if let Some(new_authority) = params.new_authority {
+            config.authority = new_authority;
+        }
+
+        if let Some(treasury_fee_bps) = params.treasury_fee_bps {
+            require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+            config.treasury_fee_bps = treasury_fee_bps;
+        }
+
+        // Lets the authority move to a new Jupiter release without a redeploy
+        if let Some(jupiter_program) = params.jupiter_program {
+            config.jupiter_program = jupiter_program;
+        }
+
+        if let Some(fee_shares) = params.fee_shares {
+            validate_fee_shares(&fee_shares)?;
+            config.fee_shares = fee_shares;
+        }
+
+        msg!(
+            "Config updated: treasury_fee_bps={}, jupiter_program={}",
+            config.treasury_fee_bps,
+            config.jupiter_program
+        );
         Ok(())
     }
+
+    /// One-time setup of the treasury `execute_swap` pays fees to, replacing
+    /// the built-in one. Only the program's upgrade authority can run it.
+    pub fn initialize_treasury_config(
+        ctx: Context<InitializeTreasuryConfig>,
+        authority: Pubkey,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let treasury_config = &mut ctx.accounts.treasury_config;
+        treasury_config.authority = authority;
+        treasury_config.treasury = treasury;
+        treasury_config.bump = ctx.bumps.treasury_config;
+        Ok(())
+    }
+
+    /// Rotates the fee treasury without a redeploy.
+    pub fn update_treasury(ctx: Context<UpdateTreasury>, new_treasury: Pubkey) -> Result<()> {
+        let treasury_config = &mut ctx.accounts.treasury_config;
+        treasury_config.treasury = new_treasury;
+        msg!("Treasury updated: {}", new_treasury);
+        Ok(())
+    }
+}
+
+/// Transfers `fee` input tokens from the user to the treasury's token account.
+fn collect_input_token_fee(accounts: &ExecuteSwap, fee: u64) -> Result<()> {
+    let (Some(user_input), Some(input_mint), Some(treasury_token_account), Some(token_program)) = (
+        accounts.user_input.as_ref(),
+        accounts.input_mint.as_ref(),
+        accounts.treasury_token_account.as_ref(),
+        accounts.token_program.as_ref(),
+    ) else {
+        return err!(RaceswapError::MissingFeeTokenAccounts);
+    };
+    require_keys_eq!(
+        treasury_token_account.owner,
+        accounts.treasury.key(),
+        RaceswapError::InvalidTreasuryAccount
+    );
+    require_keys_eq!(
+        treasury_token_account.mint,
+        input_mint.key(),
+        RaceswapError::InvalidTreasuryAccount
+    );
+    if fee == 0 {
+        return Ok(());
+    }
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: user_input.to_account_info(),
+                mint: input_mint.to_account_info(),
+                to: treasury_token_account.to_account_info(),
+                authority: accounts.user.to_account_info(),
+            },
+        ),
+        fee,
+        input_mint.decimals,
+    )
+}
+
+/// The config PDA's contents, or `None` while it is uninitialized
+fn load_config(config: &UncheckedAccount) -> Result<Option<RaceswapConfig>> {
+    if config.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*config.owner, crate::ID, RaceswapError::InvalidFeeConfig);
+    Ok(Some(RaceswapConfig::try_deserialize(&mut &config.try_borrow_data()?[..])?))
+}
+
+/// Treasury fee rate, read from the config PDA; 20 bps until it is initialized
+fn treasury_fee_bps(config: &UncheckedAccount) -> Result<u16> {
+    Ok(load_config(config)?.map_or(DEFAULT_TREASURY_FEE_BPS, |config| config.treasury_fee_bps))
+}
+
+/// Treasury wallet SOL fees go to, read from the treasury config PDA; the
+/// built-in treasury until it is initialized
+fn treasury_address(treasury_config: &UncheckedAccount) -> Result<Pubkey> {
+    if treasury_config.data_is_empty() {
+        return Ok(DEFAULT_TREASURY);
+    }
+    require_keys_eq!(*treasury_config.owner, crate::ID, RaceswapError::InvalidTreasuryAccount);
+    let treasury_config =
+        TreasuryConfig::try_deserialize(&mut &treasury_config.try_borrow_data()?[..])?;
+    Ok(treasury_config.treasury)
+}
+
+/// Jupiter program swaps must route through, read from the config PDA; v6
+/// until it is initialized
+fn jupiter_program_id(config: &UncheckedAccount) -> Result<Pubkey> {
+    Ok(load_config(config)?.map_or(JUPITER_PROGRAM_ID, |config| config.jupiter_program))
+}
+
+/// SOL fee split set by the config authority; none (all to the treasury)
+/// until the config is initialized
+fn fee_shares(config: &UncheckedAccount) -> Result<Vec<FeeShare>> {
+    Ok(load_config(config)?.map_or_else(Vec::new, |config| config.fee_shares))
 }
 
 #[derive(Accounts)]
-pub struct ExecuteSwap<'info> {
+pub struct QuoteFee<'info> {
+    /// CHECK: Config PDA, may be uninitialized (the fee then falls back to 20 bps)
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [CONFIG_SEED],
+        bump,
+        space = 8 + RaceswapConfig::LEN
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Raceswap>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ RaceswapError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-    /// CHECK: Treasury wallet - receives SOL fees
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
     #[account(
         mut,
-        address = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L")
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
     )]
+    pub config: Account<'info, RaceswapConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct RaceswapConfig {
+    pub authority: Pubkey,
+    pub treasury_fee_bps: u16,
+    pub bump: u8,
+    /// Jupiter program `execute_swap` routes through
+    pub jupiter_program: Pubkey,
+    /// SOL fee split, treasury first; empty sends the whole fee to the treasury
+    pub fee_shares: Vec<FeeShare>,
+}
+
+impl RaceswapConfig {
+    pub const LEN: usize = 32 + 2 + 1 + 32 + FeeShare::VEC_LEN;
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasuryConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [TREASURY_CONFIG_SEED],
+        bump,
+        space = 8 + TreasuryConfig::LEN
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Raceswap>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ RaceswapError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [TREASURY_CONFIG_SEED],
+        bump = treasury_config.bump,
+        has_one = authority @ RaceswapError::Unauthorized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct TreasuryConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub bump: u8,
+}
+
+impl TreasuryConfig {
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct UpdateConfigParams {
+    pub new_authority: Option<Pubkey>,
+    pub treasury_fee_bps: Option<u16>,
+    pub jupiter_program: Option<Pubkey>,
+    pub fee_shares: Option<Vec<FeeShare>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// User's token account for the output mint; its balance delta across the
+    /// Jupiter CPI is the filled amount checked against `min_out`
+    #[account(mut, token::authority = user)]
+    pub user_destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Config PDA, may be uninitialized (the fee then falls back to 20 bps)
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury config PDA, may be uninitialized (fees then go to the
+    /// built-in treasury)
+    #[account(seeds = [TREASURY_CONFIG_SEED], bump)]
+    pub treasury_config: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet - receives SOL fees; its address and
+    /// system-program owner are checked in the handler
+    #[account(mut)]
     pub treasury: UncheckedAccount<'info>,
 
-    /// CHECK: Jupiter v6 program
-    #[account(address = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"))]
+    /// CHECK: Jupiter program, checked against the config in the handler
     pub jupiter_program: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
+
+    // Required only with `FeeMode::InputToken`: the user's input account, its
+    // mint, the treasury wallet's token account for that mint and the token program
+    #[account(mut)]
+    pub user_input: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub input_mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Referrer wallet, must match `params.referrer` when set
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -89,6 +477,28 @@ pub struct ExecuteSwapParams {
     pub min_out: u64,
     pub jupiter_accounts: Vec<SerializableAccountMeta>,
     pub jupiter_data: Vec<u8>,
+    /// Reject the swap (before any fee is taken) once this unix time has passed
+    pub deadline_unix_ts: Option<i64>,
+    pub fee_mode: FeeMode,
+    /// Partner front-end paid `referrer_fee_bps` of the amount out of the
+    /// treasury fee; its wallet is passed as the `referrer` account
+    pub referrer: Option<Pubkey>,
+    pub referrer_fee_bps: u16,
+    /// Validate everything and report the fee in return data without moving
+    /// funds or invoking Jupiter
+    pub dry_run: bool,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub min_out: u64,
+    pub fee_mode: FeeMode,
+    /// Net of the referrer's share, in lamports or input tokens per `fee_mode`
+    pub treasury_fee: u64,
+    pub referrer: Option<Pubkey>,
+    pub referrer_fee_lamports: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -100,6 +510,16 @@ pub struct SerializableAccountMeta {
 
 #[error_code]
 pub enum RaceswapError {
-    #[msg("Invalid amount")]
-    InvalidAmount,
+    #[msg("Treasury must be a system-owned account")]
+    InvalidTreasuryAccount,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
+    #[msg("Input-token fee mode requires the user input, mint, treasury token account and token program")]
+    MissingFeeTokenAccounts,
+    #[msg("Referrers are only supported for SOL fees")]
+    InvalidFeeMode,
+    #[msg("Referrer account missing or does not match params.referrer")]
+    InvalidReferrer,
 }
@@ -1,19 +1,71 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
-    instruction::{AccountMeta, Instruction},
-    program::{invoke, invoke_signed},
+    instruction::{get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program::{get_return_data, invoke_signed, set_return_data},
+    pubkey::MAX_SEED_LEN,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
 };
 use anchor_lang::system_program;
-use anchor_spl::token_interface::{self, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, SetAuthority, SyncNative, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    instruction::AuthorityType,
+    state::Mint as SplMint,
+};
+use anchor_spl::associated_token::{
+    self, get_associated_token_address_with_program_id, AssociatedToken,
+};
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
 use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
 use anchor_spl::token_2022::ID as TOKEN_2022_PROGRAM_ID;
 use core::slice::Iter;
+use raceswap_common::validation::JUPITER_PROGRAM_ID;
+use raceswap_common::{SwapStatus, MAX_INSTRUCTION_DATA_LEN};
 
 declare_id!("Cy63SzwBBCP5ywaByjUrLuUXQ4pXP9nR7e7kdQqp5uLk");
 
 const CONFIG_SEED: &[u8] = b"raceswap-config";
 const AUTHORITY_SEED: &[u8] = b"raceswap-authority";
+const SESSION_SEED: &[u8] = b"raceswap-session";
+const TREASURY_AUTHORITY_SEED: &[u8] = b"raceswap-treasury";
+const USER_STATS_SEED: &[u8] = b"raceswap-user-stats";
+const REFERRAL_SEED: &[u8] = b"raceswap-referral";
+const MINT_FEE_SEED: &[u8] = b"mint-fee";
+const PROTOCOL_STATS_SEED: &[u8] = b"stats";
+const DENY_LIST_SEED: &[u8] = b"deny-list";
+const BASE_TREASURY_FEE_BPS: u16 = 20;
 const FEE_DENOMINATOR: u128 = 10_000;
+const MAX_MINT_REFLECTION_CONFIGS: usize = 8;
+/// Jupiter instructions always start with an 8-byte Anchor discriminator
+const MIN_LEG_DATA_LEN: usize = 8;
+const MAX_MIGRATION_VAULTS: usize = 8;
+const MAX_VAULT_BATCH: usize = 8;
+/// Fixed-point scale of the realized rate in `SwapRate` (9 decimal places)
+const RATE_SCALE: u128 = 1_000_000_000;
+/// Upper bound on per-leg results so the return data stays well under 1024 bytes
+const MAX_LEG_RESULTS: usize = 8;
+const MAX_REFLECTION_LEGS: usize = 2;
+const MAX_ALLOWED_CALLERS: usize = 8;
+const MAX_MULTISIG_SIGNERS: usize = 8;
+/// Room for the current and next Jupiter program during a migration
+const MAX_JUPITER_PROGRAMS: usize = 2;
+/// `shared_refs` entry meaning "take the leg's next own account"
+const UNIQUE_ACCOUNT_REF: u8 = u8::MAX;
+const MAX_DENIED_MINTS: usize = 16;
+const MAX_PAUSED_MINTS: usize = 8;
+const MAX_REBATE_MINTS: usize = 8;
+/// Pyth pull-oracle receiver, owner of `PriceUpdateV2` accounts
+const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+/// Oldest SOL/USD price a fixed USD fee is priced from until the config says otherwise
+const DEFAULT_MAX_PRICE_AGE_SECS: u64 = 60;
 #[program]
 pub mod raceswap {
     use super::*;
@@ -41,6 +93,42 @@ pub mod raceswap {
         config.treasury_wallet = params.treasury_wallet;
         config.reflection_fee_bps = params.reflection_fee_bps;
         config.treasury_fee_bps = params.treasury_fee_bps;
+        config.default_slippage_bps = 0;
+        config.allow_zero_min_out = true;
+        config.reflection_config_per_mint = Vec::new();
+        config.max_quote_age_slots = 0;
+        config.require_canonical_input_ata = false;
+        config.platform_fee_account = None;
+        config.allowed_callers = Vec::new();
+        config.min_reference_output_bps = 0;
+        config.max_writable_accounts = 0;
+        config.max_reflection_override_bps = 0;
+        config.min_out_tolerance = 0;
+        config.loyalty_fee_threshold = 0;
+        config.loyalty_period_slots = 0;
+        config.loyalty_fee_bps = BASE_TREASURY_FEE_BPS;
+        config.reflection_min_notional = 0;
+        config.max_fee_output_bps = 0;
+        config.in_progress = false;
+        config.multisig_signers = Vec::new();
+        config.multisig_threshold = 0;
+        config.max_slippage_bps = 0;
+        config.paused = false;
+        config.fee_holiday_start_slot = 0;
+        config.fee_holiday_end_slot = 0;
+        config.streak_window_slots = 0;
+        config.streak_bonus_bps = 0;
+        config.max_streak_multiplier_bps = FEE_DENOMINATOR as u16;
+        config.max_input_per_swap = 0;
+        config.jupiter_programs = vec![JUPITER_PROGRAM_ID];
+        config.circuit_breaker_active = false;
+        config.max_price_impact_bps = 0;
+        config.fixed_fee_usd_cents = 0;
+        config.sol_usd_feed_id = [0; 32];
+        config.max_price_age_secs = DEFAULT_MAX_PRICE_AGE_SECS;
+        config.paused_mints = Vec::new();
+        config.max_mints_per_swap = 0;
+        config.rebate_mints = Vec::new();
         config.bump = ctx.bumps.config;
 
         let (_, authority_bump) =
@@ -57,13 +145,18 @@ pub mod raceswap {
         Ok(())
     }
 
-    pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
+    /// With a multisig configured, co-signers beyond `authority` are passed as
+    /// signer remaining_accounts.
+    pub fn update_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        params: UpdateConfigParams,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
         let config = &mut ctx.accounts.config;
-        require_keys_eq!(
-            ctx.accounts.authority.key(),
-            config.authority,
-            RaceswapError::Unauthorized
-        );
 
         if let Some(new_authority) = params.new_authority {
             config.authority = new_authority;
@@ -83,11 +176,203 @@ pub mod raceswap {
             config.treasury_fee_bps = treasury_fee_bps;
         }
 
+        if let Some(default_slippage_bps) = params.default_slippage_bps {
+            require!(
+                (default_slippage_bps as u128) < FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.default_slippage_bps = default_slippage_bps;
+        }
+
+        if let Some(allow_zero_min_out) = params.allow_zero_min_out {
+            config.allow_zero_min_out = allow_zero_min_out;
+        }
+
+        if let Some(max_quote_age_slots) = params.max_quote_age_slots {
+            config.max_quote_age_slots = max_quote_age_slots;
+        }
+
+        if let Some(require_canonical_input_ata) = params.require_canonical_input_ata {
+            config.require_canonical_input_ata = require_canonical_input_ata;
+        }
+
+        if let Some(platform_fee_account) = params.platform_fee_account {
+            config.platform_fee_account = platform_fee_account;
+        }
+
+        if let Some(allowed_callers) = params.allowed_callers {
+            require!(
+                allowed_callers.len() <= MAX_ALLOWED_CALLERS,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.allowed_callers = allowed_callers;
+        }
+
+        if let Some(min_reference_output_bps) = params.min_reference_output_bps {
+            require!(
+                (min_reference_output_bps as u128) <= FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.min_reference_output_bps = min_reference_output_bps;
+        }
+
+        if let Some(max_writable_accounts) = params.max_writable_accounts {
+            config.max_writable_accounts = max_writable_accounts;
+        }
+
+        if let Some(max_reflection_override_bps) = params.max_reflection_override_bps {
+            require!(
+                (max_reflection_override_bps as u128) < FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.max_reflection_override_bps = max_reflection_override_bps;
+        }
+
+        if let Some(min_out_tolerance) = params.min_out_tolerance {
+            config.min_out_tolerance = min_out_tolerance;
+        }
+
+        if let Some(loyalty_fee_threshold) = params.loyalty_fee_threshold {
+            config.loyalty_fee_threshold = loyalty_fee_threshold;
+        }
+
+        if let Some(loyalty_period_slots) = params.loyalty_period_slots {
+            config.loyalty_period_slots = loyalty_period_slots;
+        }
+
+        if let Some(loyalty_fee_bps) = params.loyalty_fee_bps {
+            require!(
+                loyalty_fee_bps <= BASE_TREASURY_FEE_BPS,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.loyalty_fee_bps = loyalty_fee_bps;
+        }
+
+        if let Some(reflection_min_notional) = params.reflection_min_notional {
+            config.reflection_min_notional = reflection_min_notional;
+        }
+
+        if let Some(max_fee_output_bps) = params.max_fee_output_bps {
+            require!(
+                (max_fee_output_bps as u128) <= FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.max_fee_output_bps = max_fee_output_bps;
+        }
+
+        if let Some(max_slippage_bps) = params.max_slippage_bps {
+            require!(
+                (max_slippage_bps as u128) < FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.max_slippage_bps = max_slippage_bps;
+        }
+
+        if let Some(fee_holiday_start_slot) = params.fee_holiday_start_slot {
+            config.fee_holiday_start_slot = fee_holiday_start_slot;
+        }
+
+        if let Some(fee_holiday_end_slot) = params.fee_holiday_end_slot {
+            config.fee_holiday_end_slot = fee_holiday_end_slot;
+        }
+        // 0/0 clears the holiday; any other window must be non-empty
+        require!(
+            (config.fee_holiday_start_slot == 0 && config.fee_holiday_end_slot == 0)
+                || config.fee_holiday_start_slot < config.fee_holiday_end_slot,
+            RaceswapError::InvalidFeeHoliday
+        );
+
+        if let Some(streak_window_slots) = params.streak_window_slots {
+            config.streak_window_slots = streak_window_slots;
+        }
+
+        if let Some(streak_bonus_bps) = params.streak_bonus_bps {
+            config.streak_bonus_bps = streak_bonus_bps;
+        }
+
+        if let Some(max_streak_multiplier_bps) = params.max_streak_multiplier_bps {
+            require!(
+                max_streak_multiplier_bps as u128 >= FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.max_streak_multiplier_bps = max_streak_multiplier_bps;
+        }
+
+        if let Some(max_input_per_swap) = params.max_input_per_swap {
+            config.max_input_per_swap = max_input_per_swap;
+        }
+
+        if let Some(jupiter_programs) = params.jupiter_programs {
+            require!(
+                !jupiter_programs.is_empty() && jupiter_programs.len() <= MAX_JUPITER_PROGRAMS,
+                RaceswapError::InvalidJupiterProgram
+            );
+            config.jupiter_programs = jupiter_programs;
+        }
+
+        if let Some(max_price_impact_bps) = params.max_price_impact_bps {
+            require!(
+                (max_price_impact_bps as u128) <= FEE_DENOMINATOR,
+                RaceswapError::InvalidFeeConfig
+            );
+            config.max_price_impact_bps = max_price_impact_bps;
+        }
+
+        if let Some(max_mints_per_swap) = params.max_mints_per_swap {
+            config.max_mints_per_swap = max_mints_per_swap;
+        }
+
+        if let Some(rebate_mints) = params.rebate_mints {
+            require!(
+                rebate_mints.len() <= MAX_REBATE_MINTS,
+                RaceswapError::InvalidRebateNft
+            );
+            config.rebate_mints = rebate_mints;
+        }
+
+        if let Some(fixed_fee_usd_cents) = params.fixed_fee_usd_cents {
+            config.fixed_fee_usd_cents = fixed_fee_usd_cents;
+        }
+
+        if let Some(sol_usd_feed_id) = params.sol_usd_feed_id {
+            config.sol_usd_feed_id = sol_usd_feed_id;
+        }
+
+        if let Some(max_price_age_secs) = params.max_price_age_secs {
+            config.max_price_age_secs = max_price_age_secs;
+        }
+        // A fixed USD fee must never be priced from an arbitrarily stale price
+        require!(
+            config.fixed_fee_usd_cents == 0 || config.max_price_age_secs > 0,
+            RaceswapError::InvalidFeeConfig
+        );
+
+        if let Some(multisig_signers) = params.multisig_signers {
+            config.multisig_signers = multisig_signers;
+        }
+
+        if let Some(multisig_threshold) = params.multisig_threshold {
+            config.multisig_threshold = multisig_threshold;
+        }
+        validate_multisig(&config.multisig_signers, config.multisig_threshold)?;
+
+        if let Some(reflection_config_per_mint) = params.reflection_config_per_mint {
+            validate_mint_reflection_configs(&reflection_config_per_mint)?;
+            config.reflection_config_per_mint = reflection_config_per_mint;
+        }
+
         require!(
             (config.reflection_fee_bps as u32 + config.treasury_fee_bps as u32)
                 < FEE_DENOMINATOR as u32,
             RaceswapError::InvalidFeeConfig
         );
+        for entry in config.reflection_config_per_mint.iter() {
+            require!(
+                (entry.reflection_fee_bps as u32 + config.treasury_fee_bps as u32)
+                    < FEE_DENOMINATOR as u32,
+                RaceswapError::InvalidFeeConfig
+            );
+        }
 
         emit!(ConfigUpdated {
             authority: config.authority,
@@ -99,354 +384,3013 @@ pub mod raceswap {
         Ok(())
     }
 
-    pub fn execute_raceswap<'info>(
-        ctx: Context<'_, '_, '_, 'info, ExecuteRaceswap<'info>>,
-        params: ExecuteRaceswapParams,
+    /// Creates or updates the treasury fee override for `mint`. Swaps that pass
+    /// the override PDA pay this rate instead of the default.
+    pub fn set_mint_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMintFee<'info>>,
+        mint: Pubkey,
+        treasury_fee_bps: u16,
     ) -> Result<()> {
-        msg!("ExecuteRaceswap: total_in={}, min_main={}, min_refl={}, disable_refl={}", 
-            params.total_input_amount,
-            params.min_main_out,
-            params.min_reflection_out,
-            params.disable_reflection
-        );
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
 
-        // DEBUG: Log account details to debug InvalidAccountData issues
-        msg!(">>> RACESWAP DEBUG: input_mint = {}", ctx.accounts.input_mint.key());
-        msg!(">>> RACESWAP DEBUG: input_mint.owner = {}", ctx.accounts.input_mint.to_account_info().owner);
-        msg!(
-            ">>> RACESWAP DEBUG: token_program = {}",
-            ctx.accounts.input_token_program.key()
-        );
+        let mint_fee = &mut ctx.accounts.mint_fee;
+        mint_fee.mint = mint;
+        mint_fee.treasury_fee_bps = treasury_fee_bps;
+        mint_fee.bump = ctx.bumps.mint_fee;
+        msg!("Mint fee override: mint={}, treasury_fee_bps={}", mint, treasury_fee_bps);
+        Ok(())
+    }
+
+    /// Pauses or resumes swaps into or out of `mint` alone, for token-specific
+    /// incidents that do not warrant `set_pause`.
+    pub fn set_mint_paused<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        mint: Pubkey,
+        paused: bool,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        let config = &mut ctx.accounts.config;
+        let position = config.paused_mints.iter().position(|paused_mint| *paused_mint == mint);
+        match (paused, position) {
+            (true, None) => {
+                require!(
+                    config.paused_mints.len() < MAX_PAUSED_MINTS,
+                    RaceswapError::TooManyPausedMints
+                );
+                config.paused_mints.push(mint);
+            }
+            (false, Some(position)) => {
+                config.paused_mints.swap_remove(position);
+            }
+            _ => {}
+        }
+        msg!("Mint {} paused: {}", mint, paused);
+        Ok(())
+    }
+
+    /// Circuit breaker for turbulent markets: while active, every swap must
+    /// quote `expected_out` and stay within `max_price_impact_bps` of it.
+    pub fn set_circuit_breaker<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        active: bool,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        let config = &mut ctx.accounts.config;
+        config.circuit_breaker_active = active;
         msg!(
-            ">>> RACESWAP DEBUG: user_input = {}",
-            ctx.accounts.user_input.key()
+            "Circuit breaker active: {} (max_price_impact_bps {})",
+            active,
+            config.max_price_impact_bps
         );
+        Ok(())
+    }
 
-        // Runtime check for input mint ownership to support both Token and Token2022
-        if *ctx.accounts.input_mint.to_account_info().owner != ctx.accounts.input_token_program.key() {
-             msg!("Input mint owner mismatch! Expected {}", ctx.accounts.input_token_program.key());
-             return err!(RaceswapError::InvalidInputMintOwner);
-        }
+    /// Blocks swaps touching `mint` for callers that pass the deny-list.
+    pub fn add_denied_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, ManageDenyList<'info>>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
 
-        // Manual deserialization of input_mint to avoid InterfaceAccount strictness
-        // We strictly read the byte layout of an SPL Token Mint to get decimals.
-        // Layout: [MintAuthority(36)] [Supply(8)] [Decimals(1)] ...
-        let input_mint_info = ctx.accounts.input_mint.to_account_info();
-        let input_mint_data = input_mint_info.try_borrow_data()?;
-        
-        if input_mint_data.len() < 82 {
-             return err!(RaceswapError::InvalidInputMint);
-        }
-        
-        let decimals = input_mint_data[44];
+        let deny_list = &mut ctx.accounts.deny_list;
+        require!(
+            !deny_list.mints.contains(&mint) && deny_list.mints.len() < MAX_DENIED_MINTS,
+            RaceswapError::InvalidDenyListUpdate
+        );
+        deny_list.mints.push(mint);
+        deny_list.bump = ctx.bumps.deny_list;
+        msg!("Denied mint added: {} ({} total)", mint, deny_list.mints.len());
+        Ok(())
+    }
 
-        require!(params.total_input_amount > 0, RaceswapError::InvalidAmount);
+    pub fn remove_denied_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, ManageDenyList<'info>>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
 
-        let input_mint_key = params.input_mint;
-        let main_output_mint_key = params.main_output_mint;
-        let reflection_mint_key = params.reflection_mint;
+        let deny_list = &mut ctx.accounts.deny_list;
+        let position = deny_list
+            .mints
+            .iter()
+            .position(|denied| *denied == mint)
+            .ok_or(RaceswapError::InvalidDenyListUpdate)?;
+        deny_list.mints.swap_remove(position);
+        msg!("Denied mint removed: {} ({} total)", mint, deny_list.mints.len());
+        Ok(())
+    }
+
+    /// Kill switch: while paused, `execute_raceswap` rejects new swaps. Config
+    /// updates and unpausing keep working.
+    pub fn set_pause<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
+        paused: bool,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+        msg!("Swaps paused: {}", paused);
+        Ok(())
+    }
 
+    /// Creates the swap_authority PDA's vault ATA for each mint. Mints and their
+    /// vaults are passed as `[mint, vault]` pairs in remaining_accounts, after
+    /// any multisig co-signers; vaults that already exist are skipped so the
+    /// batch can be retried safely.
+    pub fn create_input_vaults_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateInputVaultsBatch<'info>>,
+    ) -> Result<()> {
         let config = &ctx.accounts.config;
+        let (co_signers, pairs) = split_co_signers(ctx.remaining_accounts);
+        check_config_authority(config, &ctx.accounts.authority, co_signers)?;
+        require!(
+            !pairs.is_empty() && pairs.len().is_multiple_of(2) && pairs.len() / 2 <= MAX_VAULT_BATCH,
+            RaceswapError::InvalidVaultBatch
+        );
+
         let config_key = config.key();
         let authority_signer_seeds: [&[u8]; 3] = [
             AUTHORITY_SEED,
             config_key.as_ref(),
             &[config.authority_bump],
         ];
-        
-        // Derive swap_authority PDA (not included in named accounts to prevent auto-signer privilege)
-        let (swap_authority_derived, _bump) = Pubkey::find_program_address(
-            &[AUTHORITY_SEED, config_key.as_ref()],
-            ctx.program_id
-        );
-        
-        // Verify input_vault is owned by swap_authority
-        require_keys_eq!(
-            ctx.accounts.input_vault.owner,
-            swap_authority_derived,
-            RaceswapError::InvalidVaultOwner
-        );
-        
-        let total_fee_bps = config.reflection_fee_bps as u128 + config.treasury_fee_bps as u128;
-        require!(
-            total_fee_bps < FEE_DENOMINATOR,
-            RaceswapError::InvalidFeeConfig
-        );
+        let token_program_key = ctx.accounts.token_program.key();
 
-        let reflection_enabled = !params.disable_reflection;
-        let mut reflection_required = reflection_enabled;
+        for pair in pairs.chunks(2) {
+            let (mint_info, vault_info) = (&pair[0], &pair[1]);
+            let expected_vault = get_associated_token_address_with_program_id(
+                &ctx.accounts.swap_authority.key(),
+                mint_info.key,
+                &token_program_key,
+            );
+            require_keys_eq!(vault_info.key(), expected_vault, RaceswapError::InvalidVaultBatch);
 
-        require_keys_eq!(
-            ctx.accounts.user_main_destination.mint,
-            main_output_mint_key,
-            RaceswapError::InvalidMainAccount
-        );
-        require_keys_eq!(
-            ctx.accounts.user_main_destination.owner,
-            ctx.accounts.user.key(),
-            RaceswapError::InvalidMainAccount
-        );
+            if !vault_info.data_is_empty() {
+                msg!("Vault {} already exists, skipping", vault_info.key());
+                continue;
+            }
 
-        let main_dest_info = ctx.accounts.user_main_destination.to_account_info();
-        msg!("Output Main: key={}, owner={}, mint={}", 
-            main_dest_info.key(), 
-            main_dest_info.owner, 
-            ctx.accounts.user_main_destination.mint
+            associated_token::create_idempotent(CpiContext::new_with_signer(
+                ctx.accounts.associated_token_program.to_account_info(),
+                associated_token::Create {
+                    payer: ctx.accounts.authority.to_account_info(),
+                    associated_token: vault_info.clone(),
+                    authority: ctx.accounts.swap_authority.to_account_info(),
+                    mint: mint_info.clone(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                &[&authority_signer_seeds],
+            ))?;
+            msg!("Created vault {} for mint {}", vault_info.key(), mint_info.key());
+        }
+
+        Ok(())
+    }
+
+    /// Re-assigns vault ownership from the current swap_authority PDA to the PDA
+    /// derived from `new_authority_seed`. Vaults are passed as writable
+    /// remaining_accounts, after any multisig co-signers. Run this before
+    /// deploying code that uses the new seed.
+    pub fn migrate_authority_seed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateAuthoritySeed<'info>>,
+        new_authority_seed: Vec<u8>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let (co_signers, vaults) = split_co_signers(ctx.remaining_accounts);
+        check_config_authority(config, &ctx.accounts.authority, co_signers)?;
+        require!(
+            !new_authority_seed.is_empty() && new_authority_seed != AUTHORITY_SEED,
+            RaceswapError::InvalidAuthoritySeed
         );
+        // find_program_address panics on an over-long seed; fail cleanly instead
         require!(
-            *main_dest_info.owner == TOKEN_PROGRAM_ID || *main_dest_info.owner == TOKEN_2022_PROGRAM_ID,
-            RaceswapError::InvalidMainAccount
+            new_authority_seed.len() <= MAX_SEED_LEN,
+            RaceswapError::InvalidAuthoritySeed
         );
-
-        require_keys_eq!(
-            ctx.accounts.input_vault.mint,
-            input_mint_key,
-            RaceswapError::InvalidVaultMint
+        require!(
+            !vaults.is_empty() && vaults.len() <= MAX_MIGRATION_VAULTS,
+            RaceswapError::InvalidAuthoritySeed
         );
 
+        let config_key = config.key();
+        let (old_authority, _) =
+            Pubkey::find_program_address(&[AUTHORITY_SEED, config_key.as_ref()], ctx.program_id);
         require_keys_eq!(
-            ctx.accounts.user_input.mint,
-            input_mint_key,
-            RaceswapError::InvalidUserSource
+            ctx.accounts.old_swap_authority.key(),
+            old_authority,
+            RaceswapError::InvalidVaultOwner
         );
-        require_keys_eq!(
-            ctx.accounts.user_input.owner,
-            ctx.accounts.user.key(),
-            RaceswapError::InvalidUserSource
+        let (new_authority, _) = Pubkey::find_program_address(
+            &[new_authority_seed.as_slice(), config_key.as_ref()],
+            ctx.program_id,
         );
+        let authority_signer_seeds: [&[u8]; 3] = [
+            AUTHORITY_SEED,
+            config_key.as_ref(),
+            &[config.authority_bump],
+        ];
 
-        let reflection_amount = if !reflection_enabled {
-            0u64
-        } else {
-            ((params.total_input_amount as u128)
-                .checked_mul(config.reflection_fee_bps as u128)
-                .ok_or(RaceswapError::MathOverflow)?)
-            .checked_div(FEE_DENOMINATOR)
-            .ok_or(RaceswapError::MathOverflow)? as u64
-        };
-
-        if reflection_amount == 0 {
-            reflection_required = false;
-        }
-
-        if reflection_required {
-            require_keys_eq!(
-                ctx.accounts.user_reflection_destination.mint,
-                reflection_mint_key,
-                RaceswapError::InvalidReflectionAccount
-            );
+        for vault_info in vaults.iter() {
+            let vault = InterfaceAccount::<TokenAccount>::try_from(vault_info)?;
+            require_keys_eq!(vault.owner, old_authority, RaceswapError::InvalidVaultOwner);
             require_keys_eq!(
-                ctx.accounts.user_reflection_destination.owner,
-                ctx.accounts.user.key(),
-                RaceswapError::InvalidReflectionAccount
+                *vault_info.owner,
+                ctx.accounts.token_program.key(),
+                RaceswapError::InvalidVaultOwner
             );
 
-            let refl_dest_info = ctx.accounts.user_reflection_destination.to_account_info();
-            msg!("Output Refl: key={}, owner={}, mint={}", 
-                refl_dest_info.key(), 
-                refl_dest_info.owner, 
-                ctx.accounts.user_reflection_destination.mint
-            );
-            require!(
-                *refl_dest_info.owner == TOKEN_PROGRAM_ID || *refl_dest_info.owner == TOKEN_2022_PROGRAM_ID,
-                RaceswapError::InvalidReflectionAccount
-            );
+            token_interface::set_authority(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    SetAuthority {
+                        current_authority: ctx.accounts.old_swap_authority.to_account_info(),
+                        account_or_mint: vault_info.clone(),
+                    },
+                    &[&authority_signer_seeds],
+                ),
+                AuthorityType::AccountOwner,
+                Some(new_authority),
+            )?;
+            msg!("Vault {} migrated: {} -> {}", vault_info.key(), old_authority, new_authority);
         }
 
-        // Calculate treasury fee in SOL
-        let treasury_fee_lamports = (params.total_input_amount as u64)
-            .checked_mul(20)           // 0.2%
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap();
+        Ok(())
+    }
 
-        params
-            .total_input_amount
-            .checked_sub(reflection_amount)
-            .ok_or(RaceswapError::MathOverflow)?;
+    pub fn register_session(
+        ctx: Context<RegisterSession>,
+        session_key: Pubkey,
+        expires_at_slot: u64,
+    ) -> Result<()> {
+        require!(
+            expires_at_slot > Clock::get()?.slot,
+            RaceswapError::SessionExpired
+        );
 
-        // Transfer total input from user to vault
-        token_interface::transfer_checked(
-            CpiContext::new(
-                ctx.accounts.input_token_program.to_account_info(),
+        let session = &mut ctx.accounts.session;
+        session.user = ctx.accounts.user.key();
+        session.session_key = session_key;
+        session.expires_at_slot = expires_at_slot;
+        session.bump = ctx.bumps.session;
+
+        msg!(
+            "Session registered: user={}, session_key={}, expires_at_slot={}",
+            session.user,
+            session.session_key,
+            session.expires_at_slot
+        );
+
+        Ok(())
+    }
+
+    pub fn init_user_stats(ctx: Context<InitUserStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.user_stats;
+        stats.user = ctx.accounts.user.key();
+        stats.period_start_slot = Clock::get()?.slot;
+        stats.period_fees = 0;
+        stats.bump = ctx.bumps.user_stats;
+        stats.last_swap_slot = 0;
+        stats.streak = 0;
+        Ok(())
+    }
+
+    /// Creates the global lifetime counters updated by `execute_raceswap`.
+    pub fn init_protocol_stats(ctx: Context<InitProtocolStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.protocol_stats;
+        stats.total_swaps = 0;
+        stats.total_treasury_lamports = 0;
+        stats.total_treasury_tokens = 0;
+        stats.total_volume = 0;
+        stats.bump = ctx.bumps.protocol_stats;
+        Ok(())
+    }
+
+    /// Permissionless checkpoint of the lifetime totals for indexers.
+    pub fn snapshot_stats(ctx: Context<SnapshotStats>) -> Result<()> {
+        let stats = &ctx.accounts.protocol_stats;
+        emit!(StatsSnapshot {
+            total_volume: stats.total_volume,
+            total_fees_collected: stats.total_treasury_lamports,
+            swap_nonce: stats.total_swaps,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Registers a referral code. Only the config authority (or its multisig)
+    /// can set the referrer and their share of the treasury fee.
+    pub fn register_referral<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterReferral<'info>>,
+        code: [u8; 8],
+        referrer: Pubkey,
+        fee_share_bps: u16,
+    ) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        require!(
+            (fee_share_bps as u128) <= FEE_DENOMINATOR,
+            RaceswapError::InvalidFeeConfig
+        );
+
+        let referral = &mut ctx.accounts.referral;
+        referral.code = code;
+        referral.referrer = referrer;
+        referral.fee_share_bps = fee_share_bps;
+        referral.bump = ctx.bumps.referral;
+        msg!("Referral registered: referrer={}, fee_share_bps={}", referrer, fee_share_bps);
+        Ok(())
+    }
+
+    /// Revokes a session key. The `close = user` constraint on the session PDA
+    /// does the revoking; there is nothing left for the handler to do.
+    pub fn revoke_session(_ctx: Context<RevokeSession>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Closes the config PDA and returns its rent to the signing authority.
+    /// Refused while a swap has the config marked in progress.
+    pub fn close_config<'info>(ctx: Context<'_, '_, '_, 'info, CloseConfig<'info>>) -> Result<()> {
+        check_config_authority(
+            &ctx.accounts.config,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+        require!(!ctx.accounts.config.in_progress, RaceswapError::ConfigInUse);
+        msg!(
+            "Closing config, {} lamports to {}",
+            ctx.accounts.config.to_account_info().lamports(),
+            ctx.accounts.authority.key()
+        );
+        Ok(())
+    }
+
+    /// Moves accumulated in-token fees out of the treasury_authority PDA's ATA
+    /// into a token account owned by the configured treasury wallet.
+    pub fn sweep_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, SweepFees<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        check_config_authority(config, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(amount > 0, RaceswapError::InvalidAmount);
+
+        let config_key = config.key();
+        let treasury_signer_seeds: [&[u8]; 3] = [
+            TREASURY_AUTHORITY_SEED,
+            config_key.as_ref(),
+            &[ctx.bumps.treasury_authority],
+        ];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
-                    from: ctx.accounts.user_input.to_account_info(),
-                    mint: ctx.accounts.input_mint.to_account_info(),
-                    to: ctx.accounts.input_vault.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury_authority.to_account_info(),
                 },
+                &[&treasury_signer_seeds],
             ),
-            params.total_input_amount,
-            decimals,
+            amount,
+            ctx.accounts.mint.decimals,
         )?;
+        msg!("Swept {} fee tokens to {}", amount, ctx.accounts.destination.key());
+        Ok(())
+    }
 
-        // Pay treasury fee in SOL
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: ctx.accounts.treasury_fee_destination.to_account_info(),
-                },
-            ),
-            treasury_fee_lamports,
+    /// Returns the reflection/main/treasury split `execute_raceswap` would use
+    /// for these params, as a borsh-encoded `SwapSplit` in return data.
+    pub fn preview_split(ctx: Context<PreviewSplit>, params: PreviewSplitParams) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let below_reflection_notional = config.reflection_min_notional > 0
+            && params.total_input_amount < config.reflection_min_notional;
+        let reflect_from_input = !params.disable_reflection
+            && !below_reflection_notional
+            && params.reflection_mode == ReflectionMode::Input;
+
+        // Resolve against a copy: the preview must not advance the user's
+        // loyalty period or streak
+        let mut user_stats = ctx.accounts.user_stats.as_deref().cloned();
+        let ResolvedSplit { split, .. } = resolve_split(
+            config,
+            SplitRequest {
+                user: params.user,
+                total_input_amount: params.total_input_amount,
+                reflection_fee_bps: config.reflection_fee_bps_for(&params.input_mint),
+                reflect_from_input,
+                reflection_amount_override: params.reflection_amount_override,
+                fee_in_input_token: params.fee_in_input_token,
+                mint_fee: ctx.accounts.mint_fee.as_deref(),
+                user_stats: user_stats.as_mut(),
+                rebate_nft: ctx.accounts.rebate_nft.as_deref(),
+                sol_usd_price: ctx.accounts.sol_usd_price.as_deref(),
+            },
+            Clock::get()?.slot,
         )?;
+        msg!(
+            "PreviewSplit: reflection={}, main={}, treasury_fee={}, treasury_fee_tokens={}",
+            split.reflection_amount,
+            split.main_input_amount,
+            split.treasury_fee_lamports,
+            split.treasury_fee_tokens
+        );
+        set_return_data(&split.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Returns the role of every remaining account `execute_raceswap` expects
+    /// for these params, in order, as a borsh-encoded `AccountLayout`: shared
+    /// accounts, then each leg's own accounts in execution order (reflection
+    /// legs before main in input mode, after it in output mode), then the
+    /// top-up leg's.
+    pub fn describe_account_layout(
+        _ctx: Context<DescribeAccountLayout>,
+        params: ExecuteRaceswapParams,
+    ) -> Result<()> {
+        let mut roles = vec![AccountRole::Shared; params.shared_accounts_len as usize];
+        let reflection_roles = params.reflection_legs.iter().enumerate().flat_map(|(index, leg)| {
+            std::iter::repeat_n(
+                AccountRole::Reflection { leg: index as u8 },
+                LegAccounts::unique_needed(&leg.instruction),
+            )
+        });
+        let main_len = params.main_leg.as_ref().map_or(0, LegAccounts::unique_needed);
+
+        if params.reflection_mode == ReflectionMode::Output {
+            roles.extend(std::iter::repeat_n(AccountRole::Main, main_len));
+            roles.extend(reflection_roles);
+        } else {
+            roles.extend(reflection_roles);
+            roles.extend(std::iter::repeat_n(AccountRole::Main, main_len));
+        }
+        if let Some(topup_leg) = params.topup_leg.as_ref() {
+            roles.extend(std::iter::repeat_n(AccountRole::Topup, LegAccounts::unique_needed(topup_leg)));
+        }
+
+        let layout = AccountLayout {
+            total: u16::try_from(roles.len()).map_err(|_| RaceswapError::AccountMismatch)?,
+            roles,
+        };
+        msg!("Account layout: {} remaining accounts", layout.total);
+        set_return_data(&layout.try_to_vec()?);
+        Ok(())
+    }
+
+    pub fn execute_raceswap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteRaceswap<'info>>,
+        params: ExecuteRaceswapParams,
+    ) -> Result<()> {
+        msg!("ExecuteRaceswap: total_in={}, min_main={}, refl_legs={}, disable_refl={}", 
+            params.total_input_amount,
+            params.min_main_out,
+            params.reflection_legs.len(),
+            params.disable_reflection
+        );
+
+        require!(!ctx.accounts.config.paused, RaceswapError::ProgramPaused);
+        require!(
+            !ctx.accounts.config.paused_mints.contains(&params.input_mint)
+                && !ctx.accounts.config.paused_mints.contains(&params.main_output_mint),
+            RaceswapError::MintPaused
+        );
+
+        if let Some(deny_list) = ctx.accounts.deny_list.as_ref() {
+            let denied = core::iter::once(&params.input_mint)
+                .chain(core::iter::once(&params.main_output_mint))
+                .chain(core::iter::once(&params.reflection_mint))
+                .chain(params.reflection_legs.iter().map(|leg| &leg.mint))
+                .find(|mint| deny_list.mints.contains(mint));
+            if let Some(mint) = denied {
+                msg!("Mint {} is on the deny-list", mint);
+                return err!(RaceswapError::MintDenied);
+            }
+        }
+
+        // Guard against a Jupiter route calling back into execute_raceswap
+        // before our balance deltas are measured. The flag is persisted before
+        // any CPI so a nested invocation reads it from account data; any error
+        // reverts it along with the rest of the transaction.
+        require!(
+            !ctx.accounts.config.in_progress,
+            RaceswapError::ReentrancyDetected
+        );
+        ctx.accounts.config.in_progress = true;
+        ctx.accounts.config.exit(ctx.program_id)?;
 
-        // Reflection swap leg
-        let mut remaining_iter = ctx.remaining_accounts.iter();
+        // DEBUG: Log account details to debug InvalidAccountData issues
+        msg!(">>> RACESWAP DEBUG: input_mint = {}", ctx.accounts.input_mint.key());
+        msg!(">>> RACESWAP DEBUG: input_mint.owner = {}", ctx.accounts.input_mint.to_account_info().owner);
+        msg!(
+            ">>> RACESWAP DEBUG: token_program = {}",
+            ctx.accounts.input_token_program.key()
+        );
+        msg!(
+            ">>> RACESWAP DEBUG: user_input = {}",
+            ctx.accounts.user_input.key()
+        );
 
-        let mut reflection_received: u64 = 0;
+        // Runtime check for input mint ownership to support both Token and Token2022
+        if *ctx.accounts.input_mint.to_account_info().owner != ctx.accounts.input_token_program.key() {
+             msg!("Input mint owner mismatch! Expected {}", ctx.accounts.input_token_program.key());
+             return err!(RaceswapError::InvalidInputMintOwner);
+        }
 
-        if reflection_required {
-            let reflection_leg = params
-                .reflection_leg
+        // SOL input: the user's WSOL ATA is created here when missing, and then
+        // closed again at the end of the swap so it never outlives it
+        let native_input = params.input_mint == NATIVE_MINT;
+        let wsol_created = native_input && ctx.accounts.user_input.data_is_empty();
+        if wsol_created {
+            require!(ctx.accounts.user.is_signer, RaceswapError::UserSignatureRequired);
+            let wsol_ata = get_associated_token_address_with_program_id(
+                &ctx.accounts.user.key(),
+                &NATIVE_MINT,
+                &ctx.accounts.input_token_program.key(),
+            );
+            require_keys_eq!(
+                ctx.accounts.user_input.key(),
+                wsol_ata,
+                RaceswapError::InvalidUserSource
+            );
+            let associated_token_program = ctx
+                .accounts
+                .associated_token_program
                 .as_ref()
-                .ok_or(RaceswapError::MissingReflectionLeg)?;
-
-            let before = ctx.accounts.user_reflection_destination.amount;
-            perform_jupiter_swap(
-                reflection_leg,
-                ctx.accounts.jupiter_program.to_account_info(),
-                &mut remaining_iter,
-                &authority_signer_seeds,
-                &swap_authority_derived,
-            )?;
-            ctx.accounts.user_reflection_destination.reload()?;
-            let after = ctx.accounts.user_reflection_destination.amount;
-            let delta = after
-                .checked_sub(before)
-                .ok_or(RaceswapError::InvalidReflectionAccounting)?;
-            require!(
-                delta >= params.min_reflection_out,
-                RaceswapError::ReflectionBelowMinOut
+                .ok_or(RaceswapError::MissingAssociatedTokenProgram)?;
+            associated_token::create_idempotent(CpiContext::new(
+                associated_token_program.to_account_info(),
+                associated_token::Create {
+                    payer: ctx.accounts.user.to_account_info(),
+                    associated_token: ctx.accounts.user_input.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                    mint: ctx.accounts.input_mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: ctx.accounts.input_token_program.to_account_info(),
+                },
+            ))?;
+            msg!("Created WSOL input account {}", wsol_ata);
+        }
+
+        let user_input_program = *ctx.accounts.user_input.to_account_info().owner;
+        if user_input_program != ctx.accounts.input_token_program.key() {
+            msg!(
+                "user_input is owned by {} but input_token_program is {}",
+                user_input_program,
+                ctx.accounts.input_token_program.key()
             );
-            require!(delta > 0, RaceswapError::ReflectionBelowMinOut);
-            reflection_received = delta;
+            return err!(RaceswapError::InputTokenProgramMismatch);
+        }
+        let mut user_input = load_token_account(&ctx.accounts.user_input)?;
+
+        // Manual deserialization of input_mint to avoid InterfaceAccount strictness
+        // We strictly read the byte layout of an SPL Token Mint to get decimals.
+        // Layout: [MintAuthority(36)] [Supply(8)] [Decimals(1)] ...
+        let input_mint_info = ctx.accounts.input_mint.to_account_info();
+        let input_mint_data = input_mint_info.try_borrow_data()?;
+        
+        if input_mint_data.len() < 82 {
+             return err!(RaceswapError::InvalidInputMint);
+        }
+        
+        let decimals = input_mint_data[44];
+
+        // "Swap my whole balance": the amount is read from user_input on-chain and
+        // reflection/fees are computed off that actual balance
+        let total_input_amount = if params.amount_is_max {
+            require!(params.total_input_amount == 0, RaceswapError::InvalidAmount);
+            msg!("amount_is_max: swapping full balance {}", user_input.amount);
+            user_input.amount
         } else {
+            params.total_input_amount
+        };
+        require!(total_input_amount > 0, RaceswapError::InvalidAmount);
+        // Log the cap so front-ends can show the limit alongside the error
+        let max_input_per_swap = ctx.accounts.config.max_input_per_swap;
+        if max_input_per_swap > 0 && total_input_amount > max_input_per_swap {
+            msg!(
+                "Input {} exceeds max_input_per_swap {}",
+                total_input_amount,
+                max_input_per_swap
+            );
+            return err!(RaceswapError::InputExceedsMax);
+        }
+
+        // Reject obviously truncated payloads before any funds move
+        if let Some(main_leg) = params.main_leg.as_ref() {
+            main_leg.validate()?;
+        }
+        require!(
+            params.reflection_legs.len() <= MAX_REFLECTION_LEGS,
+            RaceswapError::InvalidReflectionLegs
+        );
+        for reflection_leg in params.reflection_legs.iter() {
+            reflection_leg.instruction.validate()?;
+        }
+        if let Some(topup_leg) = params.topup_leg.as_ref() {
+            topup_leg.validate()?;
+        }
+
+        let input_mint_key = params.input_mint;
+        let main_output_mint_key = params.main_output_mint;
+        let mut reflection_mint_key = params.reflection_mint;
+
+        let config = &ctx.accounts.config;
+
+        check_caller_allowed(
+            &config.allowed_callers,
+            ctx.accounts.instructions_sysvar.as_ref(),
+        )?;
+        require!(
+            config.jupiter_programs.contains(&ctx.accounts.jupiter_program.key())
+                && ctx.accounts.jupiter_program.executable,
+            RaceswapError::InvalidJupiterProgram
+        );
+        // Fail before any fee moves rather than mid-CPI on the compute budget
+        if params.max_accounts > 0 {
+            msg!(
+                "Route accounts: {} (max {})",
+                ctx.remaining_accounts.len(),
+                params.max_accounts
+            );
             require!(
-                params.reflection_leg.is_none(),
-                RaceswapError::UnexpectedReflectionLeg
+                ctx.remaining_accounts.len() <= params.max_accounts as usize,
+                RaceswapError::RouteTooLarge
+            );
+        }
+        // Solana's writable-account and lock limits are likewise checked up front
+        if config.max_writable_accounts > 0 {
+            let named_writable = [
+                ctx.accounts.config.to_account_info(),
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.user_input.to_account_info(),
+                ctx.accounts.user_main_destination.to_account_info(),
+                ctx.accounts.user_reflection_destination.to_account_info(),
+                ctx.accounts.treasury_wallet.to_account_info(),
+                ctx.accounts.treasury_fee_destination.to_account_info(),
+                ctx.accounts.input_vault.to_account_info(),
+            ];
+            let second_reflection = ctx
+                .accounts
+                .second_reflection_destination
+                .as_ref()
+                .map(|destination| destination.to_account_info());
+            let community = ctx
+                .accounts
+                .community_destination
+                .as_ref()
+                .map(|destination| destination.to_account_info());
+            let session_key = ctx
+                .accounts
+                .session_key
+                .as_ref()
+                .map(|session_key| session_key.to_account_info());
+            let writable = count_distinct_writable(
+                named_writable
+                    .iter()
+                    .chain(second_reflection.iter())
+                    .chain(community.iter())
+                    .chain(session_key.iter())
+                    .chain(ctx.remaining_accounts),
+            );
+            msg!("Distinct writable accounts: {}", writable);
+            require!(
+                writable <= config.max_writable_accounts as usize,
+                RaceswapError::TooManyWritableAccounts
+            );
+        }
+        if config.max_mints_per_swap > 0 {
+            let mints = count_distinct_token_mints(ctx.remaining_accounts.iter());
+            msg!("Distinct mints in route: {}", mints);
+            require!(
+                mints <= config.max_mints_per_swap as usize,
+                RaceswapError::TooManyMints
             );
         }
 
-        // Main swap leg
-        let main_leg = params.main_leg.ok_or(RaceswapError::MissingMainLeg)?;
-        let main_before = ctx.accounts.user_main_destination.amount;
-        perform_jupiter_swap(
-            &main_leg,
-            ctx.accounts.jupiter_program.to_account_info(),
-            &mut remaining_iter,
-            &authority_signer_seeds,
-            &swap_authority_derived,
+        // Per-mint reflection config overrides the params-supplied reflection target
+        let reflection_fee_bps = config.reflection_fee_bps_for(&input_mint_key);
+        if let Some(entry) = config.reflection_config_for(&input_mint_key) {
+            if entry.reflection_mint != reflection_mint_key {
+                msg!(
+                    "Reflection mint overridden by per-mint config: {} -> {}",
+                    reflection_mint_key,
+                    entry.reflection_mint
+                );
+            }
+            reflection_mint_key = entry.reflection_mint;
+        }
+        // Catch reflection routes encoded for the wrong output mint; only the
+        // first leg is bound to the (possibly per-mint) reflection mint
+        if let Some(first) = params.reflection_legs.first() {
+            require_keys_eq!(
+                first.mint,
+                reflection_mint_key,
+                RaceswapError::ReflectionMintMismatch
+            );
+        }
+        // A second leg delivers its own mint into second_reflection_destination
+        if let Some(second) = params.reflection_legs.get(1) {
+            let destination = ctx
+                .accounts
+                .second_reflection_destination
+                .as_ref()
+                .ok_or(RaceswapError::InvalidReflectionAccount)?;
+            validate_reflection_destination(destination, &second.mint, &ctx.accounts.user.key())?;
+            require!(
+                destination.key() != ctx.accounts.user_main_destination.key()
+                    && destination.key() != ctx.accounts.user_reflection_destination.key(),
+                RaceswapError::InvalidReflectionAccount
+            );
+        }
+        let config_key = config.key();
+        let authority_signer_seeds: [&[u8]; 3] = [
+            AUTHORITY_SEED,
+            config_key.as_ref(),
+            &[config.authority_bump],
+        ];
+        
+        // Derive swap_authority PDA (not included in named accounts to prevent auto-signer privilege)
+        let (swap_authority_derived, _bump) = Pubkey::find_program_address(
+            &[AUTHORITY_SEED, config_key.as_ref()],
+            ctx.program_id
+        );
+        
+        // Verify input_vault is owned by swap_authority
+        require_keys_eq!(
+            ctx.accounts.input_vault.owner,
+            swap_authority_derived,
+            RaceswapError::InvalidVaultOwner
+        );
+        
+        let total_fee_bps = reflection_fee_bps as u128 + config.treasury_fee_bps as u128;
+        require!(
+            total_fee_bps < FEE_DENOMINATOR,
+            RaceswapError::InvalidFeeConfig
+        );
+
+        check_quote_age(params.quote_slot, config.max_quote_age_slots)?;
+
+        // Either leg may express its floor as bps below its own quote instead
+        let min_main_out = leg_floor(
+            params.min_main_out,
+            params.quoted_out,
+            params.main_slippage_bps,
         )?;
-        ctx.accounts.user_main_destination.reload()?;
-        let main_after = ctx.accounts.user_main_destination.amount;
-        let main_delta = main_after
-            .checked_sub(main_before)
-            .ok_or(RaceswapError::InvalidMainAccounting)?;
+        let min_reflection_out = leg_floor(
+            params.reflection_legs.first().map_or(0, |leg| leg.min_out),
+            params.reflection_quoted_out,
+            params.reflection_slippage_bps,
+        )?;
+        let min_main_out = resolve_min_main_out(
+            min_main_out,
+            params.quoted_out,
+            config,
+        )?;
+        check_max_slippage(min_main_out, params.quoted_out, config.max_slippage_bps)?;
+
+        // Small swaps below the configured notional skip reflection entirely
+        let below_reflection_notional = config.reflection_min_notional > 0
+            && total_input_amount < config.reflection_min_notional;
+        let reflection_enabled = !params.disable_reflection && !below_reflection_notional;
+        let mut reflection_skip = if params.disable_reflection {
+            ReflectionSkipReason::Disabled
+        } else if below_reflection_notional {
+            ReflectionSkipReason::BelowNotional
+        } else {
+            ReflectionSkipReason::None
+        };
+        // In output mode the reflection is carved from the realized main output
+        // after the main leg, so nothing is taken from the input up front.
+        let output_reflection = params.reflection_mode == ReflectionMode::Output;
+        let mut reflection_required = reflection_enabled && !output_reflection;
+        // Transfer mode hands the input-side reflection to a community account
+        // as-is, so there is no reflection swap (and no output-mode variant)
+        let transfer_reflection = params.reflection_action == ReflectionAction::Transfer;
         require!(
-            main_delta >= params.min_main_out,
-            RaceswapError::MainBelowMinOut
+            !(transfer_reflection && output_reflection),
+            RaceswapError::InvalidReflectionAction
         );
 
-        // Ensure no trailing accounts remain unused
+        // Balance deltas are attributed to `user`; every account they are read
+        // from must be theirs
+        require_user_owns(
+            &ctx.accounts.user.key(),
+            &[
+                (ctx.accounts.user_input.key(), user_input.owner),
+                (
+                    ctx.accounts.user_main_destination.key(),
+                    ctx.accounts.user_main_destination.owner,
+                ),
+                (
+                    ctx.accounts.user_reflection_destination.key(),
+                    ctx.accounts.user_reflection_destination.owner,
+                ),
+            ],
+        )?;
+
+        require_keys_eq!(
+            ctx.accounts.user_main_destination.mint,
+            main_output_mint_key,
+            RaceswapError::InvalidMainAccount
+        );
+
+        let main_dest_info = ctx.accounts.user_main_destination.to_account_info();
+        msg!("Output Main: key={}, owner={}, mint={}", 
+            main_dest_info.key(), 
+            main_dest_info.owner, 
+            ctx.accounts.user_main_destination.mint
+        );
         require!(
-            remaining_iter.next().is_none(),
-            RaceswapError::AccountMismatch
+            *main_dest_info.owner == TOKEN_PROGRAM_ID || *main_dest_info.owner == TOKEN_2022_PROGRAM_ID,
+            RaceswapError::InvalidMainAccount
         );
 
-        emit!(SwapExecuted {
-            user: ctx.accounts.user.key(),
-            input_mint: input_mint_key,
-            main_output_mint: main_output_mint_key,
-            reflection_output_mint: reflection_mint_key,
-            total_in: params.total_input_amount,
-            main_amount: main_delta,
-            reflection_amount: reflection_received,
-            treasury_amount: treasury_fee_lamports,
-        });
+        // A Token-2022 main output needs its mint to account for transfer fees.
+        // Transfer-hook mints are unsupported: the hook's extra accounts are
+        // never forwarded, so reject them before any fee moves.
+        let main_output_mint_info = match ctx.accounts.main_output_mint.as_ref() {
+            Some(mint) => {
+                require_keys_eq!(mint.key(), main_output_mint_key, RaceswapError::InvalidMainAccount);
+                Some(mint.to_account_info())
+            }
+            None => {
+                require!(
+                    *main_dest_info.owner != TOKEN_2022_PROGRAM_ID,
+                    RaceswapError::InvalidMainAccount
+                );
+                None
+            }
+        };
+        reject_transfer_hook(&ctx.accounts.input_mint.to_account_info())?;
+        if let Some(mint) = main_output_mint_info.as_ref() {
+            reject_transfer_hook(mint)?;
+        }
+        if let Some(mint) = ctx.accounts.reflection_mint.as_ref() {
+            reject_transfer_hook(&mint.to_account_info())?;
+        }
 
-        Ok(())
-    }
-}
+        require_keys_eq!(
+            ctx.accounts.input_vault.mint,
+            input_mint_key,
+            RaceswapError::InvalidVaultMint
+        );
 
-fn perform_jupiter_swap<'info>(
-    payload: &SerializedInstruction,
-    jupiter_program: AccountInfo<'info>,
-    remaining_iter: &mut Iter<AccountInfo<'info>>,
-    _authority_seeds: &[&[u8]],
-    swap_authority_key: &Pubkey,
-) -> Result<()> {
-    let mut infos: Vec<AccountInfo<'info>> = Vec::with_capacity(payload.accounts_len as usize + 1);
-    let mut metas: Vec<AccountMeta> = Vec::with_capacity(payload.accounts_len as usize);
-    infos.push(jupiter_program.clone());
+        require_keys_eq!(
+            user_input.mint,
+            input_mint_key,
+            RaceswapError::InvalidUserSource
+        );
 
-    let mut consumed = 0usize;
+        if config.require_canonical_input_ata {
+            let canonical_input = get_associated_token_address_with_program_id(
+                &ctx.accounts.user.key(),
+                &input_mint_key,
+                &ctx.accounts.input_token_program.key(),
+            );
+            require_keys_eq!(
+                ctx.accounts.user_input.key(),
+                canonical_input,
+                RaceswapError::NonCanonicalInputAccount
+            );
+        }
+
+        let current_slot = Clock::get()?.slot;
+        let ResolvedSplit {
+            split,
+            reflection_fee_bps,
+        } = resolve_split(
+            config,
+            SplitRequest {
+                user: ctx.accounts.user.key(),
+                total_input_amount,
+                reflection_fee_bps,
+                reflect_from_input: reflection_required,
+                reflection_amount_override: params.reflection_amount_override,
+                fee_in_input_token: params.fee_in_input_token,
+                mint_fee: ctx.accounts.mint_fee.as_deref(),
+                user_stats: ctx.accounts.user_stats.as_deref_mut(),
+                rebate_nft: ctx.accounts.rebate_nft.as_deref(),
+                sol_usd_price: ctx.accounts.sol_usd_price.as_deref(),
+            },
+            current_slot,
+        )?;
+
+        // Smart-wallet users authorize the swap with a registered session key,
+        // which then also pays the SOL fee in place of the (non-signing) user
+        let input_authority = resolve_input_authority(
+            &ctx.accounts.user,
+            ctx.accounts.session_key.as_ref(),
+            ctx.accounts.session.as_ref(),
+            ctx.program_id,
+        )?;
+
+        // Fail with the amounts up front rather than as a bare system error
+        // from the fee transfer later on
+        let available_lamports = input_authority.lamports();
+        if available_lamports < split.treasury_fee_lamports {
+            msg!(
+                "Insufficient SOL for fee: required {} lamports, available {}",
+                split.treasury_fee_lamports,
+                available_lamports
+            );
+            return err!(RaceswapError::InsufficientSolForFee);
+        }
+        // Only SOL fees count: input-token fees are in arbitrary mint units and
+        // cannot be summed with lamports
+        if let Some(stats) = ctx.accounts.user_stats.as_mut() {
+            stats.period_fees = stats.period_fees.saturating_add(split.treasury_fee_lamports);
+        }
+        let reflection_amount = split.reflection_amount;
+
+        if reflection_amount == 0 {
+            if reflection_required {
+                reflection_skip = ReflectionSkipReason::Dust;
+            }
+            reflection_required = false;
+        }
+        if reflection_skip != ReflectionSkipReason::None {
+            emit!(ReflectionSkipped {
+                user: ctx.accounts.user.key(),
+                input_mint: input_mint_key,
+                reason: reflection_skip,
+            });
+        }
+
+        if reflection_required && !transfer_reflection {
+            validate_reflection_destination(
+                &ctx.accounts.user_reflection_destination,
+                &reflection_mint_key,
+                &ctx.accounts.user.key(),
+            )?;
+        }
+
+        let treasury_fee_lamports = split.treasury_fee_lamports;
+        let treasury_fee_tokens = split.treasury_fee_tokens;
+        // The vault is credited net of any Token-2022 transfer fee on the input.
+        // The main leg absorbs it, so routes must be quoted for what arrives.
+        let input_fee_withheld = transfer_fee_withheld(
+            &ctx.accounts.input_mint.to_account_info(),
+            total_input_amount - treasury_fee_tokens,
+        )?;
+        if input_fee_withheld > 0 {
+            msg!("Input transfer fee withheld: {}", input_fee_withheld);
+        }
+        let main_input_amount = split
+            .main_input_amount
+            .checked_sub(input_fee_withheld)
+            .ok_or(RaceswapError::InvalidAmount)?;
+        msg!("Main input amount: {}", main_input_amount);
+
+        // SOL input: wrap whatever the user's WSOL account is short of the
+        // input amount, so SOL -> token needs no separate wrap instruction
+        require!(
+            !native_input || ctx.accounts.user.is_signer,
+            RaceswapError::UserSignatureRequired
+        );
+        if native_input && user_input.amount < total_input_amount {
+            let shortfall = total_input_amount - user_input.amount;
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.user_input.to_account_info(),
+                    },
+                ),
+                shortfall,
+            )?;
+            token_interface::sync_native(CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                SyncNative {
+                    account: ctx.accounts.user_input.to_account_info(),
+                },
+            ))?;
+            user_input = load_token_account(&ctx.accounts.user_input)?;
+            msg!("Wrapped {} lamports into user_input", shortfall);
+        }
+
+        require!(
+            user_input.amount >= total_input_amount,
+            RaceswapError::InsufficientInputBalance
+        );
+
+        let user_input_before = user_input.amount;
+        let vault_start = ctx.accounts.input_vault.amount;
+
+        // Instruction-start balances for the optional SwapDebug event
+        let debug_before = (
+            ctx.accounts.user_main_destination.amount,
+            ctx.accounts.user_reflection_destination.amount,
+            ctx.accounts.input_vault.amount,
+        );
+
+        // In-token fee goes straight to the treasury_authority PDA's ATA
+        if treasury_fee_tokens > 0 {
+            let treasury_token_account = ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(RaceswapError::InvalidTreasuryAccount)?;
+            let (treasury_authority, _) = Pubkey::find_program_address(
+                &[TREASURY_AUTHORITY_SEED, config_key.as_ref()],
+                ctx.program_id,
+            );
+            let expected_ata = get_associated_token_address_with_program_id(
+                &treasury_authority,
+                &input_mint_key,
+                &ctx.accounts.input_token_program.key(),
+            );
+            require_keys_eq!(
+                treasury_token_account.key(),
+                expected_ata,
+                RaceswapError::InvalidTreasuryAccount
+            );
+
+            token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.user_input.to_account_info(),
+                        mint: ctx.accounts.input_mint.to_account_info(),
+                        to: treasury_token_account.to_account_info(),
+                        authority: input_authority.clone(),
+                    },
+                ),
+                treasury_fee_tokens,
+                decimals,
+            )?;
+            msg!("Treasury fee paid in input token: {}", treasury_fee_tokens);
+        }
+
+        // Transfer the rest of the input from user to vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_input.to_account_info(),
+                    mint: ctx.accounts.input_mint.to_account_info(),
+                    to: ctx.accounts.input_vault.to_account_info(),
+                    authority: input_authority.clone(),
+                },
+            ),
+            total_input_amount
+                .checked_sub(treasury_fee_tokens)
+                .ok_or(RaceswapError::MathOverflow)?,
+            decimals,
+        )?;
+
+        // A registered referral code takes its agreed share of the SOL fee
+        let referral_fee_lamports = match params.referral_code {
+            Some(code) => {
+                let (referral, referrer) = resolve_referral(
+                    &code,
+                    ctx.accounts.referral.as_ref(),
+                    ctx.accounts.referrer.as_ref(),
+                    ctx.program_id,
+                )?;
+                let share = ((treasury_fee_lamports as u128)
+                    .checked_mul(referral.fee_share_bps as u128)
+                    .ok_or(RaceswapError::MathOverflow)?
+                    / FEE_DENOMINATOR) as u64;
+                if share > 0 {
+                    system_program::transfer(
+                        CpiContext::new(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: input_authority.clone(),
+                                to: referrer.to_account_info(),
+                            },
+                        ),
+                        share,
+                    )?;
+                    msg!("Referral fee paid: {} lamports to {}", share, referral.referrer);
+                }
+                share
+            }
+            None => 0,
+        };
+
+        // Pay treasury fee in SOL
+        let treasury_net_lamports = treasury_fee_lamports
+            .checked_sub(referral_fee_lamports)
+            .ok_or(RaceswapError::MathOverflow)?;
+        if treasury_net_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: input_authority.clone(),
+                        to: ctx.accounts.treasury_fee_destination.to_account_info(),
+                    },
+                ),
+                treasury_net_lamports,
+            )?;
+            raceswap_common::require_rent_exempt(
+                &ctx.accounts.treasury_fee_destination.to_account_info(),
+            )?;
+        }
+
+        // Shared-accounts mode: the first `shared_accounts_len` remaining accounts
+        // are passed once and referenced by index from either leg
+        let shared_accounts_len = params.shared_accounts_len as usize;
+        require!(
+            shared_accounts_len <= ctx.remaining_accounts.len(),
+            RaceswapError::AccountMismatch
+        );
+        let (shared_accounts, leg_accounts) = ctx.remaining_accounts.split_at(shared_accounts_len);
+        let mut legs = LegAccounts {
+            jupiter_program: ctx.accounts.jupiter_program.to_account_info(),
+            shared: shared_accounts,
+            unique: leg_accounts.iter(),
+            swap_authority_key: swap_authority_derived,
+            consumed: 0,
+            signing_vault: params
+                .vault_authority_signs
+                .then(|| ctx.accounts.input_vault.key()),
+        };
+
+        // Reflection swap legs: (in, out) per executed leg, in leg order
+        let mut reflection_outcomes: Vec<(u64, u64)> = Vec::with_capacity(MAX_REFLECTION_LEGS);
+        let mut reflection_transferred = 0u64;
+
+        // Optional reflection mint, used to gross up Token-2022 transfer fees
+        let reflection_mint_info = match ctx.accounts.reflection_mint.as_ref() {
+            Some(mint) => {
+                require_keys_eq!(
+                    mint.key(),
+                    reflection_mint_key,
+                    RaceswapError::InvalidReflectionAccount
+                );
+                Some(mint.to_account_info())
+            }
+            None => None,
+        };
+
+        // When both legs deliver into the same account their floors are checked
+        // against the combined delta instead of separately
+        let destinations_aliased = ctx.accounts.user_main_destination.key()
+            == ctx.accounts.user_reflection_destination.key();
+        require!(
+            !(destinations_aliased && output_reflection),
+            RaceswapError::InvalidReflectionAccount
+        );
+
+        if reflection_required && transfer_reflection {
+            require!(
+                params.reflection_legs.is_empty(),
+                RaceswapError::UnexpectedReflectionLeg
+            );
+            let community = ctx
+                .accounts
+                .community_destination
+                .as_ref()
+                .ok_or(RaceswapError::InvalidReflectionAction)?;
+            require_keys_eq!(
+                community.mint,
+                input_mint_key,
+                RaceswapError::InvalidReflectionAction
+            );
+            let swap_authority = ctx
+                .remaining_accounts
+                .iter()
+                .find(|info| *info.key == swap_authority_derived)
+                .ok_or(RaceswapError::AccountMismatch)?;
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.input_vault.to_account_info(),
+                        mint: ctx.accounts.input_mint.to_account_info(),
+                        to: community.to_account_info(),
+                        authority: swap_authority.clone(),
+                    },
+                    &[&authority_signer_seeds],
+                ),
+                reflection_amount,
+                decimals,
+            )?;
+            reflection_transferred = reflection_amount;
+            msg!("Reflection transferred: {} to {}", reflection_amount, community.key());
+        } else if reflection_required {
+            require!(
+                !params.reflection_legs.is_empty(),
+                RaceswapError::MissingReflectionLeg
+            );
+            let shares = reflection_shares(reflection_amount, &params.reflection_legs)?;
+
+            for (index, reflection_leg) in params.reflection_legs.iter().enumerate() {
+                ctx.accounts.input_vault.reload()?;
+                let vault_before = ctx.accounts.input_vault.amount;
+                let floor = match index {
+                    0 if destinations_aliased => 0,
+                    0 => min_reflection_out,
+                    _ => reflection_leg.min_out,
+                };
+                // Field borrows stay disjoint from `config`
+                let destination = match index {
+                    0 => &mut ctx.accounts.user_reflection_destination,
+                    _ => ctx
+                        .accounts
+                        .second_reflection_destination
+                        .as_mut()
+                        .ok_or(RaceswapError::InvalidReflectionAccount)?,
+                };
+                let received = perform_reflection_leg(
+                    &reflection_leg.instruction,
+                    destination,
+                    &mut legs,
+                    &authority_signer_seeds,
+                    floor,
+                    if index == 0 { reflection_mint_info.as_ref() } else { None },
+                )?;
+                // With several legs, none may eat into another's share
+                if params.reflection_legs.len() > 1 {
+                    ctx.accounts.input_vault.reload()?;
+                    let spent = vault_before.saturating_sub(ctx.accounts.input_vault.amount);
+                    require!(
+                        spent <= shares[index],
+                        RaceswapError::InvalidReflectionSplit
+                    );
+                }
+                reflection_outcomes.push((shares[index], received));
+            }
+        } else if !(reflection_enabled && output_reflection) {
+            require!(
+                params.reflection_legs.is_empty(),
+                RaceswapError::UnexpectedReflectionLeg
+            );
+        }
+
+        // Main swap leg
+        let main_leg = params.main_leg.as_ref().ok_or(RaceswapError::MissingMainLeg)?;
+        // Reload so a reflection credit into an aliased destination isn't counted again
+        ctx.accounts.user_main_destination.reload()?;
+        let main_before = ctx.accounts.user_main_destination.amount;
+        ctx.accounts.input_vault.reload()?;
+        let vault_before_main = ctx.accounts.input_vault.amount;
+        if let Some(expected) = config.platform_fee_account {
+            check_platform_fee_account(
+                main_leg,
+                &legs,
+                params.platform_fee_account_index,
+                &expected,
+                &ctx.accounts.jupiter_program.key(),
+            )?;
+        }
+        perform_jupiter_swap(main_leg, &mut legs, &authority_signer_seeds)?;
+        // Capture Jupiter's reported out_amount before any later CPI overwrites it
+        let jupiter_reported_out = get_return_data()
+            .filter(|(program_id, _)| *program_id == ctx.accounts.jupiter_program.key())
+            .and_then(|(_, data)| data.get(..8).and_then(|b| b.try_into().ok()))
+            .map(u64::from_le_bytes);
+        // The main leg may not draw more than the net input from the vault
+        ctx.accounts.input_vault.reload()?;
+        let main_spent = vault_before_main.saturating_sub(ctx.accounts.input_vault.amount);
+        require!(
+            main_spent <= main_input_amount,
+            RaceswapError::MainInputExceeded
+        );
+        ctx.accounts.user_main_destination.reload()?;
+        let main_after = ctx.accounts.user_main_destination.amount;
+        let mut main_delta = main_after
+            .checked_sub(main_before)
+            .ok_or(RaceswapError::InvalidMainAccounting)?;
+        // Input left the vault but nothing reached the measured destination:
+        // the route delivered somewhere else, which is not mere slippage
+        require!(
+            main_spent == 0 || main_delta > 0,
+            RaceswapError::MainOutputNotReceived
+        );
+        if params.check_jupiter_out {
+            check_jupiter_out(
+                jupiter_reported_out,
+                main_delta,
+                min_main_out,
+                config.min_out_tolerance,
+            )?;
+        }
+        let max_impact_bps = if config.circuit_breaker_active {
+            require!(params.expected_out > 0, RaceswapError::CircuitBreakerNeedsQuote);
+            params.max_impact_bps.min(config.max_price_impact_bps)
+        } else {
+            params.max_impact_bps
+        };
+        check_price_impact(main_delta, params.expected_out, max_impact_bps)?;
+
+        // Output-based reflection: swap a bps share of the realized main output
+        if reflection_enabled && output_reflection {
+            let output_reflection_amount = u64::try_from(
+                (main_delta as u128)
+                    .checked_mul(reflection_fee_bps as u128)
+                    .ok_or(RaceswapError::MathOverflow)?
+                    .checked_div(FEE_DENOMINATOR)
+                    .ok_or(RaceswapError::MathOverflow)?,
+            )
+            .map_err(|_| RaceswapError::MathOverflow)?;
+
+            if output_reflection_amount > 0 {
+                validate_reflection_destination(
+                    &ctx.accounts.user_reflection_destination,
+                    &reflection_mint_key,
+                    &ctx.accounts.user.key(),
+                )?;
+                require!(
+                    !params.reflection_legs.is_empty(),
+                    RaceswapError::MissingReflectionLeg
+                );
+                let shares = reflection_shares(output_reflection_amount, &params.reflection_legs)?;
+
+                for (index, reflection_leg) in params.reflection_legs.iter().enumerate() {
+                    let main_before_leg = ctx.accounts.user_main_destination.amount;
+                    let destination = match index {
+                        0 => &mut ctx.accounts.user_reflection_destination,
+                        _ => ctx
+                            .accounts
+                            .second_reflection_destination
+                            .as_mut()
+                            .ok_or(RaceswapError::InvalidReflectionAccount)?,
+                    };
+                    let received = perform_reflection_leg(
+                        &reflection_leg.instruction,
+                        destination,
+                        &mut legs,
+                        &authority_signer_seeds,
+                        if index == 0 { min_reflection_out } else { reflection_leg.min_out },
+                        if index == 0 { reflection_mint_info.as_ref() } else { None },
+                    )?;
+
+                    // Each leg may only draw up to its share of the main output
+                    ctx.accounts.user_main_destination.reload()?;
+                    let spent = main_before_leg
+                        .checked_sub(ctx.accounts.user_main_destination.amount)
+                        .ok_or(RaceswapError::InvalidMainAccounting)?;
+                    require!(
+                        spent <= shares[index],
+                        RaceswapError::InvalidReflectionSplit
+                    );
+                    main_delta = main_delta
+                        .checked_sub(spent)
+                        .ok_or(RaceswapError::InvalidMainAccounting)?;
+                    reflection_outcomes.push((spent, received));
+                }
+            } else {
+                require!(
+                    params.reflection_legs.is_empty(),
+                    RaceswapError::UnexpectedReflectionLeg
+                );
+            }
+        }
+
+        // Token-level rounding can land a unit or two short; the configured
+        // tolerance (0 by default) absorbs that instead of reverting
+        let main_floor = min_main_out.saturating_sub(config.min_out_tolerance);
+        // Floors compare the pre-fee output, as reflection legs already do, so a
+        // Token-2022 transfer fee withheld on delivery is not counted as slippage
+        let gross_main = |delta: u64| match main_output_mint_info.as_ref() {
+            Some(mint) => gross_up_transfer_fee(mint, delta),
+            None => Ok(delta),
+        };
+
+        // Guaranteed output: cover a main shortfall by swapping part of the first
+        // reflection leg's output back into the main token. The top-up may draw
+        // at most what that leg delivered in this swap.
+        if let Some(topup_leg) = params.topup_leg.as_ref() {
+            if params.topup_from_reflection
+                && !destinations_aliased
+                && gross_main(main_delta)? < main_floor
+            {
+                let available = reflection_outcomes.first().map_or(0, |outcome| outcome.1);
+                require!(available > 0, RaceswapError::TopupUnavailable);
+                ctx.accounts.user_reflection_destination.reload()?;
+                let reflection_before = ctx.accounts.user_reflection_destination.amount;
+                ctx.accounts.user_main_destination.reload()?;
+                let main_before_topup = ctx.accounts.user_main_destination.amount;
+
+                perform_jupiter_swap(topup_leg, &mut legs, &authority_signer_seeds)?;
+
+                ctx.accounts.user_reflection_destination.reload()?;
+                let drawn = reflection_before
+                    .saturating_sub(ctx.accounts.user_reflection_destination.amount);
+                require!(drawn <= available, RaceswapError::ReflectionOverdrawn);
+                ctx.accounts.user_main_destination.reload()?;
+                let gained = ctx
+                    .accounts
+                    .user_main_destination
+                    .amount
+                    .checked_sub(main_before_topup)
+                    .ok_or(RaceswapError::InvalidMainAccounting)?;
+                main_delta = main_delta
+                    .checked_add(gained)
+                    .ok_or(RaceswapError::MathOverflow)?;
+                reflection_outcomes[0].1 = available - drawn;
+                msg!("Top-up: {} reflection -> {} main", drawn, gained);
+            } else {
+                // Route not needed: consume its accounts so the trailing check holds
+                build_leg_cpi(topup_leg, &mut legs)?;
+            }
+        }
+
+        // Only the first leg can share the main destination, so it alone joins
+        // the combined floor and the SwapExecuted reflection_amount
+        let reflection_received = reflection_outcomes.first().map_or(0, |outcome| outcome.1);
+
+        let main_delta_gross = gross_main(main_delta)?;
+        if destinations_aliased {
+            let combined_delta = main_delta_gross
+                .checked_add(reflection_received)
+                .ok_or(RaceswapError::MathOverflow)?;
+            let combined_min = main_floor
+                .checked_add(min_reflection_out)
+                .ok_or(RaceswapError::MathOverflow)?;
+            require!(
+                combined_delta >= combined_min,
+                RaceswapError::MainBelowMinOut
+            );
+        } else {
+            require!(
+                main_delta_gross >= main_floor,
+                RaceswapError::MainBelowMinOut
+            );
+        }
+
+        check_reference_floor(
+            main_delta,
+            main_input_amount,
+            params.reference_price_numerator,
+            params.reference_price_denominator,
+            config.min_reference_output_bps,
+        )?;
+        // The reference price converts input units only: the SOL fee counts
+        // when the input is SOL itself, otherwise there is no rate for it
+        let fee_in_input_units = if input_mint_key == NATIVE_MINT {
+            treasury_fee_lamports
+                .checked_add(treasury_fee_tokens)
+                .ok_or(RaceswapError::MathOverflow)?
+        } else {
+            treasury_fee_tokens
+        };
+        check_fee_proportion(
+            main_delta,
+            fee_in_input_units,
+            params.reference_price_numerator,
+            params.reference_price_denominator,
+            config.max_fee_output_bps,
+        )?;
+
+        // Return input the legs did not consume, to refund_destination when given
+        ctx.accounts.input_vault.reload()?;
+        let leftover = ctx.accounts.input_vault.amount.saturating_sub(vault_start);
+        let mut refunded_to_user_input = 0u64;
+        if leftover > 0 {
+            let swap_authority = ctx
+                .remaining_accounts
+                .iter()
+                .find(|info| *info.key == swap_authority_derived)
+                .ok_or(RaceswapError::AccountMismatch)?;
+            let refund_to = match ctx.accounts.refund_destination.as_ref() {
+                Some(destination) => {
+                    require_keys_eq!(
+                        destination.mint,
+                        input_mint_key,
+                        RaceswapError::InvalidRefundDestination
+                    );
+                    require!(
+                        destination.owner == ctx.accounts.user.key()
+                            || config.allowed_callers.contains(&destination.owner),
+                        RaceswapError::InvalidRefundDestination
+                    );
+                    destination.to_account_info()
+                }
+                None => ctx.accounts.user_input.to_account_info(),
+            };
+            if refund_to.key() == ctx.accounts.user_input.key() {
+                refunded_to_user_input = leftover;
+            }
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.input_token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.input_vault.to_account_info(),
+                        mint: ctx.accounts.input_mint.to_account_info(),
+                        to: refund_to.clone(),
+                        authority: swap_authority.clone(),
+                    },
+                    &[&authority_signer_seeds],
+                ),
+                leftover,
+                decimals,
+            )?;
+            msg!("Refunded {} unused input to {}", leftover, refund_to.key());
+        }
+
+        // Ensure no trailing accounts remain unused
+        require!(
+            legs.unique.next().is_none(),
+            RaceswapError::AccountMismatch
+        );
+        ctx.accounts.config.in_progress = false;
+
+        // Nothing but the vault transfer (net of any refund) may draw from the
+        // user's input account
+        if params.reconcile_input {
+            user_input = load_token_account(&ctx.accounts.user_input)?;
+            let expected = user_input_before
+                .checked_sub(total_input_amount)
+                .and_then(|v| v.checked_add(refunded_to_user_input))
+                .ok_or(RaceswapError::MathOverflow)?;
+            if user_input.amount != expected {
+                msg!(
+                    "user_input ended at {}, expected {}",
+                    user_input.amount,
+                    expected
+                );
+                return err!(RaceswapError::InputReconciliationFailed);
+            }
+        }
+
+        // A WSOL account created above was only a wrapper: close it so any
+        // refunded input and its rent return as native SOL. Pre-existing WSOL
+        // accounts are left as they are.
+        if wsol_created {
+            token_interface::close_account(CpiContext::new(
+                ctx.accounts.input_token_program.to_account_info(),
+                CloseAccount {
+                    account: ctx.accounts.user_input.to_account_info(),
+                    destination: ctx.accounts.user.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ))?;
+            msg!("Closed temporary WSOL input account");
+        }
+
+        if params.debug {
+            ctx.accounts.user_main_destination.reload()?;
+            ctx.accounts.user_reflection_destination.reload()?;
+            ctx.accounts.input_vault.reload()?;
+            emit!(SwapDebug {
+                main_before: debug_before.0,
+                main_after: ctx.accounts.user_main_destination.amount,
+                reflection_before: debug_before.1,
+                reflection_after: ctx.accounts.user_reflection_destination.amount,
+                vault_before: debug_before.2,
+                vault_after: ctx.accounts.input_vault.amount,
+            });
+        }
+
+        // Unwrapping closes the whole WSOL account, so it is only allowed when
+        // the destination held nothing before the swap: the lamports that land
+        // in the wallet are then exactly this swap's output plus the rent
+        if params.unwrap_output {
+            require!(
+                ctx.accounts.user_main_destination.mint == NATIVE_MINT && debug_before.0 == 0,
+                RaceswapError::InvalidUnwrapOutput
+            );
+            require!(ctx.accounts.user.is_signer, RaceswapError::UserSignatureRequired);
+            let output_token_program = match ctx.accounts.output_token_program.as_ref() {
+                Some(program) => program.to_account_info(),
+                None => ctx.accounts.input_token_program.to_account_info(),
+            };
+            require_keys_eq!(
+                output_token_program.key(),
+                *ctx.accounts.user_main_destination.to_account_info().owner,
+                RaceswapError::InvalidUnwrapOutput
+            );
+            token_interface::close_account(CpiContext::new(
+                output_token_program,
+                CloseAccount {
+                    account: ctx.accounts.user_main_destination.to_account_info(),
+                    destination: ctx.accounts.user.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ))?;
+            msg!("Unwrapped {} WSOL output to native SOL", main_delta);
+        }
+
+        let stats = &mut ctx.accounts.protocol_stats;
+        stats.total_swaps = stats.total_swaps.saturating_add(1);
+        stats.total_treasury_lamports = stats
+            .total_treasury_lamports
+            .saturating_add(treasury_fee_lamports as u128);
+        stats.total_treasury_tokens = stats
+            .total_treasury_tokens
+            .saturating_add(treasury_fee_tokens as u128);
+        stats.total_volume = stats.total_volume.saturating_add(total_input_amount as u128);
+
+        emit!(SwapExecuted {
+            user: ctx.accounts.user.key(),
+            input_mint: input_mint_key,
+            main_output_mint: main_output_mint_key,
+            reflection_output_mint: reflection_mint_key,
+            total_in: total_input_amount,
+            main_amount: main_delta,
+            reflection_amount: reflection_received,
+            treasury_amount: treasury_fee_lamports,
+            reflection_skip,
+            slot: current_slot,
+        });
+
+        // Realized execution price of the main leg, in UI units when the output
+        // mint is supplied and in raw units otherwise
+        let output_decimals = main_output_mint_info
+            .as_ref()
+            .map(|mint| read_mint_decimals(mint))
+            .transpose()?;
+        let rate = realized_rate(
+            main_delta,
+            main_input_amount,
+            decimals,
+            output_decimals.unwrap_or(decimals),
+        )?;
+        msg!("Realized rate: {} (scale {})", rate, RATE_SCALE);
+        emit!(SwapRate {
+            user: ctx.accounts.user.key(),
+            input_mint: input_mint_key,
+            main_output_mint: main_output_mint_key,
+            main_input_amount,
+            main_amount: main_delta,
+            rate,
+            decimals_adjusted: output_decimals.is_some(),
+        });
+
+        // Per-leg outcomes for calling programs, in execution order
+        let mut reflection_results: Vec<LegResult> = reflection_outcomes
+            .iter()
+            .zip(params.reflection_legs.iter())
+            .map(|(&(in_amount, out_amount), leg)| LegResult {
+                leg_kind: LegKind::Reflection,
+                in_amount,
+                out_amount,
+                out_mint: leg.mint,
+            })
+            .collect();
+        // A transferred reflection is reported in the input mint
+        if reflection_transferred > 0 {
+            reflection_results.push(LegResult {
+                leg_kind: LegKind::Reflection,
+                in_amount: reflection_transferred,
+                out_amount: reflection_transferred,
+                out_mint: input_mint_key,
+            });
+        }
+        let mut leg_results: Vec<LegResult> = Vec::with_capacity(1 + reflection_results.len());
+        if !output_reflection {
+            leg_results.extend(reflection_results.iter().cloned());
+        }
+        leg_results.push(LegResult {
+            leg_kind: LegKind::Main,
+            in_amount: main_input_amount,
+            out_amount: main_delta,
+            out_mint: main_output_mint_key,
+        });
+        if output_reflection {
+            leg_results.extend(reflection_results);
+        }
+        require!(
+            leg_results.len() <= MAX_LEG_RESULTS,
+            RaceswapError::AccountMismatch
+        );
+        // Same SwapStatus prefix as the other variants, followed by the legs
+        let status = SwapStatus::ok(
+            main_delta,
+            treasury_fee_lamports
+                .checked_add(treasury_fee_tokens)
+                .ok_or(RaceswapError::MathOverflow)?,
+        );
+        set_return_data(&(status, leg_results).try_to_vec()?);
+
+        Ok(())
+    }
+}
+
+/// When invoked via CPI and `allowed_callers` is non-empty, requires the
+/// top-level program of the transaction instruction to be whitelisted. Direct
+/// calls and an empty list are always allowed.
+fn check_caller_allowed(
+    allowed_callers: &[Pubkey],
+    instructions_sysvar: Option<&UncheckedAccount>,
+) -> Result<()> {
+    if allowed_callers.is_empty() || get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+
+    let instructions_sysvar = instructions_sysvar.ok_or(RaceswapError::CallerNotAllowed)?;
+    let ixs = instructions_sysvar.to_account_info();
+    let current_index = load_current_index_checked(&ixs)?;
+    let caller = load_instruction_at_checked(current_index as usize, &ixs)?.program_id;
+    require!(
+        allowed_callers.contains(&caller),
+        RaceswapError::CallerNotAllowed
+    );
+    msg!("CPI caller {} allowed", caller);
+    Ok(())
+}
+
+/// No-oracle sanity check: with a client-supplied reference price (input units
+/// per output unit, as numerator/denominator), the main output must reach at
+/// least `min_reference_output_bps` of the theoretical output. Skipped when the
+/// config threshold is zero or no reference price is given.
+fn check_reference_floor(
+    main_delta: u64,
+    main_input_amount: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    min_reference_output_bps: u16,
+) -> Result<()> {
+    if min_reference_output_bps == 0 || price_numerator == 0 || price_denominator == 0 {
+        return Ok(());
+    }
+
+    let floor = (main_input_amount as u128)
+        .checked_mul(price_denominator as u128)
+        .and_then(|v| v.checked_mul(min_reference_output_bps as u128))
+        .ok_or(RaceswapError::MathOverflow)?
+        / (price_numerator as u128)
+        / FEE_DENOMINATOR;
+    msg!("Reference floor: {} (main_delta={})", floor, main_delta);
+    require!(
+        main_delta as u128 >= floor,
+        RaceswapError::BelowReferenceFloor
+    );
+    Ok(())
+}
+
+/// Cross-checks the out_amount Jupiter reports via return data against the
+/// measured destination delta: the report must reach `min_out` and agree with
+/// the delta to within `tolerance` units. A missing report also fails.
+fn check_jupiter_out(
+    reported_out: Option<u64>,
+    main_delta: u64,
+    min_out: u64,
+    tolerance: u64,
+) -> Result<()> {
+    let reported = reported_out.ok_or(RaceswapError::OutputAccountingMismatch)?;
+    msg!("Jupiter reported out: {} (main_delta={})", reported, main_delta);
+    require!(
+        reported >= min_out && reported.abs_diff(main_delta) <= tolerance,
+        RaceswapError::OutputAccountingMismatch
+    );
+    Ok(())
+}
+
+/// Converts `usd_cents` to lamports at the SOL/USD price in a fully verified
+/// Pyth `PriceUpdateV2` account for `feed_id`, rejecting prices older than
+/// `max_age_secs`.
+fn usd_fee_lamports(
+    price_account: &AccountInfo,
+    feed_id: &[u8; 32],
+    max_age_secs: u64,
+    usd_cents: u64,
+) -> Result<u64> {
+    require_keys_eq!(
+        *price_account.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        RaceswapError::InvalidPriceAccount
+    );
+    let data = price_account.try_borrow_data()?;
+    // discriminator, write_authority, then VerificationLevel::Full (a lone tag)
+    // ahead of the price message
+    require!(
+        data.len() >= 8 + 32 + 1 + 32 + 8 + 8 + 4 + 8
+            && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR
+            && data[40] == 1,
+        RaceswapError::InvalidPriceAccount
+    );
+    let message = &data[41..];
+    require!(message[..32] == feed_id[..], RaceswapError::InvalidPriceAccount);
+    let read_8 = |offset: usize| -> [u8; 8] { message[offset..offset + 8].try_into().unwrap() };
+    let price = i64::from_le_bytes(read_8(32));
+    let exponent = i32::from_le_bytes(message[48..52].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(read_8(52));
+    require!(price > 0, RaceswapError::InvalidPriceAccount);
+
+    let now = Clock::get()?.unix_timestamp;
+    msg!("SOL/USD price {}e{} published at {}", price, exponent, publish_time);
+    require!(
+        now.saturating_sub(publish_time) <= max_age_secs as i64,
+        RaceswapError::StalePrice
+    );
+
+    // lamports = cents / 100 / (price * 10^exponent) * 10^9
+    let scale = 10u128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or(RaceswapError::MathOverflow)?;
+    let mut numerator = (usd_cents as u128)
+        .checked_mul(LAMPORTS_PER_SOL)
+        .ok_or(RaceswapError::MathOverflow)?;
+    let mut denominator = (price as u128) * 100;
+    if exponent < 0 {
+        numerator = numerator.checked_mul(scale).ok_or(RaceswapError::MathOverflow)?;
+    } else {
+        denominator = denominator.checked_mul(scale).ok_or(RaceswapError::MathOverflow)?;
+    }
+    u64::try_from(numerator / denominator).map_err(|_| RaceswapError::MathOverflow.into())
+}
+
+/// Rejects a main leg whose output fell more than `max_impact_bps` short of
+/// the quoted `expected_out`. Skipped when no quote is given.
+fn check_price_impact(main_delta: u64, expected_out: u64, max_impact_bps: u16) -> Result<()> {
+    if expected_out == 0 {
+        return Ok(());
+    }
+
+    let impact_bps = (expected_out.saturating_sub(main_delta) as u128)
+        .checked_mul(FEE_DENOMINATOR)
+        .ok_or(RaceswapError::MathOverflow)?
+        / (expected_out as u128);
+    msg!("Price impact: {} bps (max {})", impact_bps, max_impact_bps);
+    require!(
+        impact_bps <= max_impact_bps as u128,
+        RaceswapError::ExcessivePriceImpact
+    );
+    Ok(())
+}
+
+/// Rejects swaps where the treasury fee, in input units and converted at the
+/// client's input -> output reference price, is worth more than
+/// `max_fee_output_bps` of the main output. Skipped without a reference price.
+fn check_fee_proportion(
+    main_delta: u64,
+    treasury_fee: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    max_fee_output_bps: u16,
+) -> Result<()> {
+    if max_fee_output_bps == 0 || price_numerator == 0 || price_denominator == 0 {
+        return Ok(());
+    }
+
+    let fee_in_output = (treasury_fee as u128)
+        .checked_mul(price_denominator as u128)
+        .ok_or(RaceswapError::MathOverflow)?
+        / (price_numerator as u128);
+    let max_fee = (main_delta as u128)
+        .checked_mul(max_fee_output_bps as u128)
+        .ok_or(RaceswapError::MathOverflow)?
+        / FEE_DENOMINATOR;
+    msg!("Fee in output units: {} (max {})", fee_in_output, max_fee);
+    require!(
+        fee_in_output <= max_fee,
+        RaceswapError::FeeDisproportionate
+    );
+    Ok(())
+}
+
+fn count_distinct_writable<'a, 'info: 'a>(
+    accounts: impl Iterator<Item = &'a AccountInfo<'info>>,
+) -> usize {
+    let mut seen: Vec<&Pubkey> = Vec::new();
+    for account in accounts.filter(|a| a.is_writable) {
+        if !seen.contains(&account.key) {
+            seen.push(account.key);
+        }
+    }
+    seen.len()
+}
+
+/// Distinct mints held by the token accounts among `accounts`. Token-2022
+/// accounts with extensions are told apart from mints by their account type
+/// byte after the base 165-byte layout.
+fn count_distinct_token_mints<'a, 'info: 'a>(
+    accounts: impl Iterator<Item = &'a AccountInfo<'info>>,
+) -> usize {
+    const TOKEN_ACCOUNT_LEN: usize = 165;
+    const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+    let mut seen: Vec<Pubkey> = Vec::new();
+    for account in accounts {
+        if *account.owner != TOKEN_PROGRAM_ID && *account.owner != TOKEN_2022_PROGRAM_ID {
+            continue;
+        }
+        let Ok(data) = account.try_borrow_data() else {
+            continue;
+        };
+        let is_token_account = data.len() == TOKEN_ACCOUNT_LEN
+            || (data.len() > TOKEN_ACCOUNT_LEN && data[TOKEN_ACCOUNT_LEN] == ACCOUNT_TYPE_ACCOUNT);
+        if !is_token_account {
+            continue;
+        }
+        let mint = Pubkey::new_from_array(data[..32].try_into().unwrap());
+        if !seen.contains(&mint) {
+            seen.push(mint);
+        }
+    }
+    seen.len()
+}
+
+/// Verifies the platform-fee account at `index` in the main leg is ours. Jupiter
+/// passes its own program ID in that slot when a route charges no platform fee.
+fn check_platform_fee_account(
+    main_leg: &SerializedInstruction,
+    legs: &LegAccounts,
+    index: Option<u16>,
+    expected: &Pubkey,
+    jupiter_program: &Pubkey,
+) -> Result<()> {
+    let index = index.ok_or(RaceswapError::WrongPlatformFeeAccount)? as usize;
+    let actual = legs
+        .peek(main_leg, index)
+        .ok_or(RaceswapError::WrongPlatformFeeAccount)?
+        .key;
+    require!(
+        actual == expected || actual == jupiter_program,
+        RaceswapError::WrongPlatformFeeAccount
+    );
+    Ok(())
+}
+
+/// Converts an amount credited to a token account back into the pre-fee amount
+/// that was sent, for Token-2022 mints carrying a TransferFeeConfig. Other mints
+/// are returned unchanged.
+fn gross_up_transfer_fee(mint: &AccountInfo, net_amount: u64) -> Result<u64> {
+    if *mint.owner != TOKEN_2022_PROGRAM_ID || net_amount == 0 {
+        return Ok(net_amount);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    let fee_config = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config,
+        Err(_) => return Ok(net_amount),
+    };
+    let epoch = Clock::get()?.epoch;
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or(RaceswapError::MathOverflow)?;
+    msg!("Transfer fee withheld on delivery: {}", fee);
+    net_amount
+        .checked_add(fee)
+        .ok_or(RaceswapError::MathOverflow.into())
+}
+
+/// Fee a Token-2022 TransferFeeConfig withholds from a transfer of
+/// `pre_fee_amount`; 0 for other mints.
+fn transfer_fee_withheld(mint: &AccountInfo, pre_fee_amount: u64) -> Result<u64> {
+    if *mint.owner != TOKEN_2022_PROGRAM_ID || pre_fee_amount == 0 {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    let fee_config = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config,
+        Err(_) => return Ok(0),
+    };
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, pre_fee_amount)
+        .ok_or(RaceswapError::MathOverflow.into())
+}
+
+/// Rejects Token-2022 mints with a transfer hook program set.
+fn reject_transfer_hook(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != TOKEN_2022_PROGRAM_ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    if let Ok(hook) = mint_state.get_extension::<TransferHook>() {
+        if Option::<Pubkey>::from(hook.program_id).is_some() {
+            msg!("Mint {} has a transfer hook", mint.key());
+            return err!(RaceswapError::TransferHookUnsupported);
+        }
+    }
+    Ok(())
+}
+
+fn read_mint_decimals(mint: &AccountInfo) -> Result<u8> {
+    require!(
+        *mint.owner == TOKEN_PROGRAM_ID || *mint.owner == TOKEN_2022_PROGRAM_ID,
+        RaceswapError::InvalidMainAccount
+    );
+    let data = mint.try_borrow_data()?;
+    require!(data.len() >= 82, RaceswapError::InvalidMainAccount);
+    Ok(data[44])
+}
+
+/// Output per unit of input scaled by `RATE_SCALE`, adjusted for the decimal
+/// difference between the two mints. Returns 0 when no input was swapped.
+fn realized_rate(
+    main_out: u64,
+    net_input: u64,
+    input_decimals: u8,
+    output_decimals: u8,
+) -> Result<u64> {
+    if net_input == 0 {
+        return Ok(0);
+    }
+
+    let numerator = (main_out as u128)
+        .checked_mul(RATE_SCALE)
+        .and_then(|n| n.checked_mul(10u128.checked_pow(input_decimals as u32)?))
+        .ok_or(RaceswapError::MathOverflow)?;
+    let denominator = (net_input as u128)
+        .checked_mul(10u128.checked_pow(output_decimals as u32).ok_or(RaceswapError::MathOverflow)?)
+        .ok_or(RaceswapError::MathOverflow)?;
+
+    u64::try_from(numerator / denominator).map_err(|_| RaceswapError::MathOverflow.into())
+}
+
+/// Loads the referral entry for `code` and checks the passed referrer matches it.
+/// Codes without a registry entry are rejected with `UnknownReferralCode`.
+fn resolve_referral<'a, 'info>(
+    code: &[u8; 8],
+    referral: Option<&UncheckedAccount<'info>>,
+    referrer: Option<&'a UncheckedAccount<'info>>,
+    program_id: &Pubkey,
+) -> Result<(ReferralEntry, &'a UncheckedAccount<'info>)> {
+    let referral = referral.ok_or(RaceswapError::UnknownReferralCode)?;
+    let (expected, _) = Pubkey::find_program_address(&[REFERRAL_SEED, code], program_id);
+    require_keys_eq!(referral.key(), expected, RaceswapError::UnknownReferralCode);
+    require_keys_eq!(*referral.owner, *program_id, RaceswapError::UnknownReferralCode);
+    let entry = ReferralEntry::try_deserialize(&mut &referral.try_borrow_data()?[..])
+        .map_err(|_| error!(RaceswapError::UnknownReferralCode))?;
+
+    let referrer = referrer.ok_or(RaceswapError::InvalidReferrer)?;
+    require_keys_eq!(referrer.key(), entry.referrer, RaceswapError::InvalidReferrer);
+    Ok((entry, referrer))
+}
+
+/// Fee inputs shared by `execute_raceswap` and `preview_split`, so the preview
+/// charges exactly what the swap would
+struct SplitRequest<'a, 'info> {
+    user: Pubkey,
+    total_input_amount: u64,
+    /// Reflection bps for the input mint, before any streak boost
+    reflection_fee_bps: u16,
+    reflect_from_input: bool,
+    reflection_amount_override: Option<u64>,
+    fee_in_input_token: bool,
+    mint_fee: Option<&'a MintFeeOverride>,
+    user_stats: Option<&'a mut UserStats>,
+    rebate_nft: Option<&'a TokenAccount>,
+    sol_usd_price: Option<&'a AccountInfo<'info>>,
+}
+
+struct ResolvedSplit {
+    split: SwapSplit,
+    /// Reflection bps after the streak boost
+    reflection_fee_bps: u16,
+}
+
+/// Resolves the treasury and reflection bps (per-mint override, loyalty, fee
+/// holiday, rebate NFT, streak) and computes the split, replacing the bps SOL
+/// fee with the fixed USD fee when the config sets one.
+fn resolve_split(
+    config: &RaceswapConfig,
+    request: SplitRequest,
+    slot: u64,
+) -> Result<ResolvedSplit> {
+    // Partner mints may carry their own fee; the PDA is optional
+    let base_fee_bps = match request.mint_fee {
+        Some(mint_fee) => {
+            msg!("Per-mint fee override: {} bps", mint_fee.treasury_fee_bps);
+            mint_fee.treasury_fee_bps
+        }
+        None => config.treasury_fee_bps,
+    };
+    let mut user_stats = request.user_stats;
+    // Loyalty: users past the period threshold pay the reduced fee
+    let treasury_fee_bps = match user_stats.as_deref_mut() {
+        Some(stats) => loyalty_fee_bps(config, stats, slot, base_fee_bps),
+        None => base_fee_bps,
+    };
+    // Scheduled fee holiday: [start, end) charges no treasury fee
+    let treasury_fee_bps = if config.fee_holiday_end_slot > 0
+        && (config.fee_holiday_start_slot..config.fee_holiday_end_slot).contains(&slot)
+    {
+        msg!("Fee holiday active at slot {}", slot);
+        0
+    } else {
+        treasury_fee_bps
+    };
+    // Membership NFT: holding one of the configured rebate mints waives the fee
+    let treasury_fee_bps = match request.rebate_nft {
+        Some(rebate_nft) => {
+            require!(
+                config.rebate_mints.contains(&rebate_nft.mint)
+                    && rebate_nft.owner == request.user
+                    && rebate_nft.amount == 1,
+                RaceswapError::InvalidRebateNft
+            );
+            msg!("Fee rebate NFT held: {}", rebate_nft.mint);
+            0
+        }
+        None => treasury_fee_bps,
+    };
+    // Reflection streak: consecutive swaps within the window boost the rate
+    let reflection_fee_bps = match user_stats {
+        Some(stats) => streak_reflection_bps(config, stats, slot, request.reflection_fee_bps),
+        None => request.reflection_fee_bps,
+    };
+
+    let mut split = compute_split(
+        request.total_input_amount,
+        reflection_fee_bps,
+        request.reflect_from_input,
+        request.reflection_amount_override,
+        config.max_reflection_override_bps,
+        request.fee_in_input_token,
+        treasury_fee_bps,
+    )?;
+    // A fixed USD fee replaces the bps SOL fee, unless the fee is waived
+    if config.fixed_fee_usd_cents > 0 && !request.fee_in_input_token && treasury_fee_bps > 0 {
+        let price_account = request
+            .sol_usd_price
+            .ok_or(RaceswapError::InvalidPriceAccount)?;
+        split.treasury_fee_lamports = usd_fee_lamports(
+            price_account,
+            &config.sol_usd_feed_id,
+            config.max_price_age_secs,
+            config.fixed_fee_usd_cents,
+        )?;
+        msg!(
+            "Fixed fee: {} USD cents = {} lamports",
+            config.fixed_fee_usd_cents,
+            split.treasury_fee_lamports
+        );
+    }
+    Ok(ResolvedSplit {
+        split,
+        reflection_fee_bps,
+    })
+}
+
+/// Rolls the user's fee period once `loyalty_period_slots` have elapsed and
+/// returns the treasury fee bps that applies to their next swap.
+fn loyalty_fee_bps(
+    config: &RaceswapConfig,
+    stats: &mut UserStats,
+    slot: u64,
+    base_fee_bps: u16,
+) -> u16 {
+    if config.loyalty_period_slots > 0
+        && slot.saturating_sub(stats.period_start_slot) >= config.loyalty_period_slots
+    {
+        stats.period_start_slot = slot;
+        stats.period_fees = 0;
+    }
+    if config.loyalty_fee_threshold > 0 && stats.period_fees >= config.loyalty_fee_threshold {
+        config.loyalty_fee_bps.min(base_fee_bps)
+    } else {
+        base_fee_bps
+    }
+}
+
+/// Advances the user's reflection streak and returns `base_bps` scaled by the
+/// streak multiplier (`FEE_DENOMINATOR` = 1x), capped by
+/// `max_streak_multiplier_bps` and so that reflection plus the treasury fee
+/// stays below 100%. A gap longer than `streak_window_slots` resets the streak.
+fn streak_reflection_bps(
+    config: &RaceswapConfig,
+    stats: &mut UserStats,
+    slot: u64,
+    base_bps: u16,
+) -> u16 {
+    if config.streak_window_slots == 0 {
+        return base_bps;
+    }
+    if stats.last_swap_slot > 0
+        && slot.saturating_sub(stats.last_swap_slot) <= config.streak_window_slots
+    {
+        stats.streak = stats.streak.saturating_add(1);
+    } else {
+        stats.streak = 0;
+    }
+    stats.last_swap_slot = slot;
+
+    let multiplier = (FEE_DENOMINATOR
+        + stats.streak as u128 * config.streak_bonus_bps as u128)
+        .min(config.max_streak_multiplier_bps as u128);
+    let ceiling = FEE_DENOMINATOR - 1 - config.treasury_fee_bps as u128;
+    let boosted = (base_bps as u128 * multiplier / FEE_DENOMINATOR).min(ceiling);
+    msg!("Reflection streak {}: {} bps -> {} bps", stats.streak, base_bps, boosted);
+    boosted as u16
+}
+
+/// The single source of truth for how an input amount is divided. Shared by
+/// `execute_raceswap` and `preview_split` so previews cannot drift.
+fn compute_split(
+    total_input_amount: u64,
+    reflection_fee_bps: u16,
+    reflect_from_input: bool,
+    reflection_amount_override: Option<u64>,
+    max_reflection_override_bps: u16,
+    fee_in_input_token: bool,
+    treasury_fee_bps: u16,
+) -> Result<SwapSplit> {
+    let reflection_amount = if !reflect_from_input {
+        0u64
+    } else if let Some(override_amount) = reflection_amount_override {
+        // Explicit sizing from off-chain logic, bounded by the config's max share
+        require!(
+            override_amount <= total_input_amount,
+            RaceswapError::InvalidReflectionOverride
+        );
+        let max_override = (total_input_amount as u128)
+            .checked_mul(max_reflection_override_bps as u128)
+            .ok_or(RaceswapError::MathOverflow)?
+            / FEE_DENOMINATOR;
+        require!(
+            override_amount as u128 <= max_override,
+            RaceswapError::InvalidReflectionOverride
+        );
+        override_amount
+    } else {
+        u64::try_from(
+            (total_input_amount as u128)
+                .checked_mul(reflection_fee_bps as u128)
+                .ok_or(RaceswapError::MathOverflow)?
+                .checked_div(FEE_DENOMINATOR)
+                .ok_or(RaceswapError::MathOverflow)?,
+        )
+        .map_err(|_| RaceswapError::MathOverflow)?
+    };
+
+    // Calculate treasury fee in SOL (0.2% unless reduced by loyalty)
+    let treasury_fee = total_input_amount
+        .checked_mul(treasury_fee_bps as u64)
+        .ok_or(RaceswapError::MathOverflow)?
+        .checked_div(10_000)
+        .ok_or(RaceswapError::MathOverflow)?;
+    // In-token fees are carved from the input instead of charged in SOL
+    let (treasury_fee_lamports, treasury_fee_tokens) = if fee_in_input_token {
+        (0, treasury_fee)
+    } else {
+        (treasury_fee, 0)
+    };
+
+    // The main leg swaps what is left after the reflection carve-out. Clients
+    // must quote `main_leg` for this net amount, not `total_input_amount`.
+    let main_input_amount = total_input_amount
+        .checked_sub(reflection_amount)
+        .and_then(|v| v.checked_sub(treasury_fee_tokens))
+        .ok_or(RaceswapError::MathOverflow)?;
+
+    Ok(SwapSplit {
+        reflection_amount,
+        main_input_amount,
+        treasury_fee_lamports,
+        treasury_fee_tokens,
+    })
+}
+
+fn validate_reflection_destination(
+    destination: &InterfaceAccount<TokenAccount>,
+    reflection_mint: &Pubkey,
+    user: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        destination.mint,
+        *reflection_mint,
+        RaceswapError::InvalidReflectionAccount
+    );
+    require_keys_eq!(
+        destination.owner,
+        *user,
+        RaceswapError::InvalidReflectionAccount
+    );
+
+    let refl_dest_info = destination.to_account_info();
+    msg!("Output Refl: key={}, owner={}, mint={}", 
+        refl_dest_info.key(), 
+        refl_dest_info.owner, 
+        destination.mint
+    );
+    require!(
+        *refl_dest_info.owner == TOKEN_PROGRAM_ID || *refl_dest_info.owner == TOKEN_2022_PROGRAM_ID,
+        RaceswapError::InvalidReflectionAccount
+    );
+    Ok(())
+}
+
+/// Single guard for the token accounts whose balances are attributed to the
+/// signing user: the input source and both destinations.
+/// `accounts` are `(token account, its owner)` pairs.
+fn require_user_owns(user: &Pubkey, accounts: &[(Pubkey, Pubkey)]) -> Result<()> {
+    for (account, owner) in accounts {
+        if owner != user {
+            msg!("Account {} is owned by {}, not the user", account, owner);
+            return err!(RaceswapError::OwnerMismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a token account that is unchecked in the accounts struct; its token
+/// program owner must already have been checked.
+fn load_token_account(info: &AccountInfo) -> Result<TokenAccount> {
+    TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+/// Divides `amount` across the reflection legs by `weight_bps`; the last leg
+/// takes the rounding remainder. A single leg gets everything regardless of
+/// its weight, exactly as before legs carried weights.
+fn reflection_shares(amount: u64, reflection_legs: &[ReflectionLeg]) -> Result<Vec<u64>> {
+    if reflection_legs.len() <= 1 {
+        return Ok(vec![amount; reflection_legs.len()]);
+    }
+    require!(
+        reflection_legs.iter().map(|leg| leg.weight_bps as u128).sum::<u128>() == FEE_DENOMINATOR,
+        RaceswapError::InvalidReflectionLegs
+    );
+
+    let mut remaining = amount;
+    let mut shares = Vec::with_capacity(reflection_legs.len());
+    for (index, leg) in reflection_legs.iter().enumerate() {
+        let share = if index == reflection_legs.len() - 1 {
+            remaining
+        } else {
+            ((amount as u128) * (leg.weight_bps as u128) / FEE_DENOMINATOR) as u64
+        };
+        remaining = remaining
+            .checked_sub(share)
+            .ok_or(RaceswapError::MathOverflow)?;
+        shares.push(share);
+    }
+    Ok(shares)
+}
+
+/// Runs the reflection leg and returns the amount credited to the destination,
+/// grossed up for any Token-2022 transfer fee withheld on delivery.
+fn perform_reflection_leg<'info>(
+    reflection_leg: &SerializedInstruction,
+    destination: &mut InterfaceAccount<'info, TokenAccount>,
+    legs: &mut LegAccounts<'_, 'info>,
+    authority_seeds: &[&[u8]],
+    min_reflection_out: u64,
+    reflection_mint: Option<&AccountInfo<'info>>,
+) -> Result<u64> {
+    // Distinguish an ordering bug (an earlier leg ate this leg's accounts) from
+    // a plainly short account list
+    let needed = LegAccounts::unique_needed(reflection_leg);
+    if legs.unique.len() < needed {
+        msg!(
+            "Reflection leg needs {} accounts, {} left after {} consumed by earlier legs",
+            needed,
+            legs.unique.len(),
+            legs.consumed
+        );
+        if legs.consumed > 0 {
+            return err!(RaceswapError::ReflectionAccountsConsumed);
+        }
+        return err!(RaceswapError::AccountMismatch);
+    }
+
+    let before = destination.amount;
+    perform_jupiter_swap(reflection_leg, legs, authority_seeds)?;
+    destination.reload()?;
+    let after = destination.amount;
+    let mut delta = after
+        .checked_sub(before)
+        .ok_or(RaceswapError::InvalidReflectionAccounting)?;
+    if let Some(mint) = reflection_mint {
+        delta = gross_up_transfer_fee(mint, delta)?;
+    }
+    require!(
+        delta >= min_reflection_out,
+        RaceswapError::ReflectionBelowMinOut
+    );
+    require!(delta > 0, RaceswapError::ReflectionBelowMinOut);
+    Ok(delta)
+}
+
+/// Without a multisig (`multisig_threshold == 0`) the single `authority` must
+/// sign. Otherwise at least `multisig_threshold` distinct configured signers must
+/// be present among `authority` and the signer remaining_accounts.
+fn check_config_authority(
+    config: &RaceswapConfig,
+    authority: &Signer,
+    co_signers: &[AccountInfo],
+) -> Result<()> {
+    if config.multisig_threshold == 0 {
+        require_keys_eq!(authority.key(), config.authority, RaceswapError::Unauthorized);
+        return Ok(());
+    }
+
+    let signed = config
+        .multisig_signers
+        .iter()
+        .filter(|signer| {
+            authority.key() == **signer
+                || co_signers
+                    .iter()
+                    .any(|info| info.is_signer && info.key == *signer)
+        })
+        .count();
+    msg!("Multisig: {} of {} required signers present", signed, config.multisig_threshold);
+    require!(
+        signed >= config.multisig_threshold as usize,
+        RaceswapError::MultisigThresholdNotMet
+    );
+    Ok(())
+}
+
+/// Splits the leading signer remaining_accounts (multisig co-signers) from the
+/// instruction's own remaining accounts that follow them.
+fn split_co_signers<'a, 'info>(
+    remaining: &'a [AccountInfo<'info>],
+) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+    let count = remaining.iter().take_while(|info| info.is_signer).count();
+    remaining.split_at(count)
+}
+
+fn validate_multisig(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        signers.len() <= MAX_MULTISIG_SIGNERS && threshold as usize <= signers.len(),
+        RaceswapError::InvalidMultisigConfig
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(
+            !signers[..i].contains(signer),
+            RaceswapError::InvalidMultisigConfig
+        );
+    }
+    Ok(())
+}
+
+fn validate_mint_reflection_configs(entries: &[MintReflectionConfig]) -> Result<()> {
+    require!(
+        entries.len() <= MAX_MINT_REFLECTION_CONFIGS,
+        RaceswapError::InvalidFeeConfig
+    );
+    for (i, entry) in entries.iter().enumerate() {
+        require!(entry.reflection_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+        require!(
+            entries[..i].iter().all(|other| other.input_mint != entry.input_mint),
+            RaceswapError::DuplicateMintReflectionConfig
+        );
+    }
+    Ok(())
+}
+
+/// Rejects quotes generated more than `max_quote_age_slots` ago. A zero max
+/// disables the check; a quote slot in the future is always invalid.
+fn check_quote_age(quote_slot: u64, max_quote_age_slots: u64) -> Result<()> {
+    if max_quote_age_slots == 0 {
+        return Ok(());
+    }
+
+    let current_slot = Clock::get()?.slot;
+    let age = current_slot
+        .checked_sub(quote_slot)
+        .ok_or(RaceswapError::InvalidQuoteSlot)?;
+    require!(age <= max_quote_age_slots, RaceswapError::QuoteTooOld);
+    Ok(())
+}
+
+/// Returns a leg's absolute floor, derived from `quoted_out` when the leg
+/// carries `slippage_bps` instead. Giving both forms is rejected.
+fn leg_floor(min_out: u64, quoted_out: u64, slippage_bps: Option<u16>) -> Result<u64> {
+    let Some(slippage_bps) = slippage_bps else {
+        return Ok(min_out);
+    };
+    require!(min_out == 0, RaceswapError::ConflictingLegFloor);
+    require!(
+        quoted_out > 0 && (slippage_bps as u128) <= FEE_DENOMINATOR,
+        RaceswapError::InvalidLegSlippage
+    );
+
+    let floor = (quoted_out as u128)
+        .checked_mul(FEE_DENOMINATOR - slippage_bps as u128)
+        .ok_or(RaceswapError::MathOverflow)?
+        / FEE_DENOMINATOR;
+    Ok(floor as u64)
+}
+
+/// Returns the floor for the main leg. When the client omits `min_main_out` and
+/// the config does not allow unprotected swaps, the floor is derived from
+/// `quoted_out` using the config's `default_slippage_bps`.
+fn resolve_min_main_out(
+    min_main_out: u64,
+    quoted_out: u64,
+    config: &RaceswapConfig,
+) -> Result<u64> {
+    // A floor at or above the quote can never be met; catch the client bug early
+    if min_main_out > 0 && quoted_out > 0 {
+        require!(min_main_out < quoted_out, RaceswapError::MinOutAboveQuote);
+    }
+
+    if min_main_out > 0 || config.allow_zero_min_out {
+        return Ok(min_main_out);
+    }
+
+    require!(quoted_out > 0, RaceswapError::MissingMinOut);
+
+    let floor = (quoted_out as u128)
+        .checked_mul(FEE_DENOMINATOR - config.default_slippage_bps as u128)
+        .ok_or(RaceswapError::MathOverflow)?
+        .checked_div(FEE_DENOMINATOR)
+        .ok_or(RaceswapError::MathOverflow)? as u64;
+    msg!(
+        "min_main_out omitted, applying default slippage {} bps: floor={}",
+        config.default_slippage_bps,
+        floor
+    );
+    Ok(floor)
+}
+
+/// Rejects a floor looser than the config's global slippage ceiling. With a
+/// ceiling set, `quoted_out` is required to measure the slippage against.
+fn check_max_slippage(min_main_out: u64, quoted_out: u64, max_slippage_bps: u16) -> Result<()> {
+    if max_slippage_bps == 0 {
+        return Ok(());
+    }
+    require!(quoted_out > 0, RaceswapError::SlippageTooLoose);
+
+    let ceiling_floor = (quoted_out as u128)
+        .checked_mul(FEE_DENOMINATOR - max_slippage_bps as u128)
+        .ok_or(RaceswapError::MathOverflow)?
+        / FEE_DENOMINATOR;
+    require!(
+        min_main_out as u128 >= ceiling_floor,
+        RaceswapError::SlippageTooLoose
+    );
+    Ok(())
+}
+
+/// Returns the signer that authorizes the input transfer and pays the SOL fee.
+/// Without a session this is the user, who must then sign; with one, the session
+/// PDA must be registered by the user for the signing session key and unexpired.
+/// The session key must also be an approved delegate on `user_input` for the
+/// token program to accept the transfer.
+fn resolve_input_authority<'info>(
+    user: &AccountInfo<'info>,
+    session_key: Option<&Signer<'info>>,
+    session: Option<&Account<'info, SessionAuthority>>,
+    program_id: &Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let (session_key, session) = match (session_key, session) {
+        (None, None) => {
+            require!(user.is_signer, RaceswapError::UserSignatureRequired);
+            return Ok(user.clone());
+        }
+        (Some(session_key), Some(session)) => (session_key, session),
+        _ => return err!(RaceswapError::InvalidSessionKey),
+    };
+
+    let (expected_session, _) = Pubkey::find_program_address(
+        &[SESSION_SEED, user.key().as_ref(), session_key.key().as_ref()],
+        program_id,
+    );
+    require_keys_eq!(session.key(), expected_session, RaceswapError::InvalidSessionKey);
+    require_keys_eq!(session.user, user.key(), RaceswapError::InvalidSessionKey);
+    require_keys_eq!(session.session_key, session_key.key(), RaceswapError::InvalidSessionKey);
+    require!(
+        Clock::get()?.slot < session.expires_at_slot,
+        RaceswapError::SessionExpired
+    );
+
+    msg!("Input transfer authorized by session key {}", session_key.key());
+    Ok(session_key.to_account_info())
+}
+
+/// Accounts the Jupiter legs draw from: accounts shared between legs, referenced
+/// by index, and each leg's own accounts consumed in order.
+struct LegAccounts<'a, 'info> {
+    jupiter_program: AccountInfo<'info>,
+    shared: &'a [AccountInfo<'info>],
+    unique: Iter<'a, AccountInfo<'info>>,
+    swap_authority_key: Pubkey,
+    /// Own accounts consumed so far by earlier legs
+    consumed: usize,
+    /// PDA-owned vault whose legs are invoked with the swap authority's seeds
+    signing_vault: Option<Pubkey>,
+}
+
+impl<'a, 'info> LegAccounts<'a, 'info> {
+    /// Takes the account for `position` in the leg without advancing past any
+    /// other leg's accounts.
+    fn next_for(
+        &mut self,
+        payload: &SerializedInstruction,
+        position: usize,
+    ) -> Result<&'a AccountInfo<'info>> {
+        match payload.shared_refs.get(position) {
+            Some(&index) if index != UNIQUE_ACCOUNT_REF => self
+                .shared
+                .get(index as usize)
+                .ok_or(RaceswapError::AccountMismatch.into()),
+            _ => {
+                let account = self
+                    .unique
+                    .next()
+                    .ok_or(RaceswapError::AccountMismatch)?;
+                self.consumed += 1;
+                Ok(account)
+            }
+        }
+    }
+
+    /// Number of the leg's own accounts it will take from `unique`
+    fn unique_needed(payload: &SerializedInstruction) -> usize {
+        if payload.shared_refs.is_empty() {
+            payload.accounts_len as usize
+        } else {
+            payload
+                .shared_refs
+                .iter()
+                .filter(|r| **r == UNIQUE_ACCOUNT_REF)
+                .count()
+        }
+    }
+
+    /// Resolves the account at `position` in the next leg without consuming it.
+    fn peek(&self, payload: &SerializedInstruction, position: usize) -> Option<&'a AccountInfo<'info>> {
+        if position >= payload.accounts_len as usize {
+            return None;
+        }
+        if payload.shared_refs.is_empty() {
+            return self.unique.as_slice().get(position);
+        }
+        match *payload.shared_refs.get(position)? {
+            UNIQUE_ACCOUNT_REF => {
+                let unique_before = payload.shared_refs[..position]
+                    .iter()
+                    .filter(|r| **r == UNIQUE_ACCOUNT_REF)
+                    .count();
+                self.unique.as_slice().get(unique_before)
+            }
+            index => self.shared.get(index as usize),
+        }
+    }
+}
+
+fn perform_jupiter_swap<'info>(
+    payload: &SerializedInstruction,
+    legs: &mut LegAccounts<'_, 'info>,
+    authority_seeds: &[&[u8]],
+) -> Result<()> {
+    let (mut ix, infos) = build_leg_cpi(payload, legs)?;
+
+    // A leg that moves tokens out of the PDA-owned vault needs the swap
+    // authority to actually sign, so restore its signer flag and pass the seeds
+    let vault_sourced = matches!(
+        legs.signing_vault,
+        Some(vault) if ix.accounts.iter().any(|meta| meta.pubkey == vault && meta.is_writable)
+    );
+    if vault_sourced {
+        for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == legs.swap_authority_key) {
+            meta.is_signer = true;
+        }
+        msg!("Vault-sourced leg: invoking with swap authority seeds");
+        return invoke_signed(&ix, &infos, &[authority_seeds])
+            .map_err(|_| RaceswapError::SwapCpiFailed.into());
+    }
+
+    // EXPERIMENTAL FIX: Try invoke_signed with NO signer seeds to prevent privilege escalation
+    // This tells Solana we're making a CPI but NOT using PDA signing for THIS specific call
+    // The empty slice means "no PDAs are signing for this CPI"
+    invoke_signed(&ix, &infos, &[]).map_err(|_| RaceswapError::SwapCpiFailed.into())
+}
+
+/// Builds the Jupiter instruction for one leg, consuming exactly `accounts_len`
+/// accounts from the iterator. The returned infos hold the program followed by
+/// the leg's own accounts and nothing else, so a leg can never hand Jupiter
+/// writable accounts that belong to another leg.
+fn build_leg_cpi<'info>(
+    payload: &SerializedInstruction,
+    legs: &mut LegAccounts<'_, 'info>,
+) -> Result<(Instruction, Vec<AccountInfo<'info>>)> {
+    let jupiter_program = legs.jupiter_program.clone();
+    let swap_authority_key = legs.swap_authority_key;
+    let accounts_len = payload.accounts_len as usize;
+    let mut infos: Vec<AccountInfo<'info>> = Vec::with_capacity(accounts_len + 1);
+    let mut metas: Vec<AccountMeta> = Vec::with_capacity(accounts_len);
+    infos.push(jupiter_program.clone());
+
+    for consumed in 0..accounts_len {
+        let account = legs.next_for(payload, consumed)?;
+
+        // Use Jupiter's flags but FORCE swap_authority to NOT be a signer
+        // Our PDA provides signature authority for the wrapper instruction only
+        let is_writable = payload.is_writable.get(consumed).copied().unwrap_or(account.is_writable);
+        let mut is_signer = payload.is_signer.get(consumed).copied().unwrap_or(false);
+
+        // CRITICAL FIX: Force swap_authority PDA to never be a signer in Jupiter's CPI
+        // Even if Jupiter's quote marks it as signer, we don't want privilege escalation
+        if *account.key == swap_authority_key {
+            msg!(">>> FOUND SWAP_AUTHORITY in remaining_accounts at index {}, forcing is_signer=false (was {})", consumed, is_signer);
+            is_signer = false;
+        } else if is_signer {
+            msg!(">>> Account {} at index {} has is_signer=true (not swap_authority)", account.key, consumed);
+        }
+
+        metas.push(AccountMeta {
+            pubkey: *account.key,
+            is_signer,
+            is_writable,
+        });
+        infos.push(account.clone());
+    }
+
+    require!(
+        metas.len() == accounts_len && infos.len() == accounts_len + 1,
+        RaceswapError::AccountMismatch
+    );
+
+    let ix = Instruction {
+        program_id: jupiter_program.key(),
+        accounts: metas,
+        data: payload.data.clone(),
+    };
+
+    Ok((ix, infos))
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [CONFIG_SEED],
+        bump,
+        space = 8 + RaceswapConfig::LEN
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseConfig<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the in-token fee ATAs
+    #[account(
+        seeds = [TREASURY_AUTHORITY_SEED, config.key().as_ref()],
+        bump,
+    )]
+    pub treasury_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury_authority,
+        token::token_program = token_program,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = config.treasury_wallet,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DescribeAccountLayout {}
+
+#[derive(Accounts)]
+#[instruction(params: PreviewSplitParams)]
+pub struct PreviewSplit<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+
+    /// `params.user`'s stats, for the loyalty fee and reflection streak; read only
+    #[account(
+        seeds = [USER_STATS_SEED, params.user.as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// Per-mint treasury fee override for the input mint
+    #[account(
+        seeds = [MINT_FEE_SEED, params.input_mint.as_ref()],
+        bump = mint_fee.bump,
+    )]
+    pub mint_fee: Option<Account<'info, MintFeeOverride>>,
+
+    /// CHECK: Pyth `PriceUpdateV2` for SOL/USD, as for `execute_raceswap`
+    pub sol_usd_price: Option<UncheckedAccount<'info>>,
+
+    /// `params.user`'s token account holding a fee rebate NFT
+    pub rebate_nft: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInputVaultsBatch<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: swap_authority PDA that will own the vaults
+    #[account(
+        seeds = [AUTHORITY_SEED, config.key().as_ref()],
+        bump = config.authority_bump,
+    )]
+    pub swap_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: [mint, vault (writable)] pairs
+}
 
-    while consumed < payload.accounts_len as usize {
-        let account = remaining_iter
-            .next()
-            .ok_or(RaceswapError::AccountMismatch)?;
-        
-        // Use Jupiter's flags but FORCE swap_authority to NOT be a signer
-        // Our PDA provides signature authority for the wrapper instruction only
-        let is_writable = payload.is_writable.get(consumed).copied().unwrap_or(account.is_writable);
-        let mut is_signer = payload.is_signer.get(consumed).copied().unwrap_or(false);
-        
-        // CRITICAL FIX: Force swap_authority PDA to never be a signer in Jupiter's CPI
-        // Even if Jupiter's quote marks it as signer, we don't want privilege escalation
-        if account.key == swap_authority_key {
-            msg!(">>> FOUND SWAP_AUTHORITY in remaining_accounts at index {}, forcing is_signer=false (was {})", consumed, is_signer);
-            is_signer = false;
-        } else if is_signer {
-            msg!(">>> Account {} at index {} has is_signer=true (not swap_authority)", account.key, consumed);
-        }
-        
-        metas.push(AccountMeta {
-            pubkey: *account.key,
-            is_signer,
-            is_writable,
-        });
-        infos.push(account.clone());
-        consumed += 1;
-    }
+#[derive(Accounts)]
+pub struct MigrateAuthoritySeed<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    pub authority: Signer<'info>,
+    /// CHECK: Current swap_authority PDA, verified against AUTHORITY_SEED in the handler
+    pub old_swap_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    // remaining_accounts: the vaults to migrate (writable)
+}
 
-    let ix = Instruction {
-        program_id: jupiter_program.key(),
-        accounts: metas,
-        data: payload.data.clone(),
-    };
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct RegisterSession<'info> {
+    #[account(
+        init,
+        payer = user,
+        seeds = [SESSION_SEED, user.key().as_ref(), session_key.as_ref()],
+        bump,
+        space = 8 + SessionAuthority::LEN
+    )]
+    pub session: Account<'info, SessionAuthority>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-    // EXPERIMENTAL FIX: Try invoke_signed with NO signer seeds to prevent privilege escalation
-    // This tells Solana we're making a CPI but NOT using PDA signing for THIS specific call
-    // The empty slice means "no PDAs are signing for this CPI"
-    invoke_signed(&ix, &infos, &[]).map_err(|_| RaceswapError::SwapCpiFailed.into())
+#[derive(Accounts)]
+pub struct InitUserStats<'info> {
+    #[account(
+        init,
+        payer = user,
+        seeds = [USER_STATS_SEED, user.key().as_ref()],
+        bump,
+        space = 8 + UserStats::LEN
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+pub struct InitProtocolStats<'info> {
     #[account(
         init,
         payer = payer,
+        seeds = [PROTOCOL_STATS_SEED],
+        bump,
+        space = 8 + ProtocolStats::LEN
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotStats<'info> {
+    #[account(seeds = [PROTOCOL_STATS_SEED], bump = protocol_stats.bump)]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: [u8; 8])]
+pub struct RegisterReferral<'info> {
+    #[account(
         seeds = [CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, RaceswapConfig>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [REFERRAL_SEED, code.as_ref()],
         bump,
-        space = 8 + RaceswapConfig::LEN
+        space = 8 + ReferralEntry::LEN
+    )]
+    pub referral: Account<'info, ReferralEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct SetMintFee<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
     )]
     pub config: Account<'info, RaceswapConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [MINT_FEE_SEED, mint.as_ref()],
+        bump,
+        space = 8 + MintFeeOverride::LEN
+    )]
+    pub mint_fee: Account<'info, MintFeeOverride>,
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+pub struct ManageDenyList<'info> {
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bump,
     )]
     pub config: Account<'info, RaceswapConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [DENY_LIST_SEED],
+        bump,
+        space = 8 + DenyList::LEN
+    )]
+    pub deny_list: Account<'info, DenyList>,
+    #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [SESSION_SEED, user.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump,
+        has_one = user @ RaceswapError::Unauthorized,
+    )]
+    pub session: Account<'info, SessionAuthority>,
+    #[account(mut)]
+    pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -459,8 +3403,10 @@ pub struct ExecuteRaceswap<'info> {
     )]
     pub config: Account<'info, RaceswapConfig>,
 
+    /// CHECK: The swapping wallet. Signs the swap unless a registered session key
+    /// authorizes it instead (see `resolve_input_authority`)
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub user: UncheckedAccount<'info>,
 
     #[account(
         constraint = input_mint.key() == params.input_mint
@@ -468,11 +3414,12 @@ pub struct ExecuteRaceswap<'info> {
     /// CHECK: Manually validated in handler to avoid strict InterfaceAccount checks
     pub input_mint: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        owner = input_token_program.key()
-    )]
-    pub user_input: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: The user's input token account, read in the handler once its token
+    /// program ownership is checked. For native-mint input this is the user's
+    /// WSOL ATA, created by the program (and closed again) when missing, and
+    /// topped up from the user's lamports
+    #[account(mut)]
+    pub user_input: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub user_main_destination: InterfaceAccount<'info, TokenAccount>,
@@ -506,11 +3453,96 @@ pub struct ExecuteRaceswap<'info> {
     // than the input token program. We explicitly verify output account ownership in the handler.
     pub input_token_program: Interface<'info, TokenInterface>,
 
-    /// CHECK: Jupiter v6 Aggregator - current mainnet program
-    #[account(address = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"))]
+    /// CHECK: Jupiter aggregator, checked against the config allowlist in the handler
     pub jupiter_program: UncheckedAccount<'info>,
     
     pub system_program: Program<'info, System>,
+
+    /// Optional session key authorizing the swap for smart-wallet users; it pays
+    /// the SOL fee in place of `user`. Must be passed together with `session`.
+    #[account(mut)]
+    pub session_key: Option<Signer<'info>>,
+
+    /// Session registry PDA ([SESSION_SEED, user, session_key]) validated in the handler
+    pub session: Option<Account<'info, SessionAuthority>>,
+
+    /// CHECK: Main output mint, read for decimals in the SwapRate event and its
+    /// Token-2022 transfer fee config; required for a Token-2022 main destination
+    pub main_output_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Optional reflection mint, read for its Token-2022 transfer fee config
+    pub reflection_mint: Option<UncheckedAccount<'info>>,
+
+    /// Treasury_authority PDA's ATA for the input mint; required when
+    /// `fee_in_input_token` is set, validated in the handler
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Per-user fee tracking for the loyalty discount
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED, user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
+    /// Per-mint treasury fee override for the input mint; the default fee
+    /// applies when omitted
+    #[account(
+        seeds = [MINT_FEE_SEED, input_mint.key().as_ref()],
+        bump = mint_fee.bump,
+    )]
+    pub mint_fee: Option<Account<'info, MintFeeOverride>>,
+
+    /// Receives unused input instead of `user_input`; must hold the input mint
+    /// and be owned by the user or an allowed caller program
+    #[account(mut)]
+    pub refund_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Referral registry PDA for `params.referral_code`, validated in the handler
+    pub referral: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Referrer wallet, must match the registry entry
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Instructions sysvar, required when called via CPI with allowed_callers set
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// Token program owning the main destination, for `unwrap_output` when it
+    /// differs from `input_token_program`
+    pub output_token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Lifetime counters, updated on every swap
+    #[account(
+        mut,
+        seeds = [PROTOCOL_STATS_SEED],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    /// Destination of the second reflection leg, holding that leg's mint
+    #[account(mut)]
+    pub second_reflection_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Mints that may not be swapped; omitting it skips the check
+    #[account(seeds = [DENY_LIST_SEED], bump = deny_list.bump)]
+    pub deny_list: Option<Account<'info, DenyList>>,
+
+    /// Receives the reflection in the input token under `ReflectionAction::Transfer`
+    #[account(mut)]
+    pub community_destination: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Pyth `PriceUpdateV2` for SOL/USD, validated in the handler when
+    /// the config sets a fixed USD fee
+    pub sol_usd_price: Option<UncheckedAccount<'info>>,
+
+    /// User's token account holding a fee rebate NFT from `rebate_mints`
+    pub rebate_nft: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Creates the WSOL input account when native-mint input arrives without one
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -527,6 +3559,54 @@ pub struct UpdateConfigParams {
     pub treasury_wallet: Option<Pubkey>,
     pub reflection_fee_bps: Option<u16>,
     pub treasury_fee_bps: Option<u16>,
+    pub default_slippage_bps: Option<u16>,
+    pub allow_zero_min_out: Option<bool>,
+    pub reflection_config_per_mint: Option<Vec<MintReflectionConfig>>,
+    pub max_quote_age_slots: Option<u64>,
+    pub require_canonical_input_ata: Option<bool>,
+    /// `Some(None)` clears the expected platform-fee account
+    pub platform_fee_account: Option<Option<Pubkey>>,
+    pub allowed_callers: Option<Vec<Pubkey>>,
+    pub min_reference_output_bps: Option<u16>,
+    pub max_writable_accounts: Option<u8>,
+    pub max_reflection_override_bps: Option<u16>,
+    pub min_out_tolerance: Option<u64>,
+    pub loyalty_fee_threshold: Option<u64>,
+    pub loyalty_period_slots: Option<u64>,
+    pub loyalty_fee_bps: Option<u16>,
+    pub reflection_min_notional: Option<u64>,
+    pub max_fee_output_bps: Option<u16>,
+    pub multisig_signers: Option<Vec<Pubkey>>,
+    /// 0 disables the multisig and falls back to the single `authority`
+    pub multisig_threshold: Option<u8>,
+    pub max_slippage_bps: Option<u16>,
+    pub fee_holiday_start_slot: Option<u64>,
+    pub fee_holiday_end_slot: Option<u64>,
+    pub streak_window_slots: Option<u64>,
+    pub streak_bonus_bps: Option<u16>,
+    pub max_streak_multiplier_bps: Option<u16>,
+    pub max_input_per_swap: Option<u64>,
+    /// Replaces the Jupiter program allowlist (1-2 entries)
+    pub jupiter_programs: Option<Vec<Pubkey>>,
+    pub max_price_impact_bps: Option<u16>,
+    pub max_mints_per_swap: Option<u8>,
+    /// Replaces the fee rebate NFT mint list (at most 8)
+    pub rebate_mints: Option<Vec<Pubkey>>,
+    /// 0 returns to the bps treasury fee
+    pub fixed_fee_usd_cents: Option<u64>,
+    pub sol_usd_feed_id: Option<[u8; 32]>,
+    pub max_price_age_secs: Option<u64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct MintReflectionConfig {
+    pub input_mint: Pubkey,
+    pub reflection_mint: Pubkey,
+    pub reflection_fee_bps: u16,
+}
+
+impl MintReflectionConfig {
+    pub const LEN: usize = 32 + 32 + 2;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -535,6 +3615,130 @@ pub struct SerializedInstruction {
     pub data: Vec<u8>,
     pub is_writable: Vec<bool>,
     pub is_signer: Vec<bool>,
+    /// Shared-accounts mode: per position, an index into the shared accounts or
+    /// `UNIQUE_ACCOUNT_REF` for the leg's next own account. Empty means every
+    /// account is the leg's own.
+    pub shared_refs: Vec<u8>,
+}
+
+impl SerializedInstruction {
+    /// Sanity-checks the serialized lengths. A truncated client payload shows up
+    /// as missing discriminator bytes or flag vectors shorter than `accounts_len`,
+    /// which would otherwise silently fall back to default flags.
+    pub fn validate(&self) -> Result<()> {
+        let accounts_len = self.accounts_len as usize;
+        require!(
+            self.data.len() >= MIN_LEG_DATA_LEN,
+            RaceswapError::MalformedParams
+        );
+        require!(
+            self.data.len() <= MAX_INSTRUCTION_DATA_LEN,
+            RaceswapError::InstructionDataTooLarge
+        );
+        require!(
+            self.is_writable.len() == accounts_len && self.is_signer.len() == accounts_len,
+            RaceswapError::MalformedParams
+        );
+        require!(
+            self.shared_refs.is_empty() || self.shared_refs.len() == accounts_len,
+            RaceswapError::MalformedParams
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PreviewSplitParams {
+    /// Wallet the preview is for; its stats and rebate NFT apply
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub total_input_amount: u64,
+    pub disable_reflection: bool,
+    pub reflection_mode: ReflectionMode,
+    pub reflection_amount_override: Option<u64>,
+    pub fee_in_input_token: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SwapSplit {
+    pub reflection_amount: u64,
+    pub main_input_amount: u64,
+    pub treasury_fee_lamports: u64,
+    pub treasury_fee_tokens: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LegKind {
+    Reflection,
+    Main,
+}
+
+/// What a remaining account is for, as reported by `describe_account_layout`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AccountRole {
+    /// Referenced by index from any leg's `shared_refs`
+    Shared,
+    Reflection { leg: u8 },
+    Main,
+    Topup,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccountLayout {
+    pub total: u16,
+    pub roles: Vec<AccountRole>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReflectionLeg {
+    /// Output mint the route was built for
+    pub mint: Pubkey,
+    pub min_out: u64,
+    /// Share of the reflection amount; weights sum to 10_000 across two legs
+    /// and are ignored for a single leg
+    pub weight_bps: u16,
+    pub instruction: SerializedInstruction,
+}
+
+/// Outcome of one leg, returned as a `Vec<LegResult>` after the `SwapStatus`
+/// in return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegResult {
+    pub leg_kind: LegKind,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub out_mint: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReflectionMode {
+    /// Carve the reflection from the input before the main leg (reflection leg first)
+    #[default]
+    Input,
+    /// Swap a share of the realized main output after the main leg (main leg first)
+    Output,
+}
+
+/// What happens to the input-side reflection amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReflectionAction {
+    /// Swap it through the reflection legs
+    #[default]
+    Swap,
+    /// Send it in the input token to `community_destination`, signed by the vault authority
+    Transfer,
+}
+
+/// Why no input-side reflection was taken, reported in `SwapExecuted` and `ReflectionSkipped`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReflectionSkipReason {
+    None,
+    /// `disable_reflection` was set by the caller
+    Disabled,
+    /// `total_input_amount` was below the config's `reflection_min_notional`
+    BelowNotional,
+    /// The computed reflection rounded down to zero
+    Dust,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -542,12 +3746,73 @@ pub struct ExecuteRaceswapParams {
     pub input_mint: Pubkey,
     pub main_output_mint: Pubkey,
     pub reflection_mint: Pubkey,
+    /// Must be 0 when `amount_is_max` is set
     pub total_input_amount: u64,
+    /// Swap the entire user_input balance instead of `total_input_amount`
+    pub amount_is_max: bool,
     pub min_main_out: u64,
-    pub min_reflection_out: u64,
+    /// Quoted main output, used to derive a floor when `min_main_out` is 0
+    pub quoted_out: u64,
+    /// Main floor as bps below `quoted_out`; requires `min_main_out == 0`
+    pub main_slippage_bps: Option<u16>,
+    /// Quoted reflection output for `reflection_slippage_bps`
+    pub reflection_quoted_out: u64,
+    /// First reflection leg's floor as bps below `reflection_quoted_out`;
+    /// requires that leg's `min_out == 0`
+    pub reflection_slippage_bps: Option<u16>,
+    /// Slot at which the Jupiter quote was generated
+    pub quote_slot: u64,
     pub disable_reflection: bool,
+    pub reflection_mode: ReflectionMode,
+    /// Explicit reflection amount used instead of the bps computation, capped by
+    /// the config's `max_reflection_override_bps` share of the input
+    pub reflection_amount_override: Option<u64>,
+    /// Position of Jupiter's platform-fee account within the main leg's accounts
+    pub platform_fee_account_index: Option<u16>,
+    /// Number of leading remaining_accounts shared between legs
+    pub shared_accounts_len: u8,
+    /// Emit a SwapDebug event with raw before/after balances
+    pub debug: bool,
+    /// Verify user_input ends exactly `total_input_amount` lower than it started
+    pub reconcile_input: bool,
+    /// Collect the treasury fee in the input token (to the treasury PDA's ATA)
+    /// instead of SOL
+    pub fee_in_input_token: bool,
+    /// Short code resolved against the referral registry; the referrer gets
+    /// its registered share of the SOL treasury fee
+    pub referral_code: Option<[u8; 8]>,
+    /// Require Jupiter's returned out_amount to reach min_main_out and match
+    /// the measured main delta within `min_out_tolerance`
+    pub check_jupiter_out: bool,
+    /// Quoted main output; the realized main delta may fall at most
+    /// `max_impact_bps` below it (0 disables the check)
+    pub expected_out: u64,
+    pub max_impact_bps: u16,
+    /// Reference price as input units per output unit (0 disables the floor)
+    pub reference_price_numerator: u64,
+    pub reference_price_denominator: u64,
     pub main_leg: Option<SerializedInstruction>,
-    pub reflection_leg: Option<SerializedInstruction>,
+    /// Up to two reflection legs. The first delivers the effective reflection
+    /// mint into `user_reflection_destination`, the second its own mint into
+    /// `second_reflection_destination`; one leg behaves as the old single leg
+    pub reflection_legs: Vec<ReflectionLeg>,
+    /// Close a WSOL main destination after the swap so the output arrives as
+    /// native SOL. All-or-nothing: the destination must start the swap empty
+    pub unwrap_output: bool,
+    /// Invoke legs that write to the input vault with the swap authority's
+    /// seeds, for routes where the vault PDA must authorize the transfer
+    pub vault_authority_signs: bool,
+    /// Cover a main shortfall below the floor with `topup_leg`, a route from
+    /// the reflection destination into the main destination signed by the user
+    pub topup_from_reflection: bool,
+    /// Accounts follow the reflection and main legs; consumed unused when no
+    /// top-up is needed
+    pub topup_leg: Option<SerializedInstruction>,
+    /// Swap the input-side reflection, or transfer it to `community_destination`
+    pub reflection_action: ReflectionAction,
+    /// Largest remaining_accounts count the caller's compute budget can carry
+    /// (0 disables)
+    pub max_accounts: u8,
 }
 
 #[account]
@@ -558,10 +3823,180 @@ pub struct RaceswapConfig {
     pub treasury_fee_bps: u16,
     pub bump: u8,
     pub authority_bump: u8,
+    pub default_slippage_bps: u16,
+    pub allow_zero_min_out: bool,
+    pub reflection_config_per_mint: Vec<MintReflectionConfig>,
+    pub max_quote_age_slots: u64,
+    pub require_canonical_input_ata: bool,
+    pub platform_fee_account: Option<Pubkey>,
+    pub allowed_callers: Vec<Pubkey>,
+    pub min_reference_output_bps: u16,
+    pub max_writable_accounts: u8,
+    pub max_reflection_override_bps: u16,
+    pub min_out_tolerance: u64,
+    /// Lamports of SOL fees paid within a period after which `loyalty_fee_bps`
+    /// applies (0 disables)
+    pub loyalty_fee_threshold: u64,
+    /// Length of a loyalty period in slots (0 never resets)
+    pub loyalty_period_slots: u64,
+    pub loyalty_fee_bps: u16,
+    /// Swaps with `total_input_amount` below this skip reflection (0 disables)
+    pub reflection_min_notional: u64,
+    /// Max treasury fee as a share of the main output value (0 disables)
+    pub max_fee_output_bps: u16,
+    /// Set for the duration of `execute_raceswap` to detect reentrant calls
+    pub in_progress: bool,
+    pub multisig_signers: Vec<Pubkey>,
+    /// Distinct `multisig_signers` required for admin instructions (0 = single authority)
+    pub multisig_threshold: u8,
+    /// Loosest slippage (min_main_out vs quoted_out) any swap may use (0 disables)
+    pub max_slippage_bps: u16,
+    pub paused: bool,
+    /// Treasury fee is waived for slots in [start, end); 0/0 disables
+    pub fee_holiday_start_slot: u64,
+    pub fee_holiday_end_slot: u64,
+    /// Max slots between swaps for the reflection streak to continue (0 disables)
+    pub streak_window_slots: u64,
+    /// Multiplier added per consecutive swap, in bps of 1x
+    pub streak_bonus_bps: u16,
+    /// Cap on the streak multiplier in bps (10_000 = no boost)
+    pub max_streak_multiplier_bps: u16,
+    /// Largest `total_input_amount` a single swap may use (0 disables)
+    pub max_input_per_swap: u64,
+    /// Jupiter programs swaps may route through; v6 by default
+    pub jupiter_programs: Vec<Pubkey>,
+    /// Toggled by `set_circuit_breaker`; tightens the price-impact check
+    pub circuit_breaker_active: bool,
+    /// Price-impact ceiling applied to every swap while the breaker is active
+    pub max_price_impact_bps: u16,
+    /// Flat SOL treasury fee in USD cents, priced from `sol_usd_feed_id` at
+    /// swap time (0 keeps the bps fee)
+    pub fixed_fee_usd_cents: u64,
+    /// Pyth SOL/USD feed id the price account must carry
+    pub sol_usd_feed_id: [u8; 32],
+    /// Oldest acceptable price publish time, in seconds (60 by default; must
+    /// be non-zero while `fixed_fee_usd_cents` is set)
+    pub max_price_age_secs: u64,
+    /// Mints whose swaps are rejected, as input or main output (at most 8)
+    pub paused_mints: Vec<Pubkey>,
+    /// Most distinct mints the route's token accounts may hold (0 disables)
+    pub max_mints_per_swap: u8,
+    /// NFT mints whose holders pay no treasury fee
+    pub rebate_mints: Vec<Pubkey>,
 }
 
 impl RaceswapConfig {
-    pub const LEN: usize = 32 + 32 + 2 + 2 + 1 + 1;
+    pub const LEN: usize = 32 + 32 + 2 + 2 + 1 + 1 + 2 + 1
+        + 4 + MAX_MINT_REFLECTION_CONFIGS * MintReflectionConfig::LEN
+        + 8 + 1 + (1 + 32)
+        + 4 + MAX_ALLOWED_CALLERS * 32
+        + 2 + 1 + 2 + 8
+        + 8 + 8 + 2
+        + 8 + 2
+        + 1
+        + 4 + MAX_MULTISIG_SIGNERS * 32 + 1
+        + 2 + 1
+        + 8 + 8
+        + 8 + 2 + 2
+        + 8
+        + 4 + MAX_JUPITER_PROGRAMS * 32
+        + 1 + 2
+        + 8 + 32 + 8
+        + 4 + MAX_PAUSED_MINTS * 32
+        + 1
+        + 4 + MAX_REBATE_MINTS * 32;
+
+    pub fn reflection_config_for(&self, input_mint: &Pubkey) -> Option<&MintReflectionConfig> {
+        self.reflection_config_per_mint
+            .iter()
+            .find(|entry| entry.input_mint == *input_mint)
+    }
+
+    /// Reflection bps for `input_mint`: its per-mint entry, else the default
+    pub fn reflection_fee_bps_for(&self, input_mint: &Pubkey) -> u16 {
+        self.reflection_config_for(input_mint)
+            .map_or(self.reflection_fee_bps, |entry| entry.reflection_fee_bps)
+    }
+}
+
+#[account]
+pub struct SessionAuthority {
+    pub user: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at_slot: u64,
+    pub bump: u8,
+}
+
+impl SessionAuthority {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+#[account]
+pub struct UserStats {
+    pub user: Pubkey,
+    pub period_start_slot: u64,
+    /// SOL treasury fees paid in the current period, in lamports
+    pub period_fees: u64,
+    pub bump: u8,
+    pub last_swap_slot: u64,
+    /// Consecutive swaps within `streak_window_slots` of each other
+    pub streak: u16,
+}
+
+impl UserStats {
+    pub const LEN: usize = 32 + 8 + 8 + 1 + 8 + 2;
+}
+
+/// Lifetime protocol totals for dashboards
+#[account]
+pub struct ProtocolStats {
+    pub total_swaps: u64,
+    /// SOL treasury fees, including any referral share
+    pub total_treasury_lamports: u128,
+    /// Sum of `total_input_amount` across swaps, in raw input units
+    pub total_volume: u128,
+    pub bump: u8,
+    /// Input-token treasury fees (`fee_in_input_token`), in raw input units
+    pub total_treasury_tokens: u128,
+}
+
+impl ProtocolStats {
+    pub const LEN: usize = 8 + 16 + 16 + 1 + 16;
+}
+
+#[account]
+pub struct ReferralEntry {
+    pub code: [u8; 8],
+    pub referrer: Pubkey,
+    /// Share of the treasury fee paid to the referrer
+    pub fee_share_bps: u16,
+    pub bump: u8,
+}
+
+impl ReferralEntry {
+    pub const LEN: usize = 8 + 32 + 2 + 1;
+}
+
+/// Authority-managed mints blocked from `execute_raceswap`
+#[account]
+pub struct DenyList {
+    pub mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl DenyList {
+    pub const LEN: usize = 4 + MAX_DENIED_MINTS * 32 + 1;
+}
+
+#[account]
+pub struct MintFeeOverride {
+    pub mint: Pubkey,
+    pub treasury_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl MintFeeOverride {
+    pub const LEN: usize = 32 + 2 + 1;
 }
 
 #[event]
@@ -572,6 +4007,15 @@ pub struct ConfigUpdated {
     pub treasury_fee_bps: u16,
 }
 
+/// Emitted when no input-side reflection is taken, so a caller's opt-out can
+/// be told apart from a reflection that rounded to zero
+#[event]
+pub struct ReflectionSkipped {
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub reason: ReflectionSkipReason,
+}
+
 #[event]
 pub struct SwapExecuted {
     pub user: Pubkey,
@@ -582,6 +4026,41 @@ pub struct SwapExecuted {
     pub main_amount: u64,
     pub reflection_amount: u64,
     pub treasury_amount: u64,
+    pub reflection_skip: ReflectionSkipReason,
+    /// Slot the swap executed in, for correlating without indexer metadata
+    pub slot: u64,
+}
+
+#[event]
+pub struct SwapDebug {
+    pub main_before: u64,
+    pub main_after: u64,
+    pub reflection_before: u64,
+    pub reflection_after: u64,
+    pub vault_before: u64,
+    pub vault_after: u64,
+}
+
+#[event]
+pub struct SwapRate {
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub main_output_mint: Pubkey,
+    pub main_input_amount: u64,
+    pub main_amount: u64,
+    /// Output per unit of input, scaled by 1e9
+    pub rate: u64,
+    pub decimals_adjusted: bool,
+}
+
+#[event]
+pub struct StatsSnapshot {
+    pub total_volume: u128,
+    /// Lifetime SOL treasury fees
+    pub total_fees_collected: u128,
+    /// Lifetime swap count
+    pub swap_nonce: u64,
+    pub slot: u64,
 }
 
 #[error_code]
@@ -632,4 +4111,126 @@ pub enum RaceswapError {
     InvalidInputMintOwner,
     #[msg("Invalid input mint")]
     InvalidInputMint,
+    #[msg("min_main_out omitted without a quoted_out to derive a default floor")]
+    MissingMinOut,
+    #[msg("Session key is not registered for this user")]
+    InvalidSessionKey,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("user must sign without a session key, and for native SOL input or unwrap_output")]
+    UserSignatureRequired,
+    #[msg("Input mint configured more than once in per-mint reflection config")]
+    DuplicateMintReflectionConfig,
+    #[msg("Malformed params: serialized leg is truncated")]
+    MalformedParams,
+    #[msg("Quote is older than the configured maximum age")]
+    QuoteTooOld,
+    #[msg("Quote slot is in the future")]
+    InvalidQuoteSlot,
+    #[msg("Invalid authority seed migration")]
+    InvalidAuthoritySeed,
+    #[msg("Main leg consumed more than total input minus reflection")]
+    MainInputExceeded,
+    #[msg("User input must be the canonical associated token account")]
+    NonCanonicalInputAccount,
+    #[msg("User input balance is below total_input_amount")]
+    InsufficientInputBalance,
+    #[msg("Main leg platform-fee account is not the configured one")]
+    WrongPlatformFeeAccount,
+    #[msg("Calling program is not in allowed_callers")]
+    CallerNotAllowed,
+    #[msg("Jupiter instruction data exceeds MAX_INSTRUCTION_DATA_LEN")]
+    InstructionDataTooLarge,
+    #[msg("Main output is below the reference price floor")]
+    BelowReferenceFloor,
+    #[msg("Swap touches more writable accounts than max_writable_accounts")]
+    TooManyWritableAccounts,
+    #[msg("Reflection override exceeds the allowed share of input")]
+    InvalidReflectionOverride,
+    #[msg("Creating the WSOL input account needs the associated token program")]
+    MissingAssociatedTokenProgram,
+    #[msg("user_input belongs to a different token program than input_token_program")]
+    InputTokenProgramMismatch,
+    #[msg("Reflection leg accounts were consumed by an earlier leg; check account ordering")]
+    ReflectionAccountsConsumed,
+    #[msg("User input balance does not reconcile with the swapped amount")]
+    InputReconciliationFailed,
+    #[msg("Vault batch must be non-empty [mint, vault] pairs within the batch limit")]
+    InvalidVaultBatch,
+    #[msg("Main leg consumed input but user_main_destination received nothing")]
+    MainOutputNotReceived,
+    #[msg("min_main_out must be below quoted_out")]
+    MinOutAboveQuote,
+    #[msg("Referral code is not registered")]
+    UnknownReferralCode,
+    #[msg("Referrer account does not match the registered referral")]
+    InvalidReferrer,
+    #[msg("Treasury fee is disproportionate to the swap output")]
+    FeeDisproportionate,
+    #[msg("execute_raceswap re-entered while a swap is in progress")]
+    ReentrancyDetected,
+    #[msg("Not enough multisig signers")]
+    MultisigThresholdNotMet,
+    #[msg("Multisig signers must be unique, at most 8, and cover the threshold")]
+    InvalidMultisigConfig,
+    #[msg("Slippage is looser than the configured maximum")]
+    SlippageTooLoose,
+    #[msg("Swaps are paused")]
+    ProgramPaused,
+    #[msg("Fee holiday start slot must be before its end slot")]
+    InvalidFeeHoliday,
+    #[msg("Reflection leg output mint does not match reflection_mint")]
+    ReflectionMintMismatch,
+    #[msg("Refund destination must hold the input mint and be owned by the user or an allowed caller")]
+    InvalidRefundDestination,
+    #[msg("Jupiter-reported output disagrees with the measured balance change")]
+    OutputAccountingMismatch,
+    #[msg("Main output fell too far below the quoted expected_out")]
+    ExcessivePriceImpact,
+    #[msg("A leg may set an absolute min_out or slippage_bps, not both")]
+    ConflictingLegFloor,
+    #[msg("Leg slippage_bps needs a quoted output and at most 10_000 bps")]
+    InvalidLegSlippage,
+    #[msg("unwrap_output needs an empty WSOL main destination and its token program")]
+    InvalidUnwrapOutput,
+    #[msg("total_input_amount exceeds the configured max_input_per_swap")]
+    InputExceedsMax,
+    #[msg("At most 2 reflection legs, with weights summing to 10_000")]
+    InvalidReflectionLegs,
+    #[msg("Jupiter program not in the allowlist, or allowlist empty or over 2 entries")]
+    InvalidJupiterProgram,
+    #[msg("Top-up needs reflection output in a destination separate from main")]
+    TopupUnavailable,
+    #[msg("Top-up drew more than the reflection leg delivered")]
+    ReflectionOverdrawn,
+    #[msg("User SOL balance cannot cover the treasury fee (amounts in the logs)")]
+    InsufficientSolForFee,
+    #[msg("Swap involves a mint on the deny-list")]
+    MintDenied,
+    #[msg("Mint already denied, not denied, or deny-list full (16)")]
+    InvalidDenyListUpdate,
+    #[msg("Circuit breaker is active: swaps must quote expected_out")]
+    CircuitBreakerNeedsQuote,
+    #[msg("Transfer reflection needs input mode and a community destination holding the input mint")]
+    InvalidReflectionAction,
+    #[msg("Route passes more remaining_accounts than max_accounts")]
+    RouteTooLarge,
+    #[msg("SOL/USD price account is missing, unverified, for another feed, or non-positive")]
+    InvalidPriceAccount,
+    #[msg("SOL/USD price is older than max_price_age_secs")]
+    StalePrice,
+    #[msg("Swaps of this input or output mint are paused")]
+    MintPaused,
+    #[msg("At most 8 mints may be paused at once")]
+    TooManyPausedMints,
+    #[msg("Config cannot be closed while a swap is in progress")]
+    ConfigInUse,
+    #[msg("Input or destination token account is not owned by the signing user")]
+    OwnerMismatch,
+    #[msg("Route touches more distinct mints than max_mints_per_swap")]
+    TooManyMints,
+    #[msg("Token-2022 mints with a transfer hook are not supported")]
+    TransferHookUnsupported,
+    #[msg("Rebate NFT must be a rebate_mints mint held by the user with a balance of 1 (at most 8 mints)")]
+    InvalidRebateNft,
 }
@@ -3,10 +3,22 @@ use anchor_lang::solana_program::{
     instruction::{AccountMeta, Instruction},
     program::invoke,
 };
-use anchor_lang::system_program;
+use anchor_spl::token_interface::TokenAccount;
+use raceswap_common::validation::{
+    check_amount, check_deadline, check_instruction_data, check_min_out, compute_treasury_fee,
+    validate_jupiter_program, validate_treasury_account, JUPITER_PROGRAM_ID,
+};
+use raceswap_common::{
+    distribute_sol_fee, split_fee_recipients, validate_fee_shares, FeeShare, SwapStatus,
+};
 
 declare_id!("Cy63SzwBBCP5ywaByjUrLuUXQ4pXP9nR7e7kdQqp5uLk");
 
+const TREASURY_CONFIG_SEED: &[u8] = b"raceswap-treasury-config";
+const DEFAULT_TREASURY_FEE_BPS: u16 = 20;
+/// Fee treasury until a treasury config is initialized
+const DEFAULT_TREASURY: Pubkey = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L");
+
 /// Simplified Raceswap - Non-custodial Jupiter wrapper
 /// Key design: USER signs for Jupiter, not a PDA
 #[program]
@@ -15,25 +27,45 @@ pub mod raceswap {
 
     /// Execute a Jupiter swap with treasury fee
     /// This is the SIMPLEST working version - single leg only
-    pub fn execute_swap(ctx: Context<ExecuteSwap>, params: ExecuteSwapParams) -> Result<()> {
+    pub fn execute_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteSwap<'info>>,
+        params: ExecuteSwapParams,
+    ) -> Result<()> {
         msg!("ExecuteSwap: amount={}, min_out={}", params.amount, params.min_out);
+        check_amount(params.amount)?;
+        check_instruction_data(&params.jupiter_data)?;
+        check_deadline(params.deadline_unix_ts)?;
+
+        // 1. Take treasury fee in SOL (treasury config rate, 0.2% = 20 bps by default)
+        // Safe to charge first: a failed Jupiter invoke aborts the instruction
+        // and the runtime discards this lamport transfer along with it.
+        let treasury_config = load_treasury_config(&ctx.accounts.treasury_config)?;
+        let fee_bps = treasury_config
+            .as_ref()
+            .map_or(DEFAULT_TREASURY_FEE_BPS, |config| config.treasury_fee_bps);
+        let treasury_fee_lamports = compute_treasury_fee(params.amount, fee_bps)?;
 
-        // 1. Take treasury fee in SOL (0.2% = 20 bps)
-        let treasury_fee_lamports = (params.amount as u64)
-            .checked_mul(20)
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap();
+        require_keys_eq!(
+            ctx.accounts.treasury.key(),
+            treasury_config.as_ref().map_or(DEFAULT_TREASURY, |config| config.treasury),
+            RaceswapError::InvalidTreasuryAccount
+        );
+        validate_treasury_account(&ctx.accounts.treasury)?;
+        validate_jupiter_program(&ctx.accounts.jupiter_program, &JUPITER_PROGRAM_ID)?;
+
+        // Leading remaining_accounts are the recipients of the treasury
+        // config's fee split, never one chosen by the caller
+        let fee_shares = treasury_config.map_or_else(Vec::new, |config| config.fee_shares);
+        let (fee_recipients, jupiter_remaining) =
+            split_fee_recipients(ctx.remaining_accounts, fee_shares.len())?;
 
         if treasury_fee_lamports > 0 {
-            system_program::transfer(
-                CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.user.to_account_info(),
-                        to: ctx.accounts.treasury.to_account_info(),
-                    },
-                ),
+            distribute_sol_fee(
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.treasury.to_account_info(),
+                fee_recipients,
+                &fee_shares,
                 treasury_fee_lamports,
             )?;
             msg!("Treasury fee paid: {} lamports", treasury_fee_lamports);
@@ -44,20 +76,90 @@ pub mod raceswap {
         // No PDA signing needed!
         let jupiter_ix = Instruction {
             program_id: ctx.accounts.jupiter_program.key(),
-            accounts: params.jupiter_accounts,
+            accounts: params
+                .jupiter_accounts
+                .iter()
+                .map(|acc| AccountMeta {
+                    pubkey: acc.pubkey,
+                    is_signer: acc.is_signer,
+                    is_writable: acc.is_writable,
+                })
+                .collect(),
             data: params.jupiter_data,
         };
 
         // Collect all account infos for the CPI
         let mut account_infos = vec![ctx.accounts.jupiter_program.to_account_info()];
-        account_infos.extend(ctx.remaining_accounts.iter().map(|a| a.clone()));
+        account_infos.extend(jupiter_remaining.iter().cloned());
 
         msg!("Invoking Jupiter with {} accounts", account_infos.len());
+        let destination_before = ctx.accounts.user_destination.amount;
         invoke(&jupiter_ix, &account_infos)?;
+        ctx.accounts.user_destination.reload()?;
+        let main_out = check_min_out(
+            destination_before,
+            ctx.accounts.user_destination.amount,
+            params.min_out,
+        )?;
 
         msg!("Swap completed successfully!");
+        SwapStatus::ok(main_out, treasury_fee_lamports).set()?;
         Ok(())
     }
+
+    /// One-time setup of the treasury `execute_swap` pays fees to, replacing
+    /// the built-in one. Only the program's upgrade authority can run it
+    pub fn initialize_treasury_config(
+        ctx: Context<InitializeTreasuryConfig>,
+        authority: Pubkey,
+        treasury: Pubkey,
+        treasury_fee_bps: u16,
+    ) -> Result<()> {
+        require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+
+        let treasury_config = &mut ctx.accounts.treasury_config;
+        treasury_config.authority = authority;
+        treasury_config.treasury = treasury;
+        treasury_config.bump = ctx.bumps.treasury_config;
+        treasury_config.fee_shares = Vec::new();
+        treasury_config.treasury_fee_bps = treasury_fee_bps;
+        Ok(())
+    }
+
+    /// Rotate the fee treasury without a redeploy
+    pub fn update_treasury(ctx: Context<UpdateTreasury>, new_treasury: Pubkey) -> Result<()> {
+        ctx.accounts.treasury_config.treasury = new_treasury;
+        msg!("Treasury updated: {}", new_treasury);
+        Ok(())
+    }
+
+    /// Changes the fee rate `execute_swap` charges, capped at 10%
+    pub fn set_treasury_fee_bps(ctx: Context<UpdateTreasury>, treasury_fee_bps: u16) -> Result<()> {
+        require!(treasury_fee_bps <= 1_000, RaceswapError::InvalidFeeConfig);
+        ctx.accounts.treasury_config.treasury_fee_bps = treasury_fee_bps;
+        msg!("Treasury fee updated: {} bps", treasury_fee_bps);
+        Ok(())
+    }
+
+    /// Sets how SOL fees are split, treasury first; an empty split sends the
+    /// whole fee to the treasury
+    pub fn set_fee_shares(ctx: Context<UpdateTreasury>, fee_shares: Vec<FeeShare>) -> Result<()> {
+        validate_fee_shares(&fee_shares)?;
+        ctx.accounts.treasury_config.fee_shares = fee_shares;
+        Ok(())
+    }
+}
+
+/// The treasury config PDA's contents, or `None` while it is uninitialized
+/// (fees are then 20 bps, paid whole to the built-in treasury)
+fn load_treasury_config(treasury_config: &UncheckedAccount) -> Result<Option<TreasuryConfig>> {
+    if treasury_config.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*treasury_config.owner, crate::ID, RaceswapError::InvalidTreasuryAccount);
+    Ok(Some(TreasuryConfig::try_deserialize(
+        &mut &treasury_config.try_borrow_data()?[..],
+    )?))
 }
 
 #[derive(Accounts)]
@@ -65,11 +167,19 @@ pub struct ExecuteSwap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    /// CHECK: Treasury wallet - receives SOL fees
-    #[account(
-        mut,
-        address = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L")
-    )]
+    /// User's token account for the output mint; its balance delta across the
+    /// Jupiter CPI is the filled amount checked against `min_out`
+    #[account(mut, token::authority = user)]
+    pub user_destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Treasury config PDA, may be uninitialized (the fee then falls
+    /// back to 20 bps, paid to the built-in treasury)
+    #[account(seeds = [TREASURY_CONFIG_SEED], bump)]
+    pub treasury_config: UncheckedAccount<'info>,
+
+    /// CHECK: Treasury wallet - receives SOL fees; checked against the
+    /// treasury config in the handler
+    #[account(mut)]
     pub treasury: UncheckedAccount<'info>,
 
     /// CHECK: Jupiter v6 program
@@ -80,16 +190,78 @@ pub struct ExecuteSwap<'info> {
     // remaining_accounts contains all Jupiter accounts
 }
 
+#[derive(Accounts)]
+pub struct InitializeTreasuryConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [TREASURY_CONFIG_SEED],
+        bump,
+        space = 8 + TreasuryConfig::LEN
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Raceswap>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ RaceswapError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [TREASURY_CONFIG_SEED],
+        bump = treasury_config.bump,
+        has_one = authority @ RaceswapError::Unauthorized,
+    )]
+    pub treasury_config: Account<'info, TreasuryConfig>,
+    pub authority: Signer<'info>,
+}
+
+/// Authority-rotatable fee destination and rate
+#[account]
+pub struct TreasuryConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub bump: u8,
+    /// SOL fee split, treasury first; empty sends the whole fee to the treasury
+    pub fee_shares: Vec<FeeShare>,
+    pub treasury_fee_bps: u16,
+}
+
+impl TreasuryConfig {
+    pub const LEN: usize = 32 + 32 + 1 + FeeShare::VEC_LEN + 2;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ExecuteSwapParams {
     pub amount: u64,
     pub min_out: u64,
-    pub jupiter_accounts: Vec<AccountMeta>,
+    pub jupiter_accounts: Vec<SerializableAccountMeta>,
     pub jupiter_data: Vec<u8>,
+    /// Reject the swap (before any fee is taken) once this unix time has passed
+    pub deadline_unix_ts: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SerializableAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
 }
 
 #[error_code]
 pub enum RaceswapError {
-    #[msg("Invalid amount")]
-    InvalidAmount,
+    #[msg("Treasury does not match the treasury config")]
+    InvalidTreasuryAccount,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
 }
@@ -0,0 +1,82 @@
+use crate::fixture::{instruction, leg, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+fn describe(fixture: &mut Fixture, swap: &Swap) -> AccountLayout {
+    let ix = instruction(
+        &accounts::DescribeAccountLayout {},
+        instruction::DescribeAccountLayout {
+            params: swap.params.clone(),
+        },
+        &[],
+    );
+    fixture.ledger.process(&ix).unwrap();
+    let (_, data) = fixture.ledger.return_data().unwrap();
+    AccountLayout::try_from_slice(&data).unwrap()
+}
+
+#[test]
+fn layout_matches_the_default_swap() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(1_000_000, 9_000, 50);
+    let layout = describe(&mut fixture, &swap);
+
+    assert_eq!(layout.total as usize, swap.remaining.len());
+    assert!(
+        layout.roles
+            == [
+                AccountRole::Reflection { leg: 0 },
+                AccountRole::Reflection { leg: 0 },
+                AccountRole::Main,
+                AccountRole::Main,
+                AccountRole::Main,
+            ]
+    );
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn output_mode_lists_the_main_leg_first() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reflection_mode = ReflectionMode::Output;
+    let layout = describe(&mut fixture, &swap);
+
+    assert_eq!(layout.total, 5);
+    assert!(layout.roles[..3] == [AccountRole::Main; 3]);
+    assert!(layout.roles[3..] == [AccountRole::Reflection { leg: 0 }; 2]);
+}
+
+#[test]
+fn shared_accounts_lead_and_top_up_accounts_trail() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.shared_accounts_len = 2;
+    swap.params.reflection_legs[0].instruction.shared_refs = vec![0, UNIQUE_ACCOUNT_REF];
+    swap.params.main_leg.as_mut().unwrap().shared_refs = vec![0, UNIQUE_ACCOUNT_REF, 1];
+    swap.params.topup_leg = Some(leg(route_data(20, 10), &[true, true]));
+    swap.remaining = vec![
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new_readonly(fixture.swap_authority, false),
+        AccountMeta::new(fixture.user_reflection, false),
+        AccountMeta::new(fixture.user_main, false),
+        AccountMeta::new(fixture.user_reflection, false),
+        AccountMeta::new(fixture.user_main, false),
+    ];
+    let layout = describe(&mut fixture, &swap);
+
+    assert_eq!(layout.total as usize, swap.remaining.len());
+    assert!(
+        layout.roles
+            == [
+                AccountRole::Shared,
+                AccountRole::Shared,
+                AccountRole::Reflection { leg: 0 },
+                AccountRole::Main,
+                AccountRole::Topup,
+                AccountRole::Topup,
+            ]
+    );
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
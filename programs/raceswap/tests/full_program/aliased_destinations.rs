@@ -0,0 +1,46 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+/// A swap where the reflection leg buys the main mint into the main
+/// destination, with floors of 9_900 (main) and 50 (reflection).
+fn aliased_swap(fixture: &Fixture, main_out: u64, reflection_out: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, main_out, reflection_out);
+    swap.params.min_main_out = 9_900;
+    swap.params.reflection_mint = fixture.main_mint;
+    swap.params.reflection_legs[0].mint = fixture.main_mint;
+    swap.params.reflection_legs[0].min_out = 50;
+    swap.accounts.user_reflection_destination = fixture.user_main;
+    swap.remaining[1].pubkey = fixture.user_main;
+    swap
+}
+
+#[test]
+fn aliased_legs_are_checked_against_the_combined_delta() {
+    let mut fixture = Fixture::new();
+    // Main alone misses its floor by 10, but reflection over-delivers by 20
+    let swap = aliased_swap(&fixture, 9_890, 70);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_960);
+}
+
+#[test]
+fn aliased_legs_below_the_combined_floor_are_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = aliased_swap(&fixture, 9_890, 55);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainBelowMinOut)
+    );
+}
+
+#[test]
+fn reflection_credit_is_not_counted_again_in_the_main_delta() {
+    let mut fixture = Fixture::new();
+    // 9_000 + 900 misses the 9_950 combined floor; counting the reflection
+    // credit twice would clear it
+    let swap = aliased_swap(&fixture, 9_000, 900);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainBelowMinOut)
+    );
+}
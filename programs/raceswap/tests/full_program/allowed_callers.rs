@@ -0,0 +1,82 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar;
+use std::rc::Rc;
+
+/// Registers a program that forwards its accounts (less the trailing raceswap
+/// program account) and data to raceswap.
+fn add_caller(fixture: &mut Fixture) -> Pubkey {
+    let caller = Pubkey::new_unique();
+    fixture.ledger.add_program(
+        caller,
+        Rc::new(|_, accounts, data| {
+            let (_, forwarded) = accounts.split_last().expect("raceswap program");
+            let metas = forwarded
+                .iter()
+                .map(|info| AccountMeta {
+                    pubkey: *info.key,
+                    is_signer: info.is_signer,
+                    is_writable: info.is_writable,
+                })
+                .collect();
+            let ix = Instruction {
+                program_id: ID,
+                accounts: metas,
+                data: data.to_vec(),
+            };
+            invoke(&ix, accounts)
+        }),
+    );
+    caller
+}
+
+fn allow(fixture: &mut Fixture, callers: Vec<Pubkey>) {
+    fixture
+        .update_config(UpdateConfigParams {
+            allowed_callers: Some(callers),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+fn swap_via(fixture: &mut Fixture, caller: Pubkey) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.accounts.instructions_sysvar = Some(sysvar::instructions::ID);
+    let mut ix = fixture.swap_instruction(&swap);
+    ix.program_id = caller;
+    ix.accounts.push(AccountMeta::new_readonly(ID, false));
+    fixture.ledger.process(&ix)
+}
+
+#[test]
+fn allowed_caller_program_can_cpi() {
+    let mut fixture = Fixture::new();
+    let caller = add_caller(&mut fixture);
+    allow(&mut fixture, vec![caller]);
+    swap_via(&mut fixture, caller).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+}
+
+#[test]
+fn other_caller_program_is_rejected() {
+    let mut fixture = Fixture::new();
+    let caller = add_caller(&mut fixture);
+    allow(&mut fixture, vec![Pubkey::new_unique()]);
+    assert_eq!(
+        swap_via(&mut fixture, caller),
+        error(RaceswapError::CallerNotAllowed)
+    );
+}
+
+#[test]
+fn direct_calls_and_an_empty_list_bypass_the_whitelist() {
+    let mut fixture = Fixture::new();
+    let caller = add_caller(&mut fixture);
+    swap_via(&mut fixture, caller).unwrap();
+
+    allow(&mut fixture, vec![Pubkey::new_unique()]);
+    let swap = fixture.swap(1_000_000, 9_900, 50);
+    fixture.execute(&swap).unwrap();
+}
@@ -0,0 +1,48 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+fn swap_from(fixture: &mut Fixture, user_input: Pubkey) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.accounts.user_input = user_input;
+    fixture.execute(&swap)
+}
+
+fn require_canonical(fixture: &mut Fixture) {
+    fixture
+        .update_config(UpdateConfigParams {
+            require_canonical_input_ata: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+#[test]
+fn any_user_account_is_accepted_by_default() {
+    let mut fixture = Fixture::new();
+    let ata = fixture.user_input;
+    swap_from(&mut fixture, ata).unwrap();
+
+    let other = fixture.token_account(fixture.input_mint, fixture.user, USER_INPUT_BALANCE);
+    swap_from(&mut fixture, other).unwrap();
+    assert_eq!(fixture.token_amount(&other), USER_INPUT_BALANCE - 1_000_000);
+}
+
+#[test]
+fn canonical_ata_is_accepted_when_required() {
+    let mut fixture = Fixture::new();
+    require_canonical(&mut fixture);
+    let ata = fixture.user_input;
+    swap_from(&mut fixture, ata).unwrap();
+}
+
+#[test]
+fn non_ata_input_is_rejected_when_required() {
+    let mut fixture = Fixture::new();
+    require_canonical(&mut fixture);
+    let other = fixture.token_account(fixture.input_mint, fixture.user, USER_INPUT_BALANCE);
+    assert_eq!(
+        swap_from(&mut fixture, other),
+        error(RaceswapError::NonCanonicalInputAccount)
+    );
+}
@@ -0,0 +1,88 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+fn set_breaker(fixture: &mut Fixture, authority: Pubkey, active: bool) -> ProgramResult {
+    let accounts = accounts::UpdateConfig {
+        config: fixture.config,
+        authority,
+    };
+    fixture.admin(
+        &accounts,
+        instruction::SetCircuitBreaker { active },
+        &[],
+        &[],
+    )
+}
+
+/// A fixture with a 500 bps breaker ceiling, active or not.
+fn breaker_fixture(active: bool) -> Fixture {
+    let mut fixture = Fixture::new();
+    fixture
+        .update_config(UpdateConfigParams {
+            max_price_impact_bps: Some(500),
+            ..Default::default()
+        })
+        .unwrap();
+    let authority = fixture.authority;
+    set_breaker(&mut fixture, authority, active).unwrap();
+    fixture
+}
+
+/// The default swap's legs with `main_out` against a 10_000 quote and the
+/// client allowing a 1_000 bps impact.
+fn quoted_swap(fixture: &mut Fixture, main_out: u64) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, main_out, 50);
+    swap.params.expected_out = 10_000;
+    swap.params.max_impact_bps = 1_000;
+    fixture.execute(&swap)
+}
+
+#[test]
+fn inactive_breaker_keeps_the_client_limit() {
+    let mut fixture = breaker_fixture(false);
+    quoted_swap(&mut fixture, 9_000).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn active_breaker_tightens_the_limit() {
+    let mut fixture = breaker_fixture(true);
+    assert_eq!(
+        quoted_swap(&mut fixture, 9_000),
+        error(RaceswapError::ExcessivePriceImpact)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+
+    quoted_swap(&mut fixture, 9_500).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_500);
+}
+
+#[test]
+fn active_breaker_requires_a_quote() {
+    let mut fixture = breaker_fixture(true);
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::CircuitBreakerNeedsQuote)
+    );
+}
+
+#[test]
+fn clearing_the_breaker_restores_the_client_limit() {
+    let mut fixture = breaker_fixture(true);
+    let authority = fixture.authority;
+    set_breaker(&mut fixture, authority, false).unwrap();
+    assert!(!fixture.config().circuit_breaker_active);
+    quoted_swap(&mut fixture, 9_000).unwrap();
+}
+
+#[test]
+fn only_the_authority_can_trip_the_breaker() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    assert_eq!(
+        set_breaker(&mut fixture, user, true),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.config().circuit_breaker_active);
+}
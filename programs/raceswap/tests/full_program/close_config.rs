@@ -0,0 +1,57 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+fn close_config(fixture: &mut Fixture, authority: Pubkey) -> ProgramResult {
+    let accounts = accounts::CloseConfig {
+        config: fixture.config,
+        authority,
+    };
+    fixture.admin(&accounts, instruction::CloseConfig {}, &[], &[])
+}
+
+#[test]
+fn rent_returns_to_the_authority_and_the_config_is_gone() {
+    let mut fixture = Fixture::new();
+    let (authority, config) = (fixture.authority, fixture.config);
+    let rent = fixture.ledger.lamports(&config);
+    let authority_before = fixture.ledger.lamports(&authority);
+    assert!(rent > 0);
+
+    close_config(&mut fixture, authority).unwrap();
+    assert_eq!(fixture.ledger.lamports(&authority), authority_before + rent);
+    assert!(!fixture.ledger.exists(&config));
+}
+
+#[test]
+fn only_the_authority_can_close() {
+    let mut fixture = Fixture::new();
+    let (user, config) = (fixture.user, fixture.config);
+    let rent = fixture.ledger.lamports(&config);
+    assert_eq!(
+        close_config(&mut fixture, user),
+        error(RaceswapError::Unauthorized)
+    );
+    assert_eq!(fixture.ledger.lamports(&config), rent);
+}
+
+#[test]
+fn config_mid_swap_cannot_be_closed() {
+    let mut fixture = Fixture::new();
+    let (authority, config) = (fixture.authority, fixture.config);
+    let mut state = fixture.config();
+    state.in_progress = true;
+    let mut data = fixture.ledger.data(&config);
+    let len = data.len();
+    data.clear();
+    state.try_serialize(&mut data).unwrap();
+    data.resize(len, 0);
+    let rent = fixture.ledger.lamports(&config);
+    fixture.ledger.set_account(config, rent, data, ID);
+
+    assert_eq!(
+        close_config(&mut fixture, authority),
+        error(RaceswapError::ConfigInUse)
+    );
+    assert!(fixture.ledger.exists(&config));
+}
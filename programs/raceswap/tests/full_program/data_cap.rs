@@ -0,0 +1,42 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+#[test]
+fn leg_data_at_the_cap_is_accepted() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    // Trailing bytes past the route amounts are ignored by the mock route
+    swap.params
+        .main_leg
+        .as_mut()
+        .unwrap()
+        .data
+        .resize(MAX_INSTRUCTION_DATA_LEN, 0);
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn leg_data_past_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params
+        .main_leg
+        .as_mut()
+        .unwrap()
+        .data
+        .resize(MAX_INSTRUCTION_DATA_LEN + 1, 0);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InstructionDataTooLarge)
+    );
+
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.reflection_legs[0]
+        .instruction
+        .data
+        .resize(MAX_INSTRUCTION_DATA_LEN + 1, 0);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InstructionDataTooLarge)
+    );
+}
@@ -0,0 +1,73 @@
+use crate::fixture::{error, leg, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// The default 1_000_000 swap with its 10_000 reflection split across the
+/// reflection mint and a second mint at `weights`, each leg consuming its
+/// share. Returns the swap and the second mint's destination.
+fn dual_swap(fixture: &mut Fixture, weights: [u16; 2]) -> (Swap, Pubkey) {
+    let second_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(second_mint, 6);
+    let second_destination = fixture.token_account(second_mint, fixture.user, 0);
+
+    // Out of a 10_000 reflection each share equals its weight in bps
+    let first_share = weights[0] as u64;
+    let mut swap = fixture.swap(1_000_000, 9_900, 0);
+    swap.params.reflection_legs = vec![
+        ReflectionLeg {
+            mint: fixture.reflection_mint,
+            min_out: 0,
+            weight_bps: weights[0],
+            instruction: leg(route_data(first_share, 70), &[true, true]),
+        },
+        ReflectionLeg {
+            mint: second_mint,
+            min_out: 0,
+            weight_bps: weights[1],
+            instruction: leg(route_data(10_000 - first_share, 30), &[true, true]),
+        },
+    ];
+    swap.accounts.second_reflection_destination = Some(second_destination);
+    swap.remaining.splice(
+        2..2,
+        [
+            AccountMeta::new(fixture.vault, false),
+            AccountMeta::new(second_destination, false),
+        ],
+    );
+    (swap, second_destination)
+}
+
+#[test]
+fn reflection_is_split_across_both_mints_by_weight() {
+    let mut fixture = Fixture::new();
+    let (swap, second_destination) = dual_swap(&mut fixture, [7_000, 3_000]);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 70);
+    assert_eq!(fixture.token_amount(&second_destination), 30);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn weights_not_summing_to_the_whole_are_rejected() {
+    let mut fixture = Fixture::new();
+    let (swap, _) = dual_swap(&mut fixture, [7_000, 2_000]);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionLegs)
+    );
+}
+
+#[test]
+fn more_than_two_legs_are_rejected() {
+    let mut fixture = Fixture::new();
+    let (mut swap, _) = dual_swap(&mut fixture, [7_000, 3_000]);
+    let third = swap.params.reflection_legs[1].clone();
+    swap.params.reflection_legs.push(third);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionLegs)
+    );
+}
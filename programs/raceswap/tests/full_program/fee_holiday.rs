@@ -0,0 +1,46 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+fn schedule(fixture: &mut Fixture, start: u64, end: u64) -> ProgramResult {
+    fixture.update_config(UpdateConfigParams {
+        fee_holiday_start_slot: Some(start),
+        fee_holiday_end_slot: Some(end),
+        ..Default::default()
+    })
+}
+
+/// Runs the default swap at `slot`, returning the SOL fee paid.
+fn fee_at(fixture: &mut Fixture, slot: u64) -> u64 {
+    fixture.ledger.set_slot(slot);
+    let before = fixture.ledger.lamports(&fixture.fee_destination);
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    fixture.ledger.lamports(&fixture.fee_destination) - before
+}
+
+#[test]
+fn swaps_inside_the_holiday_pay_no_fee() {
+    let mut fixture = Fixture::new();
+    schedule(&mut fixture, 1_000, 2_000).unwrap();
+
+    assert_eq!(fee_at(&mut fixture, 1_000), 0);
+    assert_eq!(fee_at(&mut fixture, 1_999), 0);
+}
+
+#[test]
+fn swaps_outside_the_holiday_pay_the_normal_fee() {
+    let mut fixture = Fixture::new();
+    schedule(&mut fixture, 1_000, 2_000).unwrap();
+
+    assert_eq!(fee_at(&mut fixture, 999), 2_000);
+    assert_eq!(fee_at(&mut fixture, 2_000), 2_000);
+}
+
+#[test]
+fn start_not_before_end_is_rejected() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        schedule(&mut fixture, 2_000, 2_000),
+        error(RaceswapError::InvalidFeeHoliday)
+    );
+}
@@ -0,0 +1,64 @@
+use crate::fixture::{error, leg, pda, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// Caps the fee at half the output and returns an in-token fee swap of
+/// 1_000_000 delivering `main_out`, priced at 100 input units per output unit.
+/// Its 2_000 token fee is then worth 20 output units.
+fn capped_swap(fixture: &mut Fixture, main_out: u64) -> Swap {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_fee_output_bps: Some(5_000),
+            ..Default::default()
+        })
+        .unwrap();
+    let treasury_authority = pda(&[TREASURY_AUTHORITY_SEED, fixture.config.as_ref()]);
+    let treasury_ata = get_associated_token_address_with_program_id(
+        &treasury_authority,
+        &fixture.input_mint,
+        &TOKEN_PROGRAM_ID,
+    );
+    let input_mint = fixture.input_mint;
+    fixture
+        .ledger
+        .add_token_account(treasury_ata, input_mint, treasury_authority, 0);
+
+    let mut swap = fixture.swap(1_000_000, main_out, 50);
+    swap.params.fee_in_input_token = true;
+    swap.params.main_leg = Some(leg(route_data(988_000, main_out), &[true, true, false]));
+    swap.params.reference_price_numerator = 100;
+    swap.params.reference_price_denominator = 1;
+    swap.accounts.treasury_token_account = Some(treasury_ata);
+    swap
+}
+
+#[test]
+fn disproportionate_fee_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = capped_swap(&mut fixture, 39);
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::FeeDisproportionate)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn fee_at_the_cap_passes() {
+    let mut fixture = Fixture::new();
+    let swap = capped_swap(&mut fixture, 40);
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 40);
+}
+
+#[test]
+fn check_is_skipped_without_reference_price() {
+    let mut fixture = Fixture::new();
+    let mut swap = capped_swap(&mut fixture, 1);
+    swap.params.reference_price_numerator = 0;
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 1);
+}
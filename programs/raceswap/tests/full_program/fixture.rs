@@ -0,0 +1,446 @@
+#![allow(dead_code)]
+
+//! A deployed program with an initialized config, a vault for the input mint
+//! and a funded user, plus builders for the instructions the tests send.
+
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+use raceswap_harness::{mock_route, route_data, Ledger};
+use std::rc::Rc;
+
+pub const TREASURY: Pubkey = pubkey!("Exh4ZxgzA32hnLrQq3UnqxEXMRd4vifogMc6oXn7bP4L");
+pub const REFLECTION_FEE_BPS: u16 = 100;
+pub const TREASURY_FEE_BPS: u16 = 20;
+pub const USER_INPUT_BALANCE: u64 = 10_000_000;
+pub const USER_LAMPORTS: u64 = 10_000_000_000;
+
+pub struct Fixture {
+    pub ledger: Ledger,
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub config: Pubkey,
+    pub swap_authority: Pubkey,
+    pub protocol_stats: Pubkey,
+    pub input_mint: Pubkey,
+    pub main_mint: Pubkey,
+    pub reflection_mint: Pubkey,
+    pub user_input: Pubkey,
+    pub user_main: Pubkey,
+    pub user_reflection: Pubkey,
+    pub vault: Pubkey,
+    pub fee_destination: Pubkey,
+}
+
+/// One `execute_raceswap` call: params, named accounts and the legs' accounts.
+pub struct Swap {
+    pub params: ExecuteRaceswapParams,
+    pub accounts: accounts::ExecuteRaceswap,
+    pub remaining: Vec<AccountMeta>,
+    /// Whether `user` signs (it is an unchecked account in the struct)
+    pub user_signs: bool,
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &ID).0
+}
+
+/// A leg of `accounts_len` own accounts, writable as given, none signing.
+pub fn leg(data: Vec<u8>, writable: &[bool]) -> SerializedInstruction {
+    SerializedInstruction {
+        accounts_len: writable.len() as u16,
+        data,
+        is_writable: writable.to_vec(),
+        is_signer: vec![false; writable.len()],
+        shared_refs: Vec::new(),
+    }
+}
+
+pub fn instruction(
+    accounts: &impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: &[AccountMeta],
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+/// Marks every meta for `key` as signing.
+pub fn signed(mut instruction: Instruction, key: &Pubkey) -> Instruction {
+    for meta in instruction
+        .accounts
+        .iter_mut()
+        .filter(|meta| meta.pubkey == *key)
+    {
+        meta.is_signer = true;
+    }
+    instruction
+}
+
+pub fn error(code: RaceswapError) -> ProgramResult {
+    Err(raceswap_harness::anchor_error(code))
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let mut ledger = Ledger::new(ID, entry);
+        ledger.add_program(JUPITER_PROGRAM_ID, Rc::new(mock_route));
+
+        let authority = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        ledger.fund(authority, USER_LAMPORTS);
+        ledger.fund(user, USER_LAMPORTS);
+        let rent_exempt = Rent::default().minimum_balance(0);
+        ledger.fund(TREASURY, rent_exempt);
+        let fee_destination = Pubkey::new_unique();
+        ledger.fund(fee_destination, rent_exempt);
+
+        let config = pda(&[CONFIG_SEED]);
+        let swap_authority = pda(&[AUTHORITY_SEED, config.as_ref()]);
+        let protocol_stats = pda(&[PROTOCOL_STATS_SEED]);
+        let input_mint = Pubkey::new_unique();
+        let main_mint = Pubkey::new_unique();
+        let reflection_mint = Pubkey::new_unique();
+        for mint in [input_mint, main_mint, reflection_mint] {
+            ledger.add_mint(mint, 6);
+        }
+        let user_input =
+            get_associated_token_address_with_program_id(&user, &input_mint, &TOKEN_PROGRAM_ID);
+        let user_main = Pubkey::new_unique();
+        let user_reflection = Pubkey::new_unique();
+        ledger.add_token_account(user_input, input_mint, user, USER_INPUT_BALANCE);
+        ledger.add_token_account(user_main, main_mint, user, 0);
+        ledger.add_token_account(user_reflection, reflection_mint, user, 0);
+        let vault = get_associated_token_address_with_program_id(
+            &swap_authority,
+            &input_mint,
+            &TOKEN_PROGRAM_ID,
+        );
+
+        let mut fixture = Self {
+            ledger,
+            authority,
+            user,
+            config,
+            swap_authority,
+            protocol_stats,
+            input_mint,
+            main_mint,
+            reflection_mint,
+            user_input,
+            user_main,
+            user_reflection,
+            vault,
+            fee_destination,
+        };
+
+        let initialize = instruction(
+            &accounts::InitializeConfig {
+                config,
+                payer: authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeConfig {
+                params: InitializeConfigParams {
+                    authority,
+                    treasury_wallet: TREASURY,
+                    reflection_fee_bps: REFLECTION_FEE_BPS,
+                    treasury_fee_bps: TREASURY_FEE_BPS,
+                },
+            },
+            &[],
+        );
+        fixture
+            .ledger
+            .process(&initialize)
+            .expect("initialize_config");
+        let init_stats = instruction(
+            &accounts::InitProtocolStats {
+                protocol_stats,
+                payer: authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitProtocolStats {},
+            &[],
+        );
+        fixture
+            .ledger
+            .process(&init_stats)
+            .expect("init_protocol_stats");
+        fixture
+            .create_vaults(&[input_mint], &[])
+            .expect("create_input_vaults_batch");
+        fixture
+    }
+
+    pub fn config(&self) -> RaceswapConfig {
+        self.ledger.anchor_account(&self.config)
+    }
+
+    pub fn protocol_stats(&self) -> ProtocolStats {
+        self.ledger.anchor_account(&self.protocol_stats)
+    }
+
+    pub fn vault_for(&self, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.swap_authority, mint, &TOKEN_PROGRAM_ID)
+    }
+
+    /// Sends an admin instruction from `authority` with `co_signers` as the
+    /// leading signer remaining_accounts.
+    pub fn admin(
+        &mut self,
+        accounts: &impl ToAccountMetas,
+        data: impl InstructionData,
+        co_signers: &[Pubkey],
+        remaining: &[AccountMeta],
+    ) -> ProgramResult {
+        let mut extra: Vec<AccountMeta> = co_signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true))
+            .collect();
+        extra.extend_from_slice(remaining);
+        self.ledger.process(&instruction(accounts, data, &extra))
+    }
+
+    pub fn update_config(&mut self, params: UpdateConfigParams) -> ProgramResult {
+        self.update_config_signed(self.authority, params, &[])
+    }
+
+    pub fn update_config_signed(
+        &mut self,
+        authority: Pubkey,
+        params: UpdateConfigParams,
+        co_signers: &[Pubkey],
+    ) -> ProgramResult {
+        let accounts = accounts::UpdateConfig {
+            config: self.config,
+            authority,
+        };
+        self.admin(
+            &accounts,
+            instruction::UpdateConfig { params },
+            co_signers,
+            &[],
+        )
+    }
+
+    pub fn set_pause(&mut self, paused: bool) -> ProgramResult {
+        let accounts = accounts::UpdateConfig {
+            config: self.config,
+            authority: self.authority,
+        };
+        self.admin(&accounts, instruction::SetPause { paused }, &[], &[])
+    }
+
+    /// Creates the swap authority's vaults for `mints` in one batch.
+    pub fn create_vaults(&mut self, mints: &[Pubkey], co_signers: &[Pubkey]) -> ProgramResult {
+        let pairs: Vec<AccountMeta> = mints
+            .iter()
+            .flat_map(|mint| {
+                [
+                    AccountMeta::new_readonly(*mint, false),
+                    AccountMeta::new(self.vault_for(mint), false),
+                ]
+            })
+            .collect();
+        let accounts = accounts::CreateInputVaultsBatch {
+            config: self.config,
+            authority: self.authority,
+            swap_authority: self.swap_authority,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        self.admin(
+            &accounts,
+            instruction::CreateInputVaultsBatch {},
+            co_signers,
+            &pairs,
+        )
+    }
+
+    /// Adds a token account for `mint` owned by `owner`.
+    pub fn token_account(&mut self, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.ledger.add_token_account(key, mint, owner, amount);
+        key
+    }
+
+    pub fn init_user_stats(&mut self) -> Pubkey {
+        let user_stats = pda(&[USER_STATS_SEED, self.user.as_ref()]);
+        let init = instruction(
+            &accounts::InitUserStats {
+                user_stats,
+                user: self.user,
+                system_program: system_program::ID,
+            },
+            instruction::InitUserStats {},
+            &[],
+        );
+        self.ledger.process(&init).expect("init_user_stats");
+        user_stats
+    }
+
+    /// A swap of `total_in` whose reflection leg delivers `reflection_out` and
+    /// whose main leg turns the rest of the input into `main_out`, with the
+    /// default reflection rate and `min_main_out == main_out`.
+    pub fn swap(&self, total_in: u64, main_out: u64, reflection_out: u64) -> Swap {
+        self.swap_at(total_in, main_out, reflection_out, REFLECTION_FEE_BPS)
+    }
+
+    /// [`Fixture::swap`] with legs sized for a `reflection_bps` reflection.
+    pub fn swap_at(
+        &self,
+        total_in: u64,
+        main_out: u64,
+        reflection_out: u64,
+        reflection_bps: u16,
+    ) -> Swap {
+        let reflection_in = total_in * reflection_bps as u64 / 10_000;
+        let main_in = total_in - reflection_in;
+        let params = ExecuteRaceswapParams {
+            input_mint: self.input_mint,
+            main_output_mint: self.main_mint,
+            reflection_mint: self.reflection_mint,
+            total_input_amount: total_in,
+            amount_is_max: false,
+            min_main_out: main_out,
+            quoted_out: 0,
+            main_slippage_bps: None,
+            reflection_quoted_out: 0,
+            reflection_slippage_bps: None,
+            quote_slot: 0,
+            disable_reflection: false,
+            reflection_mode: ReflectionMode::Input,
+            reflection_amount_override: None,
+            platform_fee_account_index: None,
+            shared_accounts_len: 0,
+            debug: false,
+            reconcile_input: false,
+            fee_in_input_token: false,
+            referral_code: None,
+            check_jupiter_out: false,
+            expected_out: 0,
+            max_impact_bps: 0,
+            reference_price_numerator: 0,
+            reference_price_denominator: 0,
+            main_leg: Some(leg(route_data(main_in, main_out), &[true, true, false])),
+            reflection_legs: vec![ReflectionLeg {
+                mint: self.reflection_mint,
+                min_out: 0,
+                weight_bps: 10_000,
+                instruction: leg(route_data(reflection_in, reflection_out), &[true, true]),
+            }],
+            unwrap_output: false,
+            vault_authority_signs: false,
+            topup_from_reflection: false,
+            topup_leg: None,
+            reflection_action: ReflectionAction::Swap,
+            max_accounts: 0,
+        };
+        let remaining = vec![
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new(self.user_reflection, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new(self.user_main, false),
+            AccountMeta::new_readonly(self.swap_authority, false),
+        ];
+        Swap {
+            params,
+            accounts: self.swap_accounts(),
+            remaining,
+            user_signs: true,
+        }
+    }
+
+    /// The same swap without a reflection leg.
+    pub fn swap_without_reflection(&self, total_in: u64, main_out: u64) -> Swap {
+        let mut swap = self.swap(total_in, main_out, 0);
+        swap.params.disable_reflection = true;
+        swap.params.reflection_legs.clear();
+        swap.params.main_leg = Some(leg(route_data(total_in, main_out), &[true, true, false]));
+        swap.remaining.drain(..2);
+        swap
+    }
+
+    pub fn swap_accounts(&self) -> accounts::ExecuteRaceswap {
+        accounts::ExecuteRaceswap {
+            config: self.config,
+            user: self.user,
+            input_mint: self.input_mint,
+            user_input: self.user_input,
+            user_main_destination: self.user_main,
+            user_reflection_destination: self.user_reflection,
+            treasury_wallet: TREASURY,
+            treasury_fee_destination: self.fee_destination,
+            input_vault: self.vault,
+            input_token_program: TOKEN_PROGRAM_ID,
+            jupiter_program: JUPITER_PROGRAM_ID,
+            system_program: system_program::ID,
+            session_key: None,
+            session: None,
+            main_output_mint: None,
+            reflection_mint: None,
+            treasury_token_account: None,
+            user_stats: None,
+            mint_fee: None,
+            refund_destination: None,
+            referral: None,
+            referrer: None,
+            instructions_sysvar: None,
+            output_token_program: None,
+            protocol_stats: self.protocol_stats,
+            second_reflection_destination: None,
+            deny_list: None,
+            community_destination: None,
+            sol_usd_price: None,
+            rebate_nft: None,
+            associated_token_program: None,
+        }
+    }
+
+    pub fn swap_instruction(&self, swap: &Swap) -> Instruction {
+        let ix = instruction(
+            &swap.accounts,
+            instruction::ExecuteRaceswap {
+                params: swap.params.clone(),
+            },
+            &swap.remaining,
+        );
+        if swap.user_signs {
+            signed(ix, &self.user)
+        } else {
+            ix
+        }
+    }
+
+    pub fn execute(&mut self, swap: &Swap) -> ProgramResult {
+        let ix = self.swap_instruction(swap);
+        self.ledger.process(&ix)
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        self.ledger.token_amount(key)
+    }
+
+    /// Raw SPL token account data, for accounts built outside the ledger.
+    pub fn token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; SplTokenAccount::LEN];
+        SplTokenAccount {
+            mint,
+            owner,
+            amount,
+            state: anchor_spl::token::spl_token::state::AccountState::Initialized,
+            ..SplTokenAccount::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+}
@@ -0,0 +1,20 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+#[test]
+fn input_above_the_user_balance_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(USER_INPUT_BALANCE + 1, 9_900, 50);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InsufficientInputBalance)
+    );
+}
+
+#[test]
+fn input_equal_to_the_user_balance_is_accepted() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(USER_INPUT_BALANCE, 9_900, 50);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_input), 0);
+}
@@ -0,0 +1,48 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+#[test]
+fn clean_swap_reconciles() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reconcile_input = true;
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE - 1_000_000
+    );
+}
+
+#[test]
+fn route_drawing_from_user_input_fails_reconciliation() {
+    let mut fixture = Fixture::new();
+    let refund = fixture.token_account(fixture.input_mint, fixture.user, 0);
+    // The main leg pulls its input from the user's account instead of the
+    // vault, and the untouched vault balance is refunded elsewhere
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reconcile_input = true;
+    swap.accounts.refund_destination = Some(refund);
+    swap.remaining[2].pubkey = fixture.user_input;
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InputReconciliationFailed)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_input), USER_INPUT_BALANCE);
+}
+
+#[test]
+fn leakage_goes_unchecked_without_reconciliation() {
+    let mut fixture = Fixture::new();
+    let refund = fixture.token_account(fixture.input_mint, fixture.user, 0);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.refund_destination = Some(refund);
+    swap.remaining[2].pubkey = fixture.user_input;
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE - 1_000_000 - 990_000
+    );
+}
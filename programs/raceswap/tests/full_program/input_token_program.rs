@@ -0,0 +1,39 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+/// Reassigns `key` to `token_program`, keeping its account layout.
+fn move_to_program(fixture: &mut Fixture, key: Pubkey, token_program: Pubkey) {
+    let lamports = fixture.ledger.lamports(&key);
+    let data = fixture.ledger.data(&key);
+    fixture.ledger.set_account(key, lamports, data, token_program);
+}
+
+#[test]
+fn token_2022_input_with_legacy_program_is_rejected() {
+    let mut fixture = Fixture::new();
+    let user_input = fixture.user_input;
+    move_to_program(&mut fixture, user_input, TOKEN_2022_PROGRAM_ID);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::InputTokenProgramMismatch)
+    );
+    assert_eq!(fixture.token_amount(&user_input), USER_INPUT_BALANCE);
+}
+
+#[test]
+fn legacy_input_with_token_2022_program_is_rejected() {
+    let mut fixture = Fixture::new();
+    // A Token-2022 mint and vault, but the user passes a legacy account
+    let (input_mint, vault) = (fixture.input_mint, fixture.vault);
+    move_to_program(&mut fixture, input_mint, TOKEN_2022_PROGRAM_ID);
+    move_to_program(&mut fixture, vault, TOKEN_2022_PROGRAM_ID);
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.input_token_program = TOKEN_2022_PROGRAM_ID;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InputTokenProgramMismatch)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_input), USER_INPUT_BALANCE);
+}
@@ -0,0 +1,17 @@
+use crate::fixture::{leg, Fixture};
+use crate::*;
+use raceswap_harness::route_data;
+
+#[test]
+fn route_listing_the_jupiter_program_is_accepted() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    // Real routes pass the program again, e.g. as Jupiter's own event authority
+    swap.params.main_leg = Some(leg(route_data(990_000, 9_000), &[true, true, false, false]));
+    swap.remaining
+        .push(AccountMeta::new_readonly(JUPITER_PROGRAM_ID, false));
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+}
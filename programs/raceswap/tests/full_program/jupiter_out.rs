@@ -0,0 +1,97 @@
+use crate::fixture::{error, leg, Fixture, Swap};
+use crate::*;
+use anchor_lang::solana_program::program::set_return_data;
+use raceswap_harness::{mock_route, route_data};
+use std::rc::Rc;
+
+/// Replaces the Jupiter mock with a route that fills like the mock but
+/// reports the u64 after the route data as its out amount, or nothing when
+/// that suffix is empty.
+fn add_reporting_route(fixture: &mut Fixture) {
+    fixture.ledger.add_program(
+        JUPITER_PROGRAM_ID,
+        Rc::new(|program_id, accounts, data| {
+            mock_route(program_id, accounts, &data[..16])?;
+            if data.len() > 16 {
+                set_return_data(&data[16..]);
+            } else {
+                set_return_data(&[]);
+            }
+            Ok(())
+        }),
+    );
+}
+
+/// The default swap with the report check on, whose main leg delivers 9_000
+/// and reports `reported`.
+fn reported_swap(fixture: &mut Fixture, reported: Option<u64>) -> Swap {
+    add_reporting_route(fixture);
+    let mut data = route_data(990_000, 9_000);
+    if let Some(reported) = reported {
+        data.extend_from_slice(&reported.to_le_bytes());
+    }
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.check_jupiter_out = true;
+    swap.params.main_leg = Some(leg(data, &[true, true, false]));
+    swap
+}
+
+#[test]
+fn matching_report_passes() {
+    let mut fixture = Fixture::new();
+    let swap = reported_swap(&mut fixture, Some(9_000));
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn report_disagreeing_with_the_delta_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = reported_swap(&mut fixture, Some(9_500));
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::OutputAccountingMismatch)
+    );
+}
+
+#[test]
+fn report_below_min_out_is_rejected() {
+    let mut fixture = Fixture::new();
+    fixture
+        .update_config(UpdateConfigParams {
+            min_out_tolerance: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+    let swap = reported_swap(&mut fixture, Some(8_995));
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::OutputAccountingMismatch)
+    );
+}
+
+#[test]
+fn report_within_the_tolerance_passes() {
+    let mut fixture = Fixture::new();
+    fixture
+        .update_config(UpdateConfigParams {
+            min_out_tolerance: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+    let swap = reported_swap(&mut fixture, Some(9_010));
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn missing_report_only_fails_with_the_check_on() {
+    let mut fixture = Fixture::new();
+    let mut swap = reported_swap(&mut fixture, None);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::OutputAccountingMismatch)
+    );
+
+    swap.params.check_jupiter_out = false;
+    fixture.execute(&swap).unwrap();
+}
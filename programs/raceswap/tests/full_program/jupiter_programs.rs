@@ -0,0 +1,57 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use raceswap_harness::mock_route;
+use std::rc::Rc;
+
+fn set_programs(fixture: &mut Fixture, jupiter_programs: Vec<Pubkey>) -> ProgramResult {
+    fixture.update_config(UpdateConfigParams {
+        jupiter_programs: Some(jupiter_programs),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn both_allowlisted_programs_route_swaps() {
+    let mut fixture = Fixture::new();
+    let v7 = Pubkey::new_unique();
+    fixture.ledger.add_program(v7, Rc::new(mock_route));
+    set_programs(&mut fixture, vec![JUPITER_PROGRAM_ID, v7]).unwrap();
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.jupiter_program = v7;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 18_000);
+}
+
+#[test]
+fn dropped_program_is_rejected() {
+    let mut fixture = Fixture::new();
+    let v7 = Pubkey::new_unique();
+    fixture.ledger.add_program(v7, Rc::new(mock_route));
+    set_programs(&mut fixture, vec![v7]).unwrap();
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::InvalidJupiterProgram)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn allowlist_must_hold_one_or_two_programs() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        set_programs(&mut fixture, Vec::new()),
+        error(RaceswapError::InvalidJupiterProgram)
+    );
+    assert_eq!(
+        set_programs(
+            &mut fixture,
+            vec![JUPITER_PROGRAM_ID, Pubkey::new_unique(), Pubkey::new_unique()]
+        ),
+        error(RaceswapError::InvalidJupiterProgram)
+    );
+    assert_eq!(fixture.config().jupiter_programs, vec![JUPITER_PROGRAM_ID]);
+}
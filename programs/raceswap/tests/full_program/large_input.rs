@@ -0,0 +1,58 @@
+use crate::fixture::{error, leg, Fixture, Swap, REFLECTION_FEE_BPS, TREASURY_FEE_BPS};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// A default swap of `total_in`, with legs sized in u128 since the fixture's
+/// u64 leg math cannot hold inputs this large.
+fn large_swap(fixture: &mut Fixture, total_in: u64) -> (Swap, u64) {
+    let reflection_in = (total_in as u128 * REFLECTION_FEE_BPS as u128 / 10_000) as u64;
+    let treasury_fee = (total_in as u128 * TREASURY_FEE_BPS as u128 / 10_000) as u64;
+    let (user, input, mint) = (fixture.user, fixture.user_input, fixture.input_mint);
+    fixture
+        .ledger
+        .add_token_account(input, mint, user, total_in);
+    fixture.ledger.fund(user, treasury_fee);
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.total_input_amount = total_in;
+    swap.params.main_leg = Some(leg(
+        route_data(total_in - reflection_in, 9_000),
+        &[true, true, false],
+    ));
+    swap.params.reflection_legs[0].instruction = leg(route_data(reflection_in, 50), &[true, true]);
+    (swap, treasury_fee)
+}
+
+#[test]
+fn reflection_past_the_u64_intermediate_is_exact() {
+    let mut fixture = Fixture::new();
+    // total_in * 100 bps no longer fits in a u64; the quotient does
+    let total_in = 500_000_000_000_000_000;
+    let (swap, treasury_fee) = large_swap(&mut fixture, total_in);
+    let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+    fixture.execute(&swap).unwrap();
+
+    // Both legs consumed exactly their share, so nothing was left behind
+    assert_eq!(fixture.token_amount(&fixture.user_input), 0);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_destination_before + treasury_fee
+    );
+}
+
+#[test]
+fn overflowing_input_fails_cleanly() {
+    let mut fixture = Fixture::new();
+    let (swap, _) = large_swap(&mut fixture, u64::MAX);
+    let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MathOverflow));
+    assert_eq!(fixture.token_amount(&fixture.user_input), u64::MAX);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 0);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_destination_before
+    );
+}
@@ -0,0 +1,93 @@
+use crate::fixture::leg;
+use crate::*;
+
+struct Account {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+}
+
+impl Account {
+    fn new() -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            lamports: 0,
+            data: Vec::new(),
+            owner: system_program::ID,
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            false,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+#[test]
+fn each_leg_cpi_carries_only_its_own_accounts() {
+    let mut program = Account::new();
+    program.key = JUPITER_PROGRAM_ID;
+    let mut accounts: Vec<Account> = (0..6).map(|_| Account::new()).collect();
+    let keys: Vec<Pubkey> = accounts.iter().map(|account| account.key).collect();
+    let swap_authority = keys[1];
+    let program_info = program.info();
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(Account::info).collect();
+
+    let mut legs = LegAccounts {
+        jupiter_program: program_info,
+        shared: &[],
+        unique: infos.iter(),
+        swap_authority_key: swap_authority,
+        consumed: 0,
+        signing_vault: None,
+    };
+
+    let mut first = leg(vec![0; 8], &[true, false]);
+    first.is_signer = vec![false, true];
+    let (ix, cpi_infos) = build_leg_cpi(&first, &mut legs).unwrap();
+    assert_eq!(cpi_infos.len(), first.accounts_len as usize + 1);
+    assert_eq!(*cpi_infos[0].key, JUPITER_PROGRAM_ID);
+    let cpi_keys: Vec<Pubkey> = cpi_infos[1..].iter().map(|info| *info.key).collect();
+    assert_eq!(cpi_keys, keys[..2]);
+    // The swap authority never signs into Jupiter, whatever the route asks
+    assert!(!ix.accounts[1].is_signer);
+
+    let second = leg(vec![0; 8], &[true, true, false]);
+    let (ix, cpi_infos) = build_leg_cpi(&second, &mut legs).unwrap();
+    assert_eq!(cpi_infos.len(), second.accounts_len as usize + 1);
+    assert_eq!(ix.accounts.len(), second.accounts_len as usize);
+    let cpi_keys: Vec<Pubkey> = cpi_infos[1..].iter().map(|info| *info.key).collect();
+    assert_eq!(cpi_keys, keys[2..5]);
+    assert_eq!(legs.consumed, 5);
+}
+
+#[test]
+fn leg_longer_than_remaining_accounts_is_rejected() {
+    let mut program = Account::new();
+    let mut accounts: Vec<Account> = (0..2).map(|_| Account::new()).collect();
+    let program_info = program.info();
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(Account::info).collect();
+    let mut legs = LegAccounts {
+        jupiter_program: program_info,
+        shared: &[],
+        unique: infos.iter(),
+        swap_authority_key: Pubkey::new_unique(),
+        consumed: 0,
+        signing_vault: None,
+    };
+
+    let payload = leg(vec![0; 8], &[true, true, true]);
+    assert_eq!(
+        build_leg_cpi(&payload, &mut legs).unwrap_err(),
+        RaceswapError::AccountMismatch.into()
+    );
+}
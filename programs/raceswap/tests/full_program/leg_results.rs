@@ -0,0 +1,56 @@
+use crate::fixture::{leg, Fixture};
+use crate::*;
+use raceswap_harness::route_data;
+
+#[test]
+fn return_data_carries_each_legs_result() {
+    let mut fixture = Fixture::new();
+    let second_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(second_mint, 6);
+    let second_destination = fixture.token_account(second_mint, fixture.user, 0);
+
+    // The 10_000 reflection is split 60/40 across two legs
+    let mut swap = fixture.swap(1_000_000, 9_900, 0);
+    swap.params.reflection_legs = vec![
+        ReflectionLeg {
+            mint: fixture.reflection_mint,
+            min_out: 0,
+            weight_bps: 6_000,
+            instruction: leg(route_data(6_000, 60), &[true, true]),
+        },
+        ReflectionLeg {
+            mint: second_mint,
+            min_out: 0,
+            weight_bps: 4_000,
+            instruction: leg(route_data(4_000, 40), &[true, true]),
+        },
+    ];
+    swap.accounts.second_reflection_destination = Some(second_destination);
+    swap.remaining.splice(
+        2..2,
+        [
+            AccountMeta::new(fixture.vault, false),
+            AccountMeta::new(second_destination, false),
+        ],
+    );
+    fixture.execute(&swap).unwrap();
+
+    let (program_id, data) = fixture.ledger.return_data().unwrap();
+    assert_eq!(program_id, ID);
+    let (status, legs) = <(SwapStatus, Vec<LegResult>)>::try_from_slice(&data).unwrap();
+    assert!(status.success);
+    assert_eq!(status.main_out, 9_900);
+
+    let expected = [
+        (LegKind::Reflection, 6_000, 60, fixture.reflection_mint),
+        (LegKind::Reflection, 4_000, 40, second_mint),
+        (LegKind::Main, 990_000, 9_900, fixture.main_mint),
+    ];
+    assert_eq!(legs.len(), expected.len());
+    for (result, (kind, in_amount, out_amount, out_mint)) in legs.iter().zip(expected) {
+        assert!(result.leg_kind == kind);
+        assert_eq!(result.in_amount, in_amount);
+        assert_eq!(result.out_amount, out_amount);
+        assert_eq!(result.out_mint, out_mint);
+    }
+}
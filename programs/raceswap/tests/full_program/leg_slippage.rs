@@ -0,0 +1,70 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+/// The default swap with both legs' floors given as bps below their quotes:
+/// 10% under a 10_000 main quote and 50% under a 100 reflection quote, so
+/// floors of 9_000 and 50.
+fn bps_swap(fixture: &Fixture, main_out: u64, reflection_out: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, main_out, reflection_out);
+    swap.params.min_main_out = 0;
+    swap.params.quoted_out = 10_000;
+    swap.params.main_slippage_bps = Some(1_000);
+    swap.params.reflection_quoted_out = 100;
+    swap.params.reflection_slippage_bps = Some(5_000);
+    swap
+}
+
+#[test]
+fn both_legs_at_their_bps_floors_pass() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&bps_swap(&fixture, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+}
+
+#[test]
+fn main_leg_below_its_bps_floor_is_rejected() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        fixture.execute(&bps_swap(&fixture, 8_999, 50)),
+        error(RaceswapError::MainBelowMinOut)
+    );
+}
+
+#[test]
+fn reflection_leg_below_its_bps_floor_is_rejected() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        fixture.execute(&bps_swap(&fixture, 9_000, 49)),
+        error(RaceswapError::ReflectionBelowMinOut)
+    );
+}
+
+#[test]
+fn absolute_and_bps_floors_on_one_leg_are_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = bps_swap(&fixture, 9_000, 50);
+    swap.params.min_main_out = 9_000;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ConflictingLegFloor)
+    );
+
+    let mut swap = bps_swap(&fixture, 9_000, 50);
+    swap.params.reflection_legs[0].min_out = 50;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ConflictingLegFloor)
+    );
+}
+
+#[test]
+fn bps_floor_without_a_quote_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = bps_swap(&fixture, 9_000, 50);
+    swap.params.reflection_quoted_out = 0;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidLegSlippage)
+    );
+}
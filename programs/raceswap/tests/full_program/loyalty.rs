@@ -0,0 +1,71 @@
+use crate::fixture::Fixture;
+use crate::*;
+
+/// Enables the loyalty discount: after 2_000 lamports of fees (one default
+/// swap's 0.2%) within 100 slots the user pays 0.1%.
+fn enable_loyalty(fixture: &mut Fixture) -> Pubkey {
+    fixture
+        .update_config(UpdateConfigParams {
+            loyalty_fee_threshold: Some(2_000),
+            loyalty_period_slots: Some(100),
+            loyalty_fee_bps: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+    fixture.init_user_stats()
+}
+
+/// Runs the default swap with the user's stats, returning the SOL fee paid.
+fn swap_fee(fixture: &mut Fixture, user_stats: Pubkey) -> u64 {
+    let before = fixture.ledger.lamports(&fixture.fee_destination);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.user_stats = Some(user_stats);
+    fixture.execute(&swap).unwrap();
+    fixture.ledger.lamports(&fixture.fee_destination) - before
+}
+
+#[test]
+fn user_crossing_the_threshold_gets_reduced_fees() {
+    let mut fixture = Fixture::new();
+    let user_stats = enable_loyalty(&mut fixture);
+
+    assert_eq!(swap_fee(&mut fixture, user_stats), 2_000);
+    assert_eq!(swap_fee(&mut fixture, user_stats), 1_000);
+    assert_eq!(swap_fee(&mut fixture, user_stats), 1_000);
+
+    let stats = fixture.ledger.anchor_account::<UserStats>(&user_stats);
+    assert_eq!(stats.period_fees, 4_000);
+}
+
+#[test]
+fn discount_ends_when_the_period_rolls_over() {
+    let mut fixture = Fixture::new();
+    let user_stats = enable_loyalty(&mut fixture);
+    assert_eq!(swap_fee(&mut fixture, user_stats), 2_000);
+    assert_eq!(swap_fee(&mut fixture, user_stats), 1_000);
+
+    let slot = fixture.ledger.slot();
+    fixture.ledger.set_slot(slot + 100);
+    assert_eq!(swap_fee(&mut fixture, user_stats), 2_000);
+
+    let stats = fixture.ledger.anchor_account::<UserStats>(&user_stats);
+    assert_eq!(stats.period_start_slot, slot + 100);
+    assert_eq!(stats.period_fees, 2_000);
+}
+
+#[test]
+fn swaps_without_stats_pay_the_full_fee() {
+    let mut fixture = Fixture::new();
+    enable_loyalty(&mut fixture);
+
+    for _ in 0..2 {
+        let before = fixture.ledger.lamports(&fixture.fee_destination);
+        fixture
+            .execute(&fixture.swap(1_000_000, 9_000, 50))
+            .unwrap();
+        assert_eq!(
+            fixture.ledger.lamports(&fixture.fee_destination) - before,
+            2_000
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Behavior tests for the full-featured program in `src/lib_v1_broken.rs`.
+//!
+//! That source is not part of the crate's build, so it is compiled here at the
+//! test crate root (anchor resolves `crate::ID`) and driven through the
+//! in-process ledger from `raceswap-harness`.
+
+include!("../../src/lib_v1_broken.rs");
+
+mod fixture;
+
+mod account_layout;
+mod aliased_destinations;
+mod allowed_callers;
+mod canonical_input;
+mod circuit_breaker;
+mod close_config;
+mod data_cap;
+mod dual_reflection;
+mod fee_holiday;
+mod fee_proportion;
+mod input_balance;
+mod input_reconciliation;
+mod input_token_program;
+mod jupiter_in_route;
+mod jupiter_out;
+mod jupiter_programs;
+mod large_input;
+mod leg_cpi;
+mod leg_results;
+mod leg_slippage;
+mod loyalty;
+mod malformed;
+mod max_amount;
+mod max_input;
+mod max_mints;
+mod migrate_authority;
+mod min_out_tolerance;
+mod mint_fee;
+mod mint_reflection;
+mod misrouted_output;
+mod multisig;
+mod native_input;
+mod net_input;
+mod output_reflection;
+mod pause;
+mod platform_fee;
+mod preview_split;
+mod price_impact;
+mod protocol_stats;
+mod quote_age;
+mod rebate_nft;
+mod reentrancy;
+mod reference_floor;
+mod referral_code;
+mod reflection_accounts;
+mod reflection_leg_mint;
+mod reflection_notional;
+mod reflection_override;
+mod reflection_transfer;
+mod reflection_transfer_fee;
+mod refund;
+mod session;
+mod shared_accounts;
+mod slippage;
+mod sol_balance;
+mod streak;
+mod swap_debug;
+mod swap_event;
+mod swap_rate;
+mod swap_status;
+mod topup;
+mod treasury_fee_ata;
+mod treasury_rent;
+mod unwrap_output;
+mod usd_fee;
+mod vault_batch;
+mod vault_signer;
+mod writable_limit;
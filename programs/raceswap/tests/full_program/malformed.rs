@@ -0,0 +1,50 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+use anchor_lang::error::ErrorCode;
+
+#[test]
+fn leg_data_shorter_than_a_discriminator_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.main_leg.as_mut().unwrap().data.truncate(4);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MalformedParams)
+    );
+}
+
+#[test]
+fn leg_flags_shorter_than_accounts_len_are_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.reflection_legs[0].instruction.is_writable.pop();
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MalformedParams)
+    );
+
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.main_leg.as_mut().unwrap().is_signer.truncate(1);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MalformedParams)
+    );
+}
+
+#[test]
+fn truncated_instruction_data_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(1_000_000, 9_900, 50);
+    let mut ix = fixture.swap_instruction(&swap);
+    ix.data.truncate(ix.data.len() / 2);
+    assert_eq!(
+        fixture.ledger.process(&ix),
+        Err(raceswap_harness::anchor_error(
+            ErrorCode::InstructionDidNotDeserialize
+        ))
+    );
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE
+    );
+}
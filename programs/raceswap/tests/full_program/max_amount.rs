@@ -0,0 +1,40 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+#[test]
+fn max_amount_swaps_the_whole_balance() {
+    let mut fixture = Fixture::new();
+    let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+    // Legs sized for the full 10_000_000 balance
+    let mut swap = fixture.swap(USER_INPUT_BALANCE, 99_000, 500);
+    swap.params.total_input_amount = 0;
+    swap.params.amount_is_max = true;
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_input), 0);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 99_000);
+    // The treasury fee is sized on the balance actually swapped
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_destination_before + USER_INPUT_BALANCE * 20 / 10_000
+    );
+}
+
+#[test]
+fn max_amount_with_an_explicit_amount_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.amount_is_max = true;
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::InvalidAmount));
+}
+
+#[test]
+fn max_amount_of_an_empty_account_is_rejected() {
+    let mut fixture = Fixture::new();
+    let (input, mint, user) = (fixture.user_input, fixture.input_mint, fixture.user);
+    fixture.ledger.add_token_account(input, mint, user, 0);
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.total_input_amount = 0;
+    swap.params.amount_is_max = true;
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::InvalidAmount));
+}
@@ -0,0 +1,58 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+fn cap_input(fixture: &mut Fixture, max_input_per_swap: u64) {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_input_per_swap: Some(max_input_per_swap),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+#[test]
+fn swap_at_the_cap_passes() {
+    let mut fixture = Fixture::new();
+    cap_input(&mut fixture, 1_000_000);
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn swap_above_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    cap_input(&mut fixture, 999_999);
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::InputExceedsMax)
+    );
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE
+    );
+}
+
+#[test]
+fn max_amount_swap_is_capped_on_the_resolved_balance() {
+    let mut fixture = Fixture::new();
+    cap_input(&mut fixture, 1_000_000);
+    let mut swap = fixture.swap(USER_INPUT_BALANCE, 99_000, 500);
+    swap.params.total_input_amount = 0;
+    swap.params.amount_is_max = true;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InputExceedsMax)
+    );
+}
+
+#[test]
+fn zero_disables_the_cap() {
+    let mut fixture = Fixture::new();
+    cap_input(&mut fixture, 1);
+    cap_input(&mut fixture, 0);
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+}
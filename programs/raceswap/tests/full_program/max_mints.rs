@@ -0,0 +1,60 @@
+use crate::fixture::{error, leg, Fixture, Swap, USER_INPUT_BALANCE};
+use crate::*;
+use raceswap_harness::route_data;
+
+fn set_max_mints(fixture: &mut Fixture, max_mints_per_swap: u8) {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_mints_per_swap: Some(max_mints_per_swap),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+/// The default swap (input, main and reflection mints) with a main route
+/// that also passes through a token account of `hops` intermediate mints.
+fn multi_hop_swap(fixture: &mut Fixture, hops: usize) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    let mut writable = vec![true, true, false];
+    for _ in 0..hops {
+        let mint = Pubkey::new_unique();
+        fixture.ledger.add_mint(mint, 6);
+        let account = fixture.token_account(mint, fixture.swap_authority, 0);
+        swap.remaining.push(AccountMeta::new(account, false));
+        writable.push(true);
+    }
+    swap.params.main_leg = Some(leg(route_data(990_000, 9_000), &writable));
+    swap
+}
+
+#[test]
+fn route_within_the_limit_passes() {
+    let mut fixture = Fixture::new();
+    set_max_mints(&mut fixture, 4);
+    let swap = multi_hop_swap(&mut fixture, 1);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn route_touching_too_many_mints_is_rejected_before_any_fee() {
+    let mut fixture = Fixture::new();
+    set_max_mints(&mut fixture, 4);
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+    let swap = multi_hop_swap(&mut fixture, 2);
+
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::TooManyMints));
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_before
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_input), USER_INPUT_BALANCE);
+}
+
+#[test]
+fn zero_disables_the_limit() {
+    let mut fixture = Fixture::new();
+    let swap = multi_hop_swap(&mut fixture, 6);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
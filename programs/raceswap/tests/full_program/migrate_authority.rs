@@ -0,0 +1,68 @@
+use crate::fixture::{error, pda, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+const NEW_SEED: &[u8] = b"raceswap-authority-v2";
+
+fn migrate(fixture: &mut Fixture, authority: Pubkey, vaults: &[Pubkey]) -> ProgramResult {
+    let accounts = accounts::MigrateAuthoritySeed {
+        config: fixture.config,
+        authority,
+        old_swap_authority: fixture.swap_authority,
+        token_program: TOKEN_PROGRAM_ID,
+    };
+    let vaults: Vec<AccountMeta> = vaults
+        .iter()
+        .map(|vault| AccountMeta::new(*vault, false))
+        .collect();
+    let data = instruction::MigrateAuthoritySeed {
+        new_authority_seed: NEW_SEED.to_vec(),
+    };
+    fixture.admin(&accounts, data, &[], &vaults)
+}
+
+#[test]
+fn vaults_move_from_the_old_to_the_new_authority_pda() {
+    let mut fixture = Fixture::new();
+    let second_mint = fixture.main_mint;
+    fixture.create_vaults(&[second_mint], &[]).unwrap();
+    let vaults = [fixture.vault, fixture.vault_for(&second_mint)];
+    assert_eq!(
+        fixture.ledger.token_account(&vaults[0]).owner,
+        fixture.swap_authority
+    );
+
+    let authority = fixture.authority;
+    migrate(&mut fixture, authority, &vaults).unwrap();
+
+    let new_authority = pda(&[NEW_SEED, fixture.config.as_ref()]);
+    for vault in vaults {
+        assert_eq!(fixture.ledger.token_account(&vault).owner, new_authority);
+    }
+}
+
+#[test]
+fn vault_not_owned_by_the_old_authority_is_rejected() {
+    let mut fixture = Fixture::new();
+    let stray = fixture.token_account(fixture.input_mint, fixture.user, 0);
+    let authority = fixture.authority;
+    assert_eq!(
+        migrate(&mut fixture, authority, &[stray]),
+        error(RaceswapError::InvalidVaultOwner)
+    );
+}
+
+#[test]
+fn non_authority_cannot_migrate() {
+    let mut fixture = Fixture::new();
+    let vault = fixture.vault;
+    let user = fixture.user;
+    assert_eq!(
+        migrate(&mut fixture, user, &[vault]),
+        error(RaceswapError::Unauthorized)
+    );
+    assert_eq!(
+        fixture.ledger.token_account(&vault).owner,
+        fixture.swap_authority
+    );
+}
@@ -0,0 +1,42 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+fn set_tolerance(fixture: &mut Fixture, min_out_tolerance: u64) {
+    fixture
+        .update_config(UpdateConfigParams {
+            min_out_tolerance: Some(min_out_tolerance),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+#[test]
+fn delta_one_unit_below_min_passes_within_tolerance() {
+    let mut fixture = Fixture::new();
+    set_tolerance(&mut fixture, 1);
+
+    let mut swap = fixture.swap(1_000_000, 8_999, 50);
+    swap.params.min_main_out = 9_000;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 8_999);
+}
+
+#[test]
+fn delta_past_tolerance_is_rejected() {
+    let mut fixture = Fixture::new();
+    set_tolerance(&mut fixture, 1);
+
+    let mut swap = fixture.swap(1_000_000, 8_998, 50);
+    swap.params.min_main_out = 9_000;
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MainBelowMinOut));
+}
+
+#[test]
+fn default_tolerance_is_strict() {
+    let mut fixture = Fixture::new();
+    assert_eq!(fixture.config().min_out_tolerance, 0);
+
+    let mut swap = fixture.swap(1_000_000, 8_999, 50);
+    swap.params.min_main_out = 9_000;
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MainBelowMinOut));
+}
@@ -0,0 +1,73 @@
+use crate::fixture::{error, pda, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+fn set_mint_fee(fixture: &mut Fixture, authority: Pubkey, treasury_fee_bps: u16) -> ProgramResult {
+    let mint = fixture.input_mint;
+    let accounts = accounts::SetMintFee {
+        config: fixture.config,
+        mint_fee: pda(&[MINT_FEE_SEED, mint.as_ref()]),
+        authority,
+        system_program: system_program::ID,
+    };
+    fixture.admin(
+        &accounts,
+        instruction::SetMintFee {
+            mint,
+            treasury_fee_bps,
+        },
+        &[],
+        &[],
+    )
+}
+
+/// Runs the default swap, optionally with the input mint's override, and
+/// returns the SOL fee paid.
+fn swap_fee(fixture: &mut Fixture, with_override: bool) -> u64 {
+    let before = fixture.ledger.lamports(&fixture.fee_destination);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    if with_override {
+        swap.accounts.mint_fee = Some(pda(&[MINT_FEE_SEED, fixture.input_mint.as_ref()]));
+    }
+    fixture.execute(&swap).unwrap();
+    fixture.ledger.lamports(&fixture.fee_destination) - before
+}
+
+#[test]
+fn override_replaces_the_configured_fee() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    set_mint_fee(&mut fixture, authority, 0).unwrap();
+    assert_eq!(swap_fee(&mut fixture, true), 0);
+
+    set_mint_fee(&mut fixture, authority, 50).unwrap();
+    assert_eq!(swap_fee(&mut fixture, true), 5_000);
+}
+
+#[test]
+fn swaps_without_the_override_pay_the_configured_fee() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    set_mint_fee(&mut fixture, authority, 0).unwrap();
+    assert_eq!(swap_fee(&mut fixture, false), 2_000);
+}
+
+#[test]
+fn only_the_authority_can_set_an_override() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    assert_eq!(
+        set_mint_fee(&mut fixture, user, 0),
+        error(RaceswapError::Unauthorized)
+    );
+}
+
+#[test]
+fn override_above_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    assert_eq!(
+        set_mint_fee(&mut fixture, authority, 1_001),
+        error(RaceswapError::InvalidFeeConfig)
+    );
+}
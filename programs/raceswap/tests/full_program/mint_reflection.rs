@@ -0,0 +1,71 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+const PER_MINT_BPS: u16 = 300;
+
+/// Points the fixture's input mint at a fresh reflection mint at
+/// `PER_MINT_BPS`, returning that mint and the user's account for it.
+fn configure(fixture: &mut Fixture) -> (Pubkey, Pubkey) {
+    let reflection_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(reflection_mint, 6);
+    let destination = fixture.token_account(reflection_mint, fixture.user, 0);
+    fixture
+        .update_config(UpdateConfigParams {
+            reflection_config_per_mint: Some(vec![MintReflectionConfig {
+                input_mint: fixture.input_mint,
+                reflection_mint,
+                reflection_fee_bps: PER_MINT_BPS,
+            }]),
+            ..Default::default()
+        })
+        .unwrap();
+    (reflection_mint, destination)
+}
+
+#[test]
+fn configured_input_mint_selects_its_reflection_target_and_rate() {
+    let mut fixture = Fixture::new();
+    let (reflection_mint, destination) = configure(&mut fixture);
+
+    let mut swap = fixture.swap_at(1_000_000, 9_700, 70, PER_MINT_BPS);
+    swap.params.reflection_legs[0].mint = reflection_mint;
+    swap.accounts.user_reflection_destination = destination;
+    swap.remaining[1].pubkey = destination;
+    fixture.execute(&swap).unwrap();
+
+    // 3% of the input went to the reflection leg instead of the default 1%
+    assert_eq!(fixture.token_amount(&destination), 70);
+    assert_eq!(fixture.token_amount(&fixture.user_input), 9_000_000);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn route_for_the_params_reflection_mint_is_rejected() {
+    let mut fixture = Fixture::new();
+    let (_, destination) = configure(&mut fixture);
+
+    let mut swap = fixture.swap_at(1_000_000, 9_700, 70, PER_MINT_BPS);
+    swap.accounts.user_reflection_destination = destination;
+    swap.remaining[1].pubkey = destination;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ReflectionMintMismatch)
+    );
+}
+
+#[test]
+fn duplicate_input_mint_entries_are_rejected() {
+    let mut fixture = Fixture::new();
+    let entry = MintReflectionConfig {
+        input_mint: fixture.input_mint,
+        reflection_mint: fixture.reflection_mint,
+        reflection_fee_bps: 100,
+    };
+    assert_eq!(
+        fixture.update_config(UpdateConfigParams {
+            reflection_config_per_mint: Some(vec![entry.clone(), entry]),
+            ..Default::default()
+        }),
+        error(RaceswapError::DuplicateMintReflectionConfig)
+    );
+}
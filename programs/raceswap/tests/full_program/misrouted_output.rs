@@ -0,0 +1,26 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+#[test]
+fn output_delivered_to_a_sibling_account_is_reported() {
+    let mut fixture = Fixture::new();
+    let sibling = fixture.token_account(fixture.main_mint, fixture.user, 0);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.remaining[3].pubkey = sibling;
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainOutputNotReceived)
+    );
+    assert_eq!(fixture.token_amount(&sibling), 0);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn short_delivery_to_the_destination_is_slippage() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 1, 50);
+    swap.params.min_main_out = 9_000;
+
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MainBelowMinOut));
+}
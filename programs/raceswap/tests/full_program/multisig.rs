@@ -0,0 +1,78 @@
+use crate::fixture::{error, Fixture, TREASURY_FEE_BPS};
+use crate::*;
+
+/// Installs a 2-of-3 multisig of the authority and two new signers,
+/// returning the new signers.
+fn two_of_three(fixture: &mut Fixture) -> [Pubkey; 2] {
+    let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+    fixture
+        .update_config(UpdateConfigParams {
+            multisig_signers: Some(vec![fixture.authority, signers[0], signers[1]]),
+            multisig_threshold: Some(2),
+            ..Default::default()
+        })
+        .unwrap();
+    signers
+}
+
+fn fee_update(treasury_fee_bps: u16) -> UpdateConfigParams {
+    UpdateConfigParams {
+        treasury_fee_bps: Some(treasury_fee_bps),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn update_meeting_the_threshold_is_applied() {
+    let mut fixture = Fixture::new();
+    let signers = two_of_three(&mut fixture);
+
+    let authority = fixture.authority;
+    fixture
+        .update_config_signed(authority, fee_update(10), &[signers[1]])
+        .unwrap();
+    assert_eq!(fixture.config().treasury_fee_bps, 10);
+
+    // Any two configured signers will do, with or without the authority
+    fixture
+        .update_config_signed(signers[0], fee_update(5), &[signers[1]])
+        .unwrap();
+    assert_eq!(fixture.config().treasury_fee_bps, 5);
+}
+
+#[test]
+fn update_short_of_the_threshold_is_rejected() {
+    let mut fixture = Fixture::new();
+    let signers = two_of_three(&mut fixture);
+
+    let authority = fixture.authority;
+    assert_eq!(
+        fixture.update_config(fee_update(10)),
+        error(RaceswapError::MultisigThresholdNotMet)
+    );
+    // An outsider co-signing does not count toward the threshold
+    let outsider = Pubkey::new_unique();
+    assert_eq!(
+        fixture.update_config_signed(authority, fee_update(10), &[outsider]),
+        error(RaceswapError::MultisigThresholdNotMet)
+    );
+    // Nor does the same signer twice
+    assert_eq!(
+        fixture.update_config_signed(signers[0], fee_update(10), &[signers[0]]),
+        error(RaceswapError::MultisigThresholdNotMet)
+    );
+    assert_eq!(fixture.config().treasury_fee_bps, TREASURY_FEE_BPS);
+}
+
+#[test]
+fn threshold_above_the_signer_count_is_rejected() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        fixture.update_config(UpdateConfigParams {
+            multisig_signers: Some(vec![fixture.authority]),
+            multisig_threshold: Some(2),
+            ..Default::default()
+        }),
+        error(RaceswapError::InvalidMultisigConfig)
+    );
+}
@@ -0,0 +1,76 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
+
+/// The default swap paid in SOL through the user's WSOL ATA, which does not
+/// exist yet. Returns the swap and the ATA.
+fn native_swap(fixture: &mut Fixture) -> (Swap, Pubkey) {
+    fixture.ledger.add_mint(NATIVE_MINT, 9);
+    fixture.create_vaults(&[NATIVE_MINT], &[]).unwrap();
+    let vault = fixture.vault_for(&NATIVE_MINT);
+    let wsol = get_associated_token_address_with_program_id(
+        &fixture.user,
+        &NATIVE_MINT,
+        &TOKEN_PROGRAM_ID,
+    );
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.input_mint = NATIVE_MINT;
+    swap.accounts.input_mint = NATIVE_MINT;
+    swap.accounts.user_input = wsol;
+    swap.accounts.input_vault = vault;
+    swap.accounts.associated_token_program = Some(associated_token::ID);
+    swap.remaining[0].pubkey = vault;
+    swap.remaining[2].pubkey = vault;
+    (swap, wsol)
+}
+
+#[test]
+fn sol_input_is_wrapped_and_the_wrapper_closed() {
+    let mut fixture = Fixture::new();
+    let (swap, wsol) = native_swap(&mut fixture);
+    let user_before = fixture.ledger.lamports(&fixture.user);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+    // The ATA's rent comes back on close: only the input and fee are spent
+    assert!(!fixture.ledger.exists(&wsol));
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.user),
+        user_before - 1_000_000 - 2_000
+    );
+}
+
+#[test]
+fn existing_wsol_account_is_topped_up_and_kept() {
+    let mut fixture = Fixture::new();
+    let (swap, wsol) = native_swap(&mut fixture);
+    let user = fixture.user;
+    fixture
+        .ledger
+        .add_token_account(wsol, NATIVE_MINT, user, 400_000);
+    let user_before = fixture.ledger.lamports(&user);
+    fixture.execute(&swap).unwrap();
+
+    // Only the 600_000 shortfall is wrapped from native SOL
+    assert!(fixture.ledger.exists(&wsol));
+    assert_eq!(fixture.token_amount(&wsol), 0);
+    assert_eq!(
+        fixture.ledger.lamports(&user),
+        user_before - 600_000 - 2_000
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn creating_the_wrapper_needs_the_associated_token_program() {
+    let mut fixture = Fixture::new();
+    let (mut swap, wsol) = native_swap(&mut fixture);
+    swap.accounts.associated_token_program = None;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MissingAssociatedTokenProgram)
+    );
+    assert!(!fixture.ledger.exists(&wsol));
+}
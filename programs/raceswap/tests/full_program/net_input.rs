@@ -0,0 +1,30 @@
+use crate::fixture::{error, leg, Fixture};
+use crate::*;
+use raceswap_harness::route_data;
+
+#[test]
+fn main_leg_runs_on_the_input_net_of_reflection() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(1_000_000, 9_900, 50);
+    fixture.execute(&swap).unwrap();
+
+    // Reflection took 1%, the main leg exactly the remaining 990_000
+    assert_eq!(fixture.token_amount(&fixture.user_input), 9_000_000);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+}
+
+#[test]
+fn main_leg_drawing_the_gross_input_is_rejected() {
+    let mut fixture = Fixture::new();
+    // Leftover vault balance the main leg could otherwise dip into
+    let (vault, mint, owner) = (fixture.vault, fixture.input_mint, fixture.swap_authority);
+    fixture.ledger.add_token_account(vault, mint, owner, 10_000);
+
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.main_leg = Some(leg(route_data(1_000_000, 9_900), &[true, true, false]));
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainInputExceeded)
+    );
+}
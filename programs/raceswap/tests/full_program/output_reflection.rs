@@ -0,0 +1,43 @@
+use crate::fixture::{error, leg, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// An output-mode swap of 1_000_000 whose main leg realizes `main_out` and
+/// whose reflection leg then draws `reflection_in` of it from the user's main
+/// destination.
+fn output_swap(fixture: &Fixture, main_out: u64, reflection_in: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, main_out - reflection_in, 0);
+    swap.params.reflection_mode = ReflectionMode::Output;
+    swap.params.main_leg = Some(leg(route_data(1_000_000, main_out), &[true, true, false]));
+    swap.params.reflection_legs[0].instruction = leg(route_data(reflection_in, 7), &[true, true]);
+    swap.remaining = vec![
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new(fixture.user_main, false),
+        AccountMeta::new_readonly(fixture.swap_authority, false),
+        AccountMeta::new(fixture.user_main, false),
+        AccountMeta::new(fixture.user_reflection, false),
+    ];
+    swap
+}
+
+#[test]
+fn reflection_is_computed_from_the_realized_main_output() {
+    let mut fixture = Fixture::new();
+    // 1% of the 10_000 realized main output is reflected
+    let swap = output_swap(&fixture, 10_000, 100);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_input), 9_000_000);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 7);
+}
+
+#[test]
+fn reflection_leg_drawing_more_than_its_share_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = output_swap(&fixture, 10_000, 101);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionSplit)
+    );
+}
@@ -0,0 +1,109 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+#[test]
+fn paused_swaps_fail_until_unpaused() {
+    let mut fixture = Fixture::new();
+    fixture.set_pause(true).unwrap();
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::ProgramPaused)
+    );
+
+    // Config updates keep working during the pause
+    fixture
+        .update_config(UpdateConfigParams {
+            treasury_fee_bps: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+
+    fixture.set_pause(false).unwrap();
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn only_the_authority_can_pause() {
+    let mut fixture = Fixture::new();
+    let accounts = accounts::UpdateConfig {
+        config: fixture.config,
+        authority: fixture.user,
+    };
+    assert_eq!(
+        fixture.admin(&accounts, instruction::SetPause { paused: true }, &[], &[]),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.config().paused);
+}
+
+fn set_mint_paused(
+    fixture: &mut Fixture,
+    authority: Pubkey,
+    mint: Pubkey,
+    paused: bool,
+) -> ProgramResult {
+    let accounts = accounts::UpdateConfig {
+        config: fixture.config,
+        authority,
+    };
+    fixture.admin(
+        &accounts,
+        instruction::SetMintPaused { mint, paused },
+        &[],
+        &[],
+    )
+}
+
+#[test]
+fn paused_input_or_output_mint_blocks_its_swaps() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    for mint in [fixture.input_mint, fixture.main_mint] {
+        set_mint_paused(&mut fixture, authority, mint, true).unwrap();
+        assert_eq!(
+            fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+            error(RaceswapError::MintPaused)
+        );
+        set_mint_paused(&mut fixture, authority, mint, false).unwrap();
+    }
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert!(fixture.config().paused_mints.is_empty());
+}
+
+#[test]
+fn swaps_of_other_mints_are_unaffected() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    set_mint_paused(&mut fixture, authority, Pubkey::new_unique(), true).unwrap();
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn at_most_eight_mints_can_be_paused() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    for _ in 0..8 {
+        set_mint_paused(&mut fixture, authority, Pubkey::new_unique(), true).unwrap();
+    }
+    assert_eq!(
+        set_mint_paused(&mut fixture, authority, Pubkey::new_unique(), true),
+        error(RaceswapError::TooManyPausedMints)
+    );
+}
+
+#[test]
+fn only_the_authority_can_pause_a_mint() {
+    let mut fixture = Fixture::new();
+    let (user, mint) = (fixture.user, fixture.input_mint);
+    assert_eq!(
+        set_mint_paused(&mut fixture, user, mint, true),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(fixture.config().paused_mints.is_empty());
+}
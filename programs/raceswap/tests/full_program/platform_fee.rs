@@ -0,0 +1,67 @@
+use crate::fixture::{error, leg, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// Configures `ours` as the platform-fee account, returning it.
+fn configure(fixture: &mut Fixture) -> Pubkey {
+    let owner = Pubkey::new_unique();
+    let ours = fixture.token_account(fixture.main_mint, owner, 0);
+    fixture
+        .update_config(UpdateConfigParams {
+            platform_fee_account: Some(Some(ours)),
+            ..Default::default()
+        })
+        .unwrap();
+    ours
+}
+
+/// A swap whose main leg carries `fee_account` as its fourth account.
+fn swap_with_fee_account(fixture: &Fixture, fee_account: Pubkey) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.main_leg = Some(leg(route_data(990_000, 9_900), &[true, true, false, true]));
+    swap.params.platform_fee_account_index = Some(3);
+    swap.remaining.push(AccountMeta::new(fee_account, false));
+    swap
+}
+
+#[test]
+fn configured_platform_fee_account_is_accepted() {
+    let mut fixture = Fixture::new();
+    let ours = configure(&mut fixture);
+    let swap = swap_with_fee_account(&fixture, ours);
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn substituted_platform_fee_account_is_rejected() {
+    let mut fixture = Fixture::new();
+    configure(&mut fixture);
+    let theirs = fixture.token_account(fixture.main_mint, Pubkey::new_unique(), 0);
+    let swap = swap_with_fee_account(&fixture, theirs);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::WrongPlatformFeeAccount)
+    );
+}
+
+#[test]
+fn route_without_a_platform_fee_uses_the_program_placeholder() {
+    let mut fixture = Fixture::new();
+    configure(&mut fixture);
+    let mut swap = swap_with_fee_account(&fixture, JUPITER_PROGRAM_ID);
+    swap.params.main_leg.as_mut().unwrap().is_writable[3] = false;
+    swap.remaining[5] = AccountMeta::new_readonly(JUPITER_PROGRAM_ID, false);
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn missing_platform_fee_index_is_rejected() {
+    let mut fixture = Fixture::new();
+    let ours = configure(&mut fixture);
+    let mut swap = swap_with_fee_account(&fixture, ours);
+    swap.params.platform_fee_account_index = None;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::WrongPlatformFeeAccount)
+    );
+}
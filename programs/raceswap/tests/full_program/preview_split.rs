@@ -0,0 +1,52 @@
+use crate::fixture::{instruction, Fixture};
+use crate::*;
+
+fn preview(fixture: &mut Fixture, total_input_amount: u64) -> SwapSplit {
+    let ix = instruction(
+        &accounts::PreviewSplit {
+            config: fixture.config,
+            user_stats: None,
+            mint_fee: None,
+            sol_usd_price: None,
+            rebate_nft: None,
+        },
+        instruction::PreviewSplit {
+            params: PreviewSplitParams {
+                user: fixture.user,
+                input_mint: fixture.input_mint,
+                total_input_amount,
+                disable_reflection: false,
+                reflection_mode: ReflectionMode::Input,
+                reflection_amount_override: None,
+                fee_in_input_token: false,
+            },
+        },
+        &[],
+    );
+    fixture.ledger.process(&ix).unwrap();
+    let (_, data) = fixture.ledger.return_data().unwrap();
+    SwapSplit::try_from_slice(&data).unwrap()
+}
+
+#[test]
+fn preview_matches_the_executed_split() {
+    let mut fixture = Fixture::new();
+    for total in [1_000_000, 999_999, 12_345, 5_000_001] {
+        let split = preview(&mut fixture, total);
+
+        let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+        let swap = fixture.swap(total, 9_900, 50);
+        fixture.execute(&swap).unwrap();
+        let (_, data) = fixture.ledger.return_data().unwrap();
+        let (_, legs) = <(SwapStatus, Vec<LegResult>)>::try_from_slice(&data).unwrap();
+
+        assert_eq!(split.reflection_amount, legs[0].in_amount, "total {total}");
+        assert_eq!(split.main_input_amount, legs[1].in_amount, "total {total}");
+        assert_eq!(
+            split.treasury_fee_lamports,
+            fixture.ledger.lamports(&fixture.fee_destination) - fee_destination_before,
+            "total {total}"
+        );
+        assert_eq!(split.treasury_fee_tokens, 0);
+    }
+}
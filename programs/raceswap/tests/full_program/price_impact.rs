@@ -0,0 +1,45 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+/// The default swap (9_000 out) against a 10_000 quote: a 1_000 bps impact.
+fn quoted_swap(fixture: &mut Fixture, max_impact_bps: u16) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.expected_out = 10_000;
+    swap.params.max_impact_bps = max_impact_bps;
+    fixture.execute(&swap)
+}
+
+#[test]
+fn impact_at_the_limit_passes() {
+    let mut fixture = Fixture::new();
+    quoted_swap(&mut fixture, 1_000).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn impact_past_the_limit_is_rejected_despite_clearing_min_out() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        quoted_swap(&mut fixture, 999),
+        error(RaceswapError::ExcessivePriceImpact)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn output_above_the_quote_passes() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.expected_out = 8_000;
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn no_quote_skips_the_check() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(1_000_000, 9_000, 50);
+    assert_eq!(swap.params.expected_out, 0);
+    assert_eq!(swap.params.max_impact_bps, 0);
+    fixture.execute(&swap).unwrap();
+}
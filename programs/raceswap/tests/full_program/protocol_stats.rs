@@ -0,0 +1,77 @@
+use crate::fixture::{instruction, Fixture};
+use crate::*;
+
+#[test]
+fn swaps_accumulate_into_the_lifetime_totals() {
+    let mut fixture = Fixture::new();
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    fixture.execute(&fixture.swap(500_000, 4_500, 25)).unwrap();
+
+    let stats = fixture.protocol_stats();
+    assert_eq!(stats.total_swaps, 2);
+    assert_eq!(stats.total_volume, 1_500_000);
+    assert_eq!(stats.total_treasury_lamports, 3_000);
+    assert_eq!(stats.total_treasury_tokens, 0);
+}
+
+#[test]
+fn reverted_swap_is_not_counted() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.min_main_out = 9_001;
+    assert!(fixture.execute(&swap).is_err());
+
+    let stats = fixture.protocol_stats();
+    assert_eq!(stats.total_swaps, 0);
+    assert_eq!(stats.total_volume, 0);
+    assert_eq!(stats.total_treasury_lamports, 0);
+}
+
+#[test]
+fn stats_cannot_be_initialized_twice() {
+    let mut fixture = Fixture::new();
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    let init = instruction(
+        &accounts::InitProtocolStats {
+            protocol_stats: fixture.protocol_stats,
+            payer: fixture.authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitProtocolStats {},
+        &[],
+    );
+    assert!(fixture.ledger.process(&init).is_err());
+    assert_eq!(fixture.protocol_stats().total_swaps, 1);
+}
+
+#[test]
+fn snapshot_reports_the_totals_after_a_few_swaps() {
+    let mut fixture = Fixture::new();
+    for _ in 0..3 {
+        fixture
+            .execute(&fixture.swap(1_000_000, 9_000, 50))
+            .unwrap();
+    }
+    fixture.ledger.set_slot(42);
+
+    // Anyone may take the snapshot: no signer is required
+    let snapshot = instruction(
+        &accounts::SnapshotStats {
+            protocol_stats: fixture.protocol_stats,
+        },
+        instruction::SnapshotStats {},
+        &[],
+    );
+    fixture.ledger.process(&snapshot).unwrap();
+
+    let events = fixture.ledger.events::<StatsSnapshot>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].total_volume, 3_000_000);
+    assert_eq!(events[0].total_fees_collected, 6_000);
+    assert_eq!(events[0].swap_nonce, 3);
+    assert_eq!(events[0].slot, 42);
+}
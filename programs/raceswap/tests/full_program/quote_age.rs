@@ -0,0 +1,57 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+const MAX_AGE: u64 = 20;
+
+fn swap_quoted_at(fixture: &mut Fixture, quote_slot: u64) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.quote_slot = quote_slot;
+    fixture.execute(&swap)
+}
+
+fn fixture_with_max_age() -> Fixture {
+    let mut fixture = Fixture::new();
+    fixture
+        .update_config(UpdateConfigParams {
+            max_quote_age_slots: Some(MAX_AGE),
+            ..Default::default()
+        })
+        .unwrap();
+    fixture
+}
+
+#[test]
+fn fresh_quote_is_accepted() {
+    let mut fixture = fixture_with_max_age();
+    let slot = fixture.ledger.slot();
+    swap_quoted_at(&mut fixture, slot).unwrap();
+    // Exactly at the maximum age still passes
+    swap_quoted_at(&mut fixture, slot - MAX_AGE).unwrap();
+}
+
+#[test]
+fn stale_quote_is_rejected() {
+    let mut fixture = fixture_with_max_age();
+    let slot = fixture.ledger.slot();
+    assert_eq!(
+        swap_quoted_at(&mut fixture, slot - MAX_AGE - 1),
+        error(RaceswapError::QuoteTooOld)
+    );
+}
+
+#[test]
+fn future_quote_is_rejected() {
+    let mut fixture = fixture_with_max_age();
+    let slot = fixture.ledger.slot();
+    assert_eq!(
+        swap_quoted_at(&mut fixture, slot + 1),
+        error(RaceswapError::InvalidQuoteSlot)
+    );
+}
+
+#[test]
+fn zero_max_age_disables_the_check() {
+    let mut fixture = Fixture::new();
+    swap_quoted_at(&mut fixture, 0).unwrap();
+}
@@ -0,0 +1,78 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+/// A fixture whose config lists one rebate NFT mint, returned alongside it.
+fn rebate_fixture() -> (Fixture, Pubkey) {
+    let mut fixture = Fixture::new();
+    let nft_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(nft_mint, 0);
+    fixture
+        .update_config(UpdateConfigParams {
+            rebate_mints: Some(vec![nft_mint]),
+            ..Default::default()
+        })
+        .unwrap();
+    (fixture, nft_mint)
+}
+
+/// Runs the default swap presenting `rebate_nft`, if any.
+fn swap_with(fixture: &mut Fixture, rebate_nft: Option<Pubkey>) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.rebate_nft = rebate_nft;
+    fixture.execute(&swap)
+}
+
+#[test]
+fn holder_pays_no_treasury_fee() {
+    let (mut fixture, nft_mint) = rebate_fixture();
+    let nft = fixture.token_account(nft_mint, fixture.user, 1);
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+    swap_with(&mut fixture, Some(nft)).unwrap();
+
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_before
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn non_holder_is_charged_the_fee() {
+    let (mut fixture, _) = rebate_fixture();
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+    swap_with(&mut fixture, None).unwrap();
+
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_before + 2_000
+    );
+}
+
+#[test]
+fn nft_held_by_someone_else_is_rejected() {
+    let (mut fixture, nft_mint) = rebate_fixture();
+    let nft = fixture.token_account(nft_mint, Pubkey::new_unique(), 1);
+    assert_eq!(
+        swap_with(&mut fixture, Some(nft)),
+        error(RaceswapError::InvalidRebateNft)
+    );
+}
+
+#[test]
+fn empty_or_unlisted_token_accounts_are_rejected() {
+    let (mut fixture, nft_mint) = rebate_fixture();
+    let empty = fixture.token_account(nft_mint, fixture.user, 0);
+    assert_eq!(
+        swap_with(&mut fixture, Some(empty)),
+        error(RaceswapError::InvalidRebateNft)
+    );
+
+    let unlisted_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(unlisted_mint, 0);
+    let unlisted = fixture.token_account(unlisted_mint, fixture.user, 1);
+    assert_eq!(
+        swap_with(&mut fixture, Some(unlisted)),
+        error(RaceswapError::InvalidRebateNft)
+    );
+}
@@ -0,0 +1,78 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+use anchor_lang::solana_program::program::invoke;
+use std::rc::Rc;
+
+/// Replaces the Jupiter mock with a route that calls straight back into
+/// raceswap, forwarding its accounts and data as the nested instruction.
+fn add_reentrant_route(fixture: &mut Fixture) {
+    fixture.ledger.add_program(
+        JUPITER_PROGRAM_ID,
+        Rc::new(|_, accounts, data| {
+            let metas = accounts
+                .iter()
+                .map(|info| AccountMeta {
+                    pubkey: *info.key,
+                    is_signer: info.is_signer,
+                    is_writable: info.is_writable,
+                })
+                .collect();
+            let ix = Instruction {
+                program_id: ID,
+                accounts: metas,
+                data: data.to_vec(),
+            };
+            invoke(&ix, accounts)
+        }),
+    );
+}
+
+/// A swap whose main leg carries a complete nested `execute_raceswap` as its
+/// accounts and data.
+fn recursive_swap(fixture: &Fixture) -> Swap {
+    let nested = fixture.swap_instruction(&fixture.swap_without_reflection(1_000, 9));
+    let mut swap = fixture.swap_without_reflection(1_000_000, 9_000);
+    swap.params.main_leg = Some(SerializedInstruction {
+        accounts_len: nested.accounts.len() as u16,
+        data: nested.data,
+        is_writable: nested
+            .accounts
+            .iter()
+            .map(|meta| meta.is_writable)
+            .collect(),
+        is_signer: nested.accounts.iter().map(|meta| meta.is_signer).collect(),
+        shared_refs: Vec::new(),
+    });
+    swap.remaining = nested.accounts;
+    swap
+}
+
+#[test]
+fn recursive_swap_trips_the_guard() {
+    let mut fixture = Fixture::new();
+    add_reentrant_route(&mut fixture);
+
+    assert_eq!(
+        fixture.execute(&recursive_swap(&fixture)),
+        error(RaceswapError::SwapCpiFailed)
+    );
+    assert!(fixture
+        .ledger
+        .logs()
+        .iter()
+        .any(|log| log.contains("ReentrancyDetected")));
+    assert!(!fixture.config().in_progress);
+}
+
+#[test]
+fn guard_is_cleared_after_each_swap() {
+    let mut fixture = Fixture::new();
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    assert!(!fixture.config().in_progress);
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 18_000);
+}
@@ -0,0 +1,51 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+fn require_reference_floor(fixture: &mut Fixture, min_reference_output_bps: u16) {
+    fixture
+        .update_config(UpdateConfigParams {
+            min_reference_output_bps: Some(min_reference_output_bps),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+/// The default swap with a reference price of 100 input units per output unit,
+/// so the 990_000 main input is worth 9_900 output.
+fn priced_swap(fixture: &Fixture, main_out: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, main_out, 50);
+    swap.params.reference_price_numerator = 100;
+    swap.params.reference_price_denominator = 1;
+    swap
+}
+
+#[test]
+fn route_far_below_reference_floor_is_rejected() {
+    let mut fixture = Fixture::new();
+    require_reference_floor(&mut fixture, 5_000);
+
+    assert_eq!(
+        fixture.execute(&priced_swap(&fixture, 100)),
+        error(RaceswapError::BelowReferenceFloor)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn route_at_reference_floor_passes() {
+    let mut fixture = Fixture::new();
+    require_reference_floor(&mut fixture, 5_000);
+
+    // Half of the 9_900 theoretical output
+    fixture.execute(&priced_swap(&fixture, 4_950)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 4_950);
+}
+
+#[test]
+fn floor_is_skipped_without_reference_price() {
+    let mut fixture = Fixture::new();
+    require_reference_floor(&mut fixture, 5_000);
+
+    fixture.execute(&fixture.swap(1_000_000, 100, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 100);
+}
@@ -0,0 +1,83 @@
+use crate::fixture::{error, pda, Fixture, Swap};
+use crate::*;
+
+const CODE: [u8; 8] = *b"RACE0001";
+
+/// Registers `CODE` for a funded referrer taking half the treasury fee.
+fn register(fixture: &mut Fixture) -> Pubkey {
+    let referrer = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(referrer, Rent::default().minimum_balance(0));
+    let accounts = accounts::RegisterReferral {
+        config: fixture.config,
+        referral: pda(&[REFERRAL_SEED, &CODE]),
+        authority: fixture.authority,
+        system_program: system_program::ID,
+    };
+    fixture
+        .admin(
+            &accounts,
+            instruction::RegisterReferral {
+                code: CODE,
+                referrer,
+                fee_share_bps: 5_000,
+            },
+            &[],
+            &[],
+        )
+        .unwrap();
+    referrer
+}
+
+fn referred_swap(fixture: &Fixture, code: [u8; 8], referrer: Pubkey) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.referral_code = Some(code);
+    swap.accounts.referral = Some(pda(&[REFERRAL_SEED, &code]));
+    swap.accounts.referrer = Some(referrer);
+    swap
+}
+
+#[test]
+fn registered_code_pays_its_referrer() {
+    let mut fixture = Fixture::new();
+    let referrer = register(&mut fixture);
+    let referrer_before = fixture.ledger.lamports(&referrer);
+    let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+
+    fixture
+        .execute(&referred_swap(&fixture, CODE, referrer))
+        .unwrap();
+    // Half of the 2_000 lamport treasury fee goes to the referrer
+    assert_eq!(fixture.ledger.lamports(&referrer) - referrer_before, 1_000);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination) - fee_destination_before,
+        1_000
+    );
+}
+
+#[test]
+fn unknown_code_is_rejected() {
+    let mut fixture = Fixture::new();
+    let referrer = register(&mut fixture);
+
+    assert_eq!(
+        fixture.execute(&referred_swap(&fixture, *b"NOTACODE", referrer)),
+        error(RaceswapError::UnknownReferralCode)
+    );
+}
+
+#[test]
+fn referrer_other_than_the_registered_one_is_rejected() {
+    let mut fixture = Fixture::new();
+    register(&mut fixture);
+    let impostor = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(impostor, Rent::default().minimum_balance(0));
+
+    assert_eq!(
+        fixture.execute(&referred_swap(&fixture, CODE, impostor)),
+        error(RaceswapError::InvalidReferrer)
+    );
+}
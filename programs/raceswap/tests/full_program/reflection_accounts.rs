@@ -0,0 +1,42 @@
+use crate::fixture::{error, leg, Fixture};
+use crate::*;
+use raceswap_harness::route_data;
+
+#[test]
+fn main_leg_consuming_reflection_accounts_is_reported() {
+    let mut fixture = Fixture::new();
+    // Output mode runs the main leg first; a main leg that claims all five
+    // accounts leaves none for the reflection leg after it
+    let mut swap = fixture.swap(1_000_000, 9_900, 0);
+    swap.params.reflection_mode = ReflectionMode::Output;
+    swap.params.main_leg = Some(leg(
+        route_data(1_000_000, 10_000),
+        &[true, true, false, true, true],
+    ));
+    swap.params.reflection_legs[0].instruction = leg(route_data(100, 7), &[true, true]);
+    swap.remaining = vec![
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new(fixture.user_main, false),
+        AccountMeta::new_readonly(fixture.swap_authority, false),
+        AccountMeta::new(fixture.user_main, false),
+        AccountMeta::new(fixture.user_reflection, false),
+    ];
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ReflectionAccountsConsumed)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn short_accounts_for_the_first_leg_are_a_plain_mismatch() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.remaining.truncate(1);
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::AccountMismatch)
+    );
+}
@@ -0,0 +1,23 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+#[test]
+fn leg_declared_for_another_mint_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reflection_legs[0].mint = fixture.main_mint;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ReflectionMintMismatch)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 0);
+}
+
+#[test]
+fn leg_declared_for_the_reflection_mint_swaps() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(1_000_000, 9_000, 50);
+    assert_eq!(swap.params.reflection_legs[0].mint, fixture.reflection_mint);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+}
@@ -0,0 +1,63 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+fn set_notional(fixture: &mut Fixture, reflection_min_notional: u64) {
+    fixture
+        .update_config(UpdateConfigParams {
+            reflection_min_notional: Some(reflection_min_notional),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+#[test]
+fn swap_below_notional_skips_reflection() {
+    let mut fixture = Fixture::new();
+    set_notional(&mut fixture, 2_000_000);
+
+    // No reflection carve-out: the main leg swaps the whole input
+    let mut swap = fixture.swap_without_reflection(1_000_000, 9_000);
+    swap.params.disable_reflection = false;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 0);
+
+    let skipped = fixture.ledger.events::<ReflectionSkipped>();
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0].reason == ReflectionSkipReason::BelowNotional);
+}
+
+#[test]
+fn reflection_leg_below_notional_is_rejected() {
+    let mut fixture = Fixture::new();
+    set_notional(&mut fixture, 2_000_000);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::UnexpectedReflectionLeg)
+    );
+}
+
+#[test]
+fn swap_at_notional_reflects() {
+    let mut fixture = Fixture::new();
+    set_notional(&mut fixture, 1_000_000);
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+    assert!(fixture.ledger.events::<ReflectionSkipped>().is_empty());
+}
+
+#[test]
+fn notional_skip_is_distinct_from_dust() {
+    let mut fixture = Fixture::new();
+
+    // 1% of 99 rounds down to nothing
+    let mut swap = fixture.swap_without_reflection(99, 1);
+    swap.params.disable_reflection = false;
+    fixture.execute(&swap).unwrap();
+
+    let skipped = fixture.ledger.events::<ReflectionSkipped>();
+    assert_eq!(skipped.len(), 1);
+    assert!(skipped[0].reason == ReflectionSkipReason::Dust);
+}
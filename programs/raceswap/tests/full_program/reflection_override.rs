@@ -0,0 +1,78 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+fn allow_override(fixture: &mut Fixture, max_reflection_override_bps: u16) {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_reflection_override_bps: Some(max_reflection_override_bps),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+/// A swap of 1_000_000 whose reflection leg is sized for `reflection_amount`
+/// and which asks for exactly that amount through the override.
+fn override_swap(fixture: &Fixture, reflection_amount: u64) -> Swap {
+    let reflection_bps = (reflection_amount * 10_000 / 1_000_000) as u16;
+    let mut swap = fixture.swap_at(1_000_000, 9_000, 300, reflection_bps);
+    swap.params.reflection_amount_override = Some(reflection_amount);
+    swap
+}
+
+#[test]
+fn override_replaces_bps_reflection() {
+    let mut fixture = Fixture::new();
+    allow_override(&mut fixture, 500);
+
+    // 3% of the input instead of the configured 1%
+    fixture.execute(&override_swap(&fixture, 30_000)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 300);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn override_above_configured_share_is_rejected() {
+    let mut fixture = Fixture::new();
+    allow_override(&mut fixture, 500);
+
+    assert_eq!(
+        fixture.execute(&override_swap(&fixture, 60_000)),
+        error(RaceswapError::InvalidReflectionOverride)
+    );
+}
+
+#[test]
+fn override_is_rejected_while_disabled() {
+    let mut fixture = Fixture::new();
+
+    assert_eq!(
+        fixture.execute(&override_swap(&fixture, 30_000)),
+        error(RaceswapError::InvalidReflectionOverride)
+    );
+}
+
+#[test]
+fn override_above_total_input_is_rejected() {
+    let mut fixture = Fixture::new();
+    allow_override(&mut fixture, 9_999);
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reflection_amount_override = Some(1_000_001);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionOverride)
+    );
+}
+
+#[test]
+fn without_override_reflection_follows_bps() {
+    let mut fixture = Fixture::new();
+    allow_override(&mut fixture, 500);
+
+    // The main leg spends the 990_000 left after the configured 1%, which it
+    // could not if the reflection were sized any larger
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
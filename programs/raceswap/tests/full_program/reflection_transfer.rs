@@ -0,0 +1,74 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+/// The default swap with its reflection sent to a fresh community account
+/// in the input token instead of swapped.
+fn transfer_swap(fixture: &mut Fixture) -> (Swap, Pubkey) {
+    let community = fixture.token_account(fixture.input_mint, Pubkey::new_unique(), 0);
+    let mut swap = fixture.swap(1_000_000, 9_000, 0);
+    swap.params.reflection_legs.clear();
+    swap.remaining.drain(..2);
+    swap.params.reflection_action = ReflectionAction::Transfer;
+    swap.accounts.community_destination = Some(community);
+    (swap, community)
+}
+
+#[test]
+fn reflection_is_transferred_without_a_leg() {
+    let mut fixture = Fixture::new();
+    let (swap, community) = transfer_swap(&mut fixture);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&community), 10_000);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 0);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn transfer_needs_a_community_account() {
+    let mut fixture = Fixture::new();
+    let (mut swap, _) = transfer_swap(&mut fixture);
+    swap.accounts.community_destination = None;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionAction)
+    );
+}
+
+#[test]
+fn community_account_must_hold_the_input_mint() {
+    let mut fixture = Fixture::new();
+    let (mut swap, _) = transfer_swap(&mut fixture);
+    let community = fixture.token_account(fixture.main_mint, Pubkey::new_unique(), 0);
+    swap.accounts.community_destination = Some(community);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionAction)
+    );
+}
+
+#[test]
+fn transfer_with_a_reflection_leg_is_rejected() {
+    let mut fixture = Fixture::new();
+    let community = fixture.token_account(fixture.input_mint, Pubkey::new_unique(), 0);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.reflection_action = ReflectionAction::Transfer;
+    swap.accounts.community_destination = Some(community);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::UnexpectedReflectionLeg)
+    );
+    assert_eq!(fixture.token_amount(&community), 0);
+}
+
+#[test]
+fn transfer_in_output_mode_is_rejected() {
+    let mut fixture = Fixture::new();
+    let (mut swap, _) = transfer_swap(&mut fixture);
+    swap.params.reflection_mode = ReflectionMode::Output;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidReflectionAction)
+    );
+}
@@ -0,0 +1,53 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+/// A swap whose reflection leg buys a Token-2022 mint withholding 1% on
+/// delivery, with a reflection floor of 1_000 and `delivered` landing in the
+/// user's account after the fee.
+fn fee_bearing_swap(fixture: &mut Fixture, delivered: u64) -> Swap {
+    let mint = Pubkey::new_unique();
+    fixture.ledger.add_transfer_fee_mint(mint, 6, 100, u64::MAX);
+    let destination = fixture.token_account(mint, fixture.user, 0);
+
+    let mut swap = fixture.swap(1_000_000, 9_900, delivered);
+    swap.params.reflection_mint = mint;
+    swap.params.reflection_legs[0].mint = mint;
+    swap.params.reflection_legs[0].min_out = 1_000;
+    swap.accounts.reflection_mint = Some(mint);
+    swap.accounts.user_reflection_destination = destination;
+    swap.remaining[1].pubkey = destination;
+    swap
+}
+
+#[test]
+fn withheld_fee_counts_toward_the_reflection_floor() {
+    let mut fixture = Fixture::new();
+    // 1_000 sent, 10 withheld: the 990 credited meets the 1_000 floor
+    let swap = fee_bearing_swap(&mut fixture, 990);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(
+        fixture.token_amount(&swap.accounts.user_reflection_destination),
+        990
+    );
+}
+
+#[test]
+fn reflection_below_the_floor_after_grossing_up_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = fee_bearing_swap(&mut fixture, 980);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ReflectionBelowMinOut)
+    );
+}
+
+#[test]
+fn without_the_mint_account_the_credited_amount_is_used() {
+    let mut fixture = Fixture::new();
+    let mut swap = fee_bearing_swap(&mut fixture, 990);
+    swap.accounts.reflection_mint = None;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::ReflectionBelowMinOut)
+    );
+}
@@ -0,0 +1,80 @@
+use crate::fixture::{error, leg, Fixture, Swap, USER_INPUT_BALANCE};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// The default swap with a main leg that only takes 900_000 of its 990_000,
+/// leaving 90_000 in the vault.
+fn partial_fill(fixture: &Fixture) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.main_leg = Some(leg(route_data(900_000, 9_000), &[true, true, false]));
+    swap
+}
+
+#[test]
+fn leftover_returns_to_user_input_by_default() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&partial_fill(&fixture)).unwrap();
+
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE - 1_000_000 + 90_000
+    );
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn leftover_lands_in_the_refund_destination() {
+    let mut fixture = Fixture::new();
+    let refund = fixture.token_account(fixture.input_mint, fixture.user, 0);
+    let mut swap = partial_fill(&fixture);
+    swap.accounts.refund_destination = Some(refund);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&refund), 90_000);
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE - 1_000_000
+    );
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn refund_destination_for_another_mint_is_rejected() {
+    let mut fixture = Fixture::new();
+    let refund = fixture.token_account(fixture.main_mint, fixture.user, 0);
+    let mut swap = partial_fill(&fixture);
+    swap.accounts.refund_destination = Some(refund);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidRefundDestination)
+    );
+}
+
+#[test]
+fn refund_destination_owned_by_someone_else_is_rejected() {
+    let mut fixture = Fixture::new();
+    let refund = fixture.token_account(fixture.input_mint, Pubkey::new_unique(), 0);
+    let mut swap = partial_fill(&fixture);
+    swap.accounts.refund_destination = Some(refund);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidRefundDestination)
+    );
+}
+
+#[test]
+fn refund_destination_owned_by_an_allowed_caller_is_accepted() {
+    let mut fixture = Fixture::new();
+    let strategy = Pubkey::new_unique();
+    fixture
+        .update_config(UpdateConfigParams {
+            allowed_callers: Some(vec![strategy]),
+            ..Default::default()
+        })
+        .unwrap();
+    let refund = fixture.token_account(fixture.input_mint, strategy, 0);
+    let mut swap = partial_fill(&fixture);
+    swap.accounts.refund_destination = Some(refund);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&refund), 90_000);
+}
@@ -0,0 +1,91 @@
+use crate::fixture::{error, instruction, pda, signed, Fixture, USER_LAMPORTS};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+/// Registers `session_key` for the fixture's user and approves it on
+/// `user_input`, returning the session PDA.
+fn register(fixture: &mut Fixture, session_key: Pubkey, expires_at_slot: u64) -> Pubkey {
+    let session = pda(&[SESSION_SEED, fixture.user.as_ref(), session_key.as_ref()]);
+    let register = instruction(
+        &accounts::RegisterSession {
+            session,
+            user: fixture.user,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterSession {
+            session_key,
+            expires_at_slot,
+        },
+        &[],
+    );
+    fixture.ledger.process(&register).unwrap();
+    fixture
+        .ledger
+        .approve(fixture.user_input, session_key, u64::MAX);
+    fixture.ledger.fund(session_key, USER_LAMPORTS);
+    session
+}
+
+fn swap_with_session(fixture: &mut Fixture, session_key: Pubkey, session: Pubkey) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.user_signs = false;
+    swap.accounts.session_key = Some(session_key);
+    swap.accounts.session = Some(session);
+    let ix = signed(fixture.swap_instruction(&swap), &session_key);
+    fixture.ledger.process(&ix)
+}
+
+#[test]
+fn registered_session_key_signs_for_the_user() {
+    let mut fixture = Fixture::new();
+    let session_key = Pubkey::new_unique();
+    let expires = fixture.ledger.slot() + 100;
+    let session = register(&mut fixture, session_key, expires);
+
+    swap_with_session(&mut fixture, session_key, session).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+    assert_eq!(fixture.token_amount(&fixture.user_input), 9_000_000);
+}
+
+#[test]
+fn session_registered_for_another_key_is_rejected() {
+    let mut fixture = Fixture::new();
+    let registered_key = Pubkey::new_unique();
+    let expires = fixture.ledger.slot() + 100;
+    let session = register(&mut fixture, registered_key, expires);
+
+    let other_key = Pubkey::new_unique();
+    fixture
+        .ledger
+        .approve(fixture.user_input, other_key, u64::MAX);
+    fixture.ledger.fund(other_key, USER_LAMPORTS);
+    assert_eq!(
+        swap_with_session(&mut fixture, other_key, session),
+        error(RaceswapError::InvalidSessionKey)
+    );
+}
+
+#[test]
+fn expired_session_is_rejected() {
+    let mut fixture = Fixture::new();
+    let session_key = Pubkey::new_unique();
+    let expires = fixture.ledger.slot() + 10;
+    let session = register(&mut fixture, session_key, expires);
+
+    fixture.ledger.set_slot(expires);
+    assert_eq!(
+        swap_with_session(&mut fixture, session_key, session),
+        error(RaceswapError::SessionExpired)
+    );
+}
+
+#[test]
+fn unsigned_user_without_session_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.user_signs = false;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::UserSignatureRequired)
+    );
+}
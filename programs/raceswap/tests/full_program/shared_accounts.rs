@@ -0,0 +1,59 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+
+/// The default swap in shared-accounts mode: the vault and swap authority are
+/// passed once up front and both legs reference them by index, leaving only
+/// each leg's destination as its own account.
+fn shared_swap(fixture: &Fixture, main_out: u64, reflection_out: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, main_out, reflection_out);
+    swap.params.shared_accounts_len = 2;
+    swap.params.reflection_legs[0].instruction.shared_refs = vec![0, UNIQUE_ACCOUNT_REF];
+    swap.params.main_leg.as_mut().unwrap().shared_refs = vec![0, UNIQUE_ACCOUNT_REF, 1];
+    swap.remaining = vec![
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new_readonly(fixture.swap_authority, false),
+        AccountMeta::new(fixture.user_reflection, false),
+        AccountMeta::new(fixture.user_main, false),
+    ];
+    swap
+}
+
+#[test]
+fn shared_accounts_are_mapped_into_both_legs() {
+    let mut fixture = Fixture::new();
+    let swap = shared_swap(&fixture, 9_000, 50);
+    assert_eq!(
+        swap.remaining.len(),
+        fixture.swap(1_000_000, 9_000, 50).remaining.len() - 1
+    );
+
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn shared_index_past_the_shared_accounts_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = shared_swap(&fixture, 9_000, 50);
+    swap.params.main_leg.as_mut().unwrap().shared_refs = vec![0, UNIQUE_ACCOUNT_REF, 2];
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::AccountMismatch)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn shared_refs_shorter_than_the_leg_are_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = shared_swap(&fixture, 9_000, 50);
+    swap.params.main_leg.as_mut().unwrap().shared_refs = vec![0, UNIQUE_ACCOUNT_REF];
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MalformedParams)
+    );
+}
@@ -0,0 +1,112 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+fn require_min_out(fixture: &mut Fixture, default_slippage_bps: u16) {
+    fixture
+        .update_config(UpdateConfigParams {
+            default_slippage_bps: Some(default_slippage_bps),
+            allow_zero_min_out: Some(false),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+fn cap_slippage(fixture: &mut Fixture, max_slippage_bps: u16) {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_slippage_bps: Some(max_slippage_bps),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+#[test]
+fn omitted_min_out_applies_default_slippage_to_quote() {
+    let mut fixture = Fixture::new();
+    require_min_out(&mut fixture, 100);
+
+    // Floor is 1% below the 10_000 quote: 9_899 misses it, 9_900 meets it
+    let mut swap = fixture.swap(1_000_000, 9_899, 50);
+    swap.params.min_main_out = 0;
+    swap.params.quoted_out = 10_000;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainBelowMinOut)
+    );
+
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.min_main_out = 0;
+    swap.params.quoted_out = 10_000;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_900);
+}
+
+#[test]
+fn omitted_min_out_without_quote_is_rejected() {
+    let mut fixture = Fixture::new();
+    require_min_out(&mut fixture, 100);
+
+    let mut swap = fixture.swap(1_000_000, 9_900, 50);
+    swap.params.min_main_out = 0;
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MissingMinOut));
+}
+
+#[test]
+fn zero_min_out_passes_when_allowed() {
+    let mut fixture = Fixture::new();
+
+    let mut swap = fixture.swap(1_000_000, 1, 50);
+    swap.params.min_main_out = 0;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 1);
+}
+
+#[test]
+fn min_out_at_or_above_quote_is_rejected() {
+    let mut fixture = Fixture::new();
+
+    for min_main_out in [10_000, 10_001] {
+        let mut swap = fixture.swap(1_000_000, 10_001, 50);
+        swap.params.min_main_out = min_main_out;
+        swap.params.quoted_out = 10_000;
+        assert_eq!(
+            fixture.execute(&swap),
+            error(RaceswapError::MinOutAboveQuote)
+        );
+    }
+
+    let mut swap = fixture.swap(1_000_000, 10_001, 50);
+    swap.params.min_main_out = 9_999;
+    swap.params.quoted_out = 10_000;
+    fixture.execute(&swap).unwrap();
+}
+
+#[test]
+fn slippage_looser_than_the_ceiling_is_rejected() {
+    let mut fixture = Fixture::new();
+    cap_slippage(&mut fixture, 100);
+
+    // 1% below the 10_000 quote is 9_900: 9_899 is looser than allowed
+    let mut swap = fixture.swap(1_000_000, 9_950, 50);
+    swap.params.min_main_out = 9_899;
+    swap.params.quoted_out = 10_000;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::SlippageTooLoose)
+    );
+
+    swap.params.min_main_out = 9_900;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_950);
+}
+
+#[test]
+fn ceiling_requires_a_quote() {
+    let mut fixture = Fixture::new();
+    cap_slippage(&mut fixture, 100);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_950, 50)),
+        error(RaceswapError::SlippageTooLoose)
+    );
+}
@@ -0,0 +1,47 @@
+use crate::fixture::{error, Fixture, USER_INPUT_BALANCE};
+use crate::*;
+
+/// A fixture whose user holds only `lamports` of SOL.
+fn user_with(lamports: u64) -> Fixture {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    fixture
+        .ledger
+        .set_account(user, lamports, Vec::new(), system_program::ID);
+    fixture
+}
+
+#[test]
+fn short_balance_fails_before_anything_moves() {
+    let mut fixture = user_with(1_999);
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::InsufficientSolForFee)
+    );
+    assert!(fixture
+        .ledger
+        .logs()
+        .iter()
+        .any(|log| log.contains("required 2000 lamports, available 1999")));
+    assert_eq!(fixture.ledger.lamports(&fixture.user), 1_999);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_before
+    );
+    assert_eq!(
+        fixture.token_amount(&fixture.user_input),
+        USER_INPUT_BALANCE
+    );
+}
+
+#[test]
+fn balance_covering_the_fee_exactly_is_enough() {
+    let mut fixture = user_with(2_000);
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    assert_eq!(fixture.ledger.lamports(&fixture.user), 0);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
@@ -0,0 +1,83 @@
+use crate::fixture::Fixture;
+use crate::*;
+
+/// Enables the streak: each swap within 10 slots of the last adds 0.5x to
+/// the reflection rate, up to 2x.
+fn enable_streak(fixture: &mut Fixture) -> Pubkey {
+    fixture
+        .update_config(UpdateConfigParams {
+            streak_window_slots: Some(10),
+            streak_bonus_bps: Some(5_000),
+            max_streak_multiplier_bps: Some(20_000),
+            ..Default::default()
+        })
+        .unwrap();
+    fixture.ledger.set_slot(100);
+    fixture.init_user_stats()
+}
+
+/// Runs a swap with the user's stats `slots` after the current slot, its legs
+/// sized for `reflection_bps`, returning the rate the program logged for it.
+fn streak_swap(
+    fixture: &mut Fixture,
+    user_stats: Pubkey,
+    slots: u64,
+    reflection_bps: u16,
+) -> String {
+    let slot = fixture.ledger.slot();
+    fixture.ledger.set_slot(slot + slots);
+    let mut swap = fixture.swap_at(1_000_000, 9_000, 50, reflection_bps);
+    swap.accounts.user_stats = Some(user_stats);
+    fixture.execute(&swap).unwrap();
+    let logs = fixture.ledger.logs();
+    let streak = logs
+        .iter()
+        .find(|log| log.starts_with("Reflection streak"))
+        .expect("streak log");
+    streak.rsplit("-> ").next().unwrap().to_string()
+}
+
+#[test]
+fn consecutive_swaps_raise_reflection_up_to_the_cap() {
+    let mut fixture = Fixture::new();
+    let user_stats = enable_streak(&mut fixture);
+
+    // 1x, 1.5x, 2x, then capped at 2x
+    for bps in [100, 150, 200, 200] {
+        assert_eq!(
+            streak_swap(&mut fixture, user_stats, 5, bps),
+            format!("{bps} bps")
+        );
+    }
+    let stats = fixture.ledger.anchor_account::<UserStats>(&user_stats);
+    assert_eq!(stats.streak, 3);
+}
+
+#[test]
+fn streak_resets_after_the_window() {
+    let mut fixture = Fixture::new();
+    let user_stats = enable_streak(&mut fixture);
+    assert_eq!(streak_swap(&mut fixture, user_stats, 0, 100), "100 bps");
+    assert_eq!(streak_swap(&mut fixture, user_stats, 10, 150), "150 bps");
+
+    assert_eq!(streak_swap(&mut fixture, user_stats, 11, 100), "100 bps");
+    let stats = fixture.ledger.anchor_account::<UserStats>(&user_stats);
+    assert_eq!(stats.streak, 0);
+    assert_eq!(stats.last_swap_slot, 121);
+}
+
+#[test]
+fn swaps_without_stats_have_no_streak() {
+    let mut fixture = Fixture::new();
+    enable_streak(&mut fixture);
+    for _ in 0..2 {
+        fixture
+            .execute(&fixture.swap(1_000_000, 9_000, 50))
+            .unwrap();
+        assert!(!fixture
+            .ledger
+            .logs()
+            .iter()
+            .any(|log| log.starts_with("Reflection streak")));
+    }
+}
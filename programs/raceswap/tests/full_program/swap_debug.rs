@@ -0,0 +1,35 @@
+use crate::fixture::Fixture;
+use crate::*;
+
+#[test]
+fn debug_event_reports_raw_balances_when_flagged() {
+    let mut fixture = Fixture::new();
+    let user_main = fixture.user_main;
+    fixture
+        .ledger
+        .add_token_account(user_main, fixture.main_mint, fixture.user, 7);
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.debug = true;
+    fixture.execute(&swap).unwrap();
+
+    let events = fixture.ledger.events::<SwapDebug>();
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].main_before, events[0].main_after), (7, 9_007));
+    assert_eq!(
+        (events[0].reflection_before, events[0].reflection_after),
+        (0, 50)
+    );
+    assert_eq!((events[0].vault_before, events[0].vault_after), (0, 0));
+}
+
+#[test]
+fn debug_event_is_not_emitted_by_default() {
+    let mut fixture = Fixture::new();
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+
+    assert!(fixture.ledger.events::<SwapDebug>().is_empty());
+    assert_eq!(fixture.ledger.events::<SwapRate>().len(), 1);
+}
@@ -0,0 +1,19 @@
+use crate::fixture::Fixture;
+use crate::*;
+
+#[test]
+fn event_records_the_execution_slot() {
+    let mut fixture = Fixture::new();
+    for slot in [48_213, 48_214] {
+        fixture.ledger.set_slot(slot);
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+
+        let events = fixture.ledger.events::<SwapExecuted>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].slot, slot);
+        assert_eq!(events[0].user, fixture.user);
+        assert_eq!(events[0].total_in, 1_000_000);
+        assert_eq!(events[0].main_amount, 9_000);
+        assert_eq!(events[0].reflection_amount, 50);
+    }
+}
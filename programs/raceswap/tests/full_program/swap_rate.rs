@@ -0,0 +1,41 @@
+use crate::fixture::Fixture;
+use crate::*;
+
+#[test]
+fn swap_emits_the_realized_rate() {
+    let mut fixture = Fixture::new();
+    // 990_000 net input buys 1_980_000: two output units per input unit
+    let swap = fixture.swap(1_000_000, 1_980_000, 50);
+    fixture.execute(&swap).unwrap();
+
+    let events = fixture.ledger.events::<SwapRate>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].main_input_amount, 990_000);
+    assert_eq!(events[0].main_amount, 1_980_000);
+    assert_eq!(events[0].rate, 2 * RATE_SCALE as u64);
+}
+
+#[test]
+fn rate_is_adjusted_for_output_decimals() {
+    let mut fixture = Fixture::new();
+    let output_mint = Pubkey::new_unique();
+    fixture.ledger.add_mint(output_mint, 9);
+    let destination = fixture.token_account(output_mint, fixture.user, 0);
+
+    // 0.99 input (6 decimals) buys 1_980 output (9 decimals)
+    let mut swap = fixture.swap(1_000_000, 1_980_000_000_000, 50);
+    swap.params.main_output_mint = output_mint;
+    swap.accounts.main_output_mint = Some(output_mint);
+    swap.accounts.user_main_destination = destination;
+    swap.remaining[3].pubkey = destination;
+    fixture.execute(&swap).unwrap();
+
+    let event = &fixture.ledger.events::<SwapRate>()[0];
+    assert!(event.decimals_adjusted);
+    assert_eq!(event.rate, 2_000 * RATE_SCALE as u64);
+}
+
+#[test]
+fn zero_net_input_yields_a_zero_rate() {
+    assert_eq!(realized_rate(1_000, 0, 6, 6).unwrap(), 0);
+}
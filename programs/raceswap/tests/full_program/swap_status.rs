@@ -0,0 +1,23 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+#[test]
+fn swap_returns_the_shared_status_prefix() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+
+    let (program_id, data) = fixture.ledger.return_data().unwrap();
+    assert_eq!(program_id, ID);
+    let status = SwapStatus::deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(status, SwapStatus::ok(9_000, 2_000));
+}
+
+#[test]
+fn reverted_swap_leaves_no_return_data() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.min_main_out = 9_001;
+
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::MainBelowMinOut));
+    assert_eq!(fixture.ledger.return_data(), None);
+}
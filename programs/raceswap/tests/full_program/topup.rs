@@ -0,0 +1,75 @@
+use crate::fixture::{error, leg, Fixture, Swap};
+use crate::*;
+use raceswap_harness::route_data;
+
+/// The default swap with a main leg landing 10 short of its 9_000 floor and
+/// a top-up route drawing `drawn` reflection for `gained` main.
+fn short_swap(fixture: &Fixture, drawn: u64, gained: u64) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.main_leg = Some(leg(route_data(990_000, 8_990), &[true, true, false]));
+    swap.params.topup_from_reflection = true;
+    swap.params.topup_leg = Some(leg(route_data(drawn, gained), &[true, true]));
+    swap.remaining.extend([
+        AccountMeta::new(fixture.user_reflection, false),
+        AccountMeta::new(fixture.user_main, false),
+    ]);
+    swap
+}
+
+#[test]
+fn shortfall_is_covered_from_reflection() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&short_swap(&fixture, 20, 10)).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 30);
+}
+
+#[test]
+fn top_up_is_skipped_when_main_meets_the_floor() {
+    let mut fixture = Fixture::new();
+    let mut swap = short_swap(&fixture, 20, 10);
+    swap.params.main_leg = Some(leg(route_data(990_000, 9_000), &[true, true, false]));
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 50);
+}
+
+#[test]
+fn shortfall_reverts_with_the_flag_off() {
+    let mut fixture = Fixture::new();
+    let mut swap = short_swap(&fixture, 20, 10);
+    swap.params.topup_from_reflection = false;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MainBelowMinOut)
+    );
+}
+
+#[test]
+fn top_up_never_draws_more_than_this_swap_delivered() {
+    let mut fixture = Fixture::new();
+    // Reflection tokens the user already held are not available to the top-up
+    let user = fixture.user;
+    let (user_reflection, reflection_mint) = (fixture.user_reflection, fixture.reflection_mint);
+    fixture
+        .ledger
+        .add_token_account(user_reflection, reflection_mint, user, 1_000);
+
+    assert_eq!(
+        fixture.execute(&short_swap(&fixture, 60, 10)),
+        error(RaceswapError::ReflectionOverdrawn)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_reflection), 1_000);
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn top_up_falling_short_still_reverts() {
+    let mut fixture = Fixture::new();
+    assert_eq!(
+        fixture.execute(&short_swap(&fixture, 20, 5)),
+        error(RaceswapError::MainBelowMinOut)
+    );
+}
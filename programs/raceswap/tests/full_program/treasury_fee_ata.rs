@@ -0,0 +1,123 @@
+use crate::fixture::{error, leg, pda, Fixture, Swap, TREASURY};
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use raceswap_harness::route_data;
+
+/// The treasury_authority PDA and its input-mint ATA, created empty.
+fn treasury_ata(fixture: &mut Fixture) -> (Pubkey, Pubkey) {
+    let treasury_authority = pda(&[TREASURY_AUTHORITY_SEED, fixture.config.as_ref()]);
+    let ata = get_associated_token_address_with_program_id(
+        &treasury_authority,
+        &fixture.input_mint,
+        &TOKEN_PROGRAM_ID,
+    );
+    let input_mint = fixture.input_mint;
+    fixture
+        .ledger
+        .add_token_account(ata, input_mint, treasury_authority, 0);
+    (treasury_authority, ata)
+}
+
+/// The default swap with its 0.2% treasury fee carved from the input into
+/// `treasury_token_account`.
+fn in_token_fee_swap(fixture: &Fixture, treasury_token_account: Pubkey) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.fee_in_input_token = true;
+    swap.params.main_leg = Some(leg(route_data(988_000, 9_000), &[true, true, false]));
+    swap.accounts.treasury_token_account = Some(treasury_token_account);
+    swap
+}
+
+fn sweep(
+    fixture: &mut Fixture,
+    authority: Pubkey,
+    treasury_authority: Pubkey,
+    ata: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    fixture.admin(
+        &accounts::SweepFees {
+            config: fixture.config,
+            authority,
+            treasury_authority,
+            treasury_token_account: ata,
+            destination,
+            mint: fixture.input_mint,
+            token_program: TOKEN_PROGRAM_ID,
+        },
+        instruction::SweepFees { amount },
+        &[],
+        &[],
+    )
+}
+
+#[test]
+fn in_token_fees_accumulate_in_the_pda_ata() {
+    let mut fixture = Fixture::new();
+    let (_, ata) = treasury_ata(&mut fixture);
+    let fee_destination_before = fixture.ledger.lamports(&fixture.fee_destination);
+
+    fixture.execute(&in_token_fee_swap(&fixture, ata)).unwrap();
+    fixture.execute(&in_token_fee_swap(&fixture, ata)).unwrap();
+    assert_eq!(fixture.token_amount(&ata), 4_000);
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        fee_destination_before
+    );
+}
+
+#[test]
+fn in_token_fee_to_another_account_is_rejected() {
+    let mut fixture = Fixture::new();
+    let wallet_account = fixture.token_account(fixture.input_mint, TREASURY, 0);
+
+    assert_eq!(
+        fixture.execute(&in_token_fee_swap(&fixture, wallet_account)),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+    assert_eq!(fixture.token_amount(&wallet_account), 0);
+}
+
+#[test]
+fn accumulated_fees_sweep_to_the_treasury_wallet() {
+    let mut fixture = Fixture::new();
+    let (treasury_authority, ata) = treasury_ata(&mut fixture);
+    fixture.execute(&in_token_fee_swap(&fixture, ata)).unwrap();
+    let destination = fixture.token_account(fixture.input_mint, TREASURY, 0);
+
+    let authority = fixture.authority;
+    sweep(
+        &mut fixture,
+        authority,
+        treasury_authority,
+        ata,
+        destination,
+        1_500,
+    )
+    .unwrap();
+    assert_eq!(fixture.token_amount(&ata), 500);
+    assert_eq!(fixture.token_amount(&destination), 1_500);
+}
+
+#[test]
+fn sweep_by_non_authority_is_rejected() {
+    let mut fixture = Fixture::new();
+    let (treasury_authority, ata) = treasury_ata(&mut fixture);
+    fixture.execute(&in_token_fee_swap(&fixture, ata)).unwrap();
+    let destination = fixture.token_account(fixture.input_mint, TREASURY, 0);
+
+    let user = fixture.user;
+    assert_eq!(
+        sweep(
+            &mut fixture,
+            user,
+            treasury_authority,
+            ata,
+            destination,
+            1_500
+        ),
+        error(RaceswapError::Unauthorized)
+    );
+    assert_eq!(fixture.token_amount(&ata), 2_000);
+}
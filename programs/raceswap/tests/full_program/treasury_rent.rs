@@ -0,0 +1,57 @@
+use crate::fixture::Fixture;
+use crate::*;
+use raceswap_common::FeeSplitError;
+use raceswap_harness::anchor_error;
+
+/// Points the SOL fee at a fresh, empty account.
+fn empty_fee_destination(fixture: &mut Fixture) -> Pubkey {
+    let destination = Pubkey::new_unique();
+    fixture.ledger.fund(destination, 0);
+    destination
+}
+
+#[test]
+fn fee_leaving_the_destination_below_rent_exemption_is_rejected() {
+    let mut fixture = Fixture::new();
+    let destination = empty_fee_destination(&mut fixture);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.treasury_fee_destination = destination;
+
+    // 2_000 lamports would strand as dust in an account that can't be closed
+    assert_eq!(
+        fixture.execute(&swap),
+        Err(anchor_error(FeeSplitError::TreasuryNotRentExempt))
+    );
+    assert_eq!(fixture.ledger.lamports(&destination), 0);
+}
+
+#[test]
+fn fee_covering_rent_exemption_is_accepted() {
+    let mut fixture = Fixture::new();
+    let destination = empty_fee_destination(&mut fixture);
+    let (user, input, mint) = (fixture.user, fixture.user_input, fixture.input_mint);
+    fixture
+        .ledger
+        .add_token_account(input, mint, user, 500_000_000);
+    let mut swap = fixture.swap(500_000_000, 9_000, 50);
+    swap.accounts.treasury_fee_destination = destination;
+    fixture.execute(&swap).unwrap();
+
+    // 0.2% of 500_000_000 clears the minimum for an empty account
+    let fee = 1_000_000;
+    assert!(fee >= Rent::default().minimum_balance(0));
+    assert_eq!(fixture.ledger.lamports(&destination), fee);
+}
+
+#[test]
+fn fee_into_a_rent_exempt_destination_is_accepted() {
+    let mut fixture = Fixture::new();
+    let before = fixture.ledger.lamports(&fixture.fee_destination);
+    fixture
+        .execute(&fixture.swap(1_000_000, 9_000, 50))
+        .unwrap();
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination),
+        before + 2_000
+    );
+}
@@ -0,0 +1,63 @@
+use crate::fixture::{error, Fixture, Swap};
+use crate::*;
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
+
+/// The default swap delivering 9_000 into a WSOL account that already holds
+/// `balance`, asking for it to be unwrapped. Returns the swap and the account.
+fn wsol_swap(fixture: &mut Fixture, balance: u64) -> (Swap, Pubkey) {
+    fixture.ledger.add_mint(NATIVE_MINT, 9);
+    let wsol = fixture.token_account(NATIVE_MINT, fixture.user, balance);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.main_output_mint = NATIVE_MINT;
+    swap.params.unwrap_output = true;
+    swap.accounts.user_main_destination = wsol;
+    swap.remaining[3].pubkey = wsol;
+    (swap, wsol)
+}
+
+#[test]
+fn wsol_output_lands_as_native_sol() {
+    let mut fixture = Fixture::new();
+    let (swap, wsol) = wsol_swap(&mut fixture, 0);
+    let rent = fixture.ledger.lamports(&wsol);
+    let user_before = fixture.ledger.lamports(&fixture.user);
+    fixture.execute(&swap).unwrap();
+
+    // The output and the account's rent come back, less the 2_000 fee
+    assert!(!fixture.ledger.exists(&wsol));
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.user),
+        user_before + 9_000 + rent - 2_000
+    );
+}
+
+#[test]
+fn wsol_account_with_a_prior_balance_is_not_unwrapped() {
+    let mut fixture = Fixture::new();
+    let (swap, wsol) = wsol_swap(&mut fixture, 1);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidUnwrapOutput)
+    );
+    assert_eq!(fixture.token_amount(&wsol), 1);
+}
+
+#[test]
+fn non_wsol_output_cannot_be_unwrapped() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.unwrap_output = true;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidUnwrapOutput)
+    );
+}
+
+#[test]
+fn wsol_output_is_kept_without_the_flag() {
+    let mut fixture = Fixture::new();
+    let (mut swap, wsol) = wsol_swap(&mut fixture, 0);
+    swap.params.unwrap_output = false;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&wsol), 9_000);
+}
@@ -0,0 +1,106 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+const FEED_ID: [u8; 32] = [7; 32];
+const NOW: i64 = 1_700_000_000;
+
+/// A verified Pyth `PriceUpdateV2` for `feed_id` at `price`e-8, published at
+/// `publish_time`.
+fn price_update(feed_id: [u8; 32], price: i64, publish_time: i64) -> Vec<u8> {
+    let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&[0; 32]); // write_authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&feed_id);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // conf
+    data.extend_from_slice(&(-8i32).to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes()); // prev_publish_time
+    data
+}
+
+/// A fixture charging a flat 10 cents, with a SOL/USD account at `data`.
+fn fixed_fee_fixture(data: Vec<u8>) -> (Fixture, Pubkey) {
+    let mut fixture = Fixture::new();
+    fixture
+        .update_config(UpdateConfigParams {
+            fixed_fee_usd_cents: Some(10),
+            sol_usd_feed_id: Some(FEED_ID),
+            ..Default::default()
+        })
+        .unwrap();
+    fixture.ledger.set_unix_timestamp(NOW);
+    let price = Pubkey::new_unique();
+    fixture
+        .ledger
+        .set_account(price, 1_000_000, data, PYTH_RECEIVER_PROGRAM_ID);
+    (fixture, price)
+}
+
+#[test]
+fn fixed_usd_fee_is_charged_in_lamports_at_the_oracle_price() {
+    // $150.00 per SOL: 10 cents is 666_666.6 lamports
+    let (mut fixture, price) = fixed_fee_fixture(price_update(FEED_ID, 15_000_000_000, NOW - 5));
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.sol_usd_price = Some(price);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination) - fee_before,
+        666_666
+    );
+}
+
+#[test]
+fn fee_does_not_depend_on_the_swap_size() {
+    let (mut fixture, price) = fixed_fee_fixture(price_update(FEED_ID, 15_000_000_000, NOW));
+    let fee_before = fixture.ledger.lamports(&fixture.fee_destination);
+    let mut swap = fixture.swap(5_000_000, 9_000, 50);
+    swap.accounts.sol_usd_price = Some(price);
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(
+        fixture.ledger.lamports(&fixture.fee_destination) - fee_before,
+        666_666
+    );
+}
+
+#[test]
+fn stale_price_is_rejected() {
+    let (mut fixture, price) = fixed_fee_fixture(price_update(
+        FEED_ID,
+        15_000_000_000,
+        NOW - DEFAULT_MAX_PRICE_AGE_SECS as i64 - 1,
+    ));
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.sol_usd_price = Some(price);
+    assert_eq!(fixture.execute(&swap), error(RaceswapError::StalePrice));
+}
+
+#[test]
+fn missing_or_foreign_price_accounts_are_rejected() {
+    let (mut fixture, price) = fixed_fee_fixture(price_update([8; 32], 15_000_000_000, NOW));
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::InvalidPriceAccount)
+    );
+
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.sol_usd_price = Some(price);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidPriceAccount)
+    );
+}
+
+#[test]
+fn non_positive_price_is_rejected() {
+    let (mut fixture, price) = fixed_fee_fixture(price_update(FEED_ID, 0, NOW));
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.accounts.sol_usd_price = Some(price);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::InvalidPriceAccount)
+    );
+}
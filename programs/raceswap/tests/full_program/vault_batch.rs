@@ -0,0 +1,100 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+fn new_mints(fixture: &mut Fixture, count: usize) -> Vec<Pubkey> {
+    (0..count)
+        .map(|_| {
+            let mint = Pubkey::new_unique();
+            fixture.ledger.add_mint(mint, 6);
+            mint
+        })
+        .collect()
+}
+
+#[test]
+fn batch_creates_a_vault_per_mint() {
+    let mut fixture = Fixture::new();
+    let mints = new_mints(&mut fixture, 3);
+
+    fixture.create_vaults(&mints, &[]).unwrap();
+    for mint in &mints {
+        let vault = fixture.ledger.token_account(&fixture.vault_for(mint));
+        assert_eq!(vault.mint, *mint);
+        assert_eq!(vault.owner, fixture.swap_authority);
+        assert_eq!(vault.amount, 0);
+    }
+}
+
+#[test]
+fn existing_vaults_are_skipped() {
+    let mut fixture = Fixture::new();
+    let mut mints = new_mints(&mut fixture, 2);
+    // The input vault already exists and holds a balance that must survive
+    let input_mint = fixture.input_mint;
+    let vault = fixture.vault;
+    let swap_authority = fixture.swap_authority;
+    fixture
+        .ledger
+        .add_token_account(vault, input_mint, swap_authority, 42);
+    mints.push(input_mint);
+
+    fixture.create_vaults(&mints, &[]).unwrap();
+    fixture.create_vaults(&mints, &[]).unwrap();
+    assert_eq!(fixture.token_amount(&vault), 42);
+    for mint in &mints {
+        assert!(fixture.ledger.exists(&fixture.vault_for(mint)));
+    }
+}
+
+#[test]
+fn batch_over_the_bound_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mints = new_mints(&mut fixture, MAX_VAULT_BATCH + 1);
+
+    assert_eq!(
+        fixture.create_vaults(&mints, &[]),
+        error(RaceswapError::InvalidVaultBatch)
+    );
+    assert!(!fixture.ledger.exists(&fixture.vault_for(&mints[0])));
+
+    fixture
+        .create_vaults(&mints[..MAX_VAULT_BATCH], &[])
+        .unwrap();
+}
+
+#[test]
+fn vault_not_at_the_derived_address_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mints = new_mints(&mut fixture, 2);
+    let mut pairs = vec![
+        AccountMeta::new_readonly(mints[0], false),
+        AccountMeta::new(fixture.vault_for(&mints[1]), false),
+    ];
+    let accounts = accounts::CreateInputVaultsBatch {
+        config: fixture.config,
+        authority: fixture.authority,
+        swap_authority: fixture.swap_authority,
+        token_program: TOKEN_PROGRAM_ID,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+    };
+
+    assert_eq!(
+        fixture.admin(
+            &accounts,
+            instruction::CreateInputVaultsBatch {},
+            &[],
+            &pairs
+        ),
+        error(RaceswapError::InvalidVaultBatch)
+    );
+    pairs[1].pubkey = fixture.vault_for(&mints[0]);
+    fixture
+        .admin(
+            &accounts,
+            instruction::CreateInputVaultsBatch {},
+            &[],
+            &pairs,
+        )
+        .unwrap();
+}
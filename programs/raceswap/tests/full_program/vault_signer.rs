@@ -0,0 +1,44 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+use raceswap_harness::mock_route;
+use std::rc::Rc;
+
+/// Replaces the Jupiter mock with a route that, like one pulling tokens out
+/// of the PDA-owned vault, needs the swap authority (a leg's third account)
+/// to sign.
+fn add_authority_route(fixture: &mut Fixture) {
+    fixture.ledger.add_program(
+        JUPITER_PROGRAM_ID,
+        Rc::new(|program_id, accounts, data| {
+            if accounts
+                .get(2)
+                .is_some_and(|authority| !authority.is_signer)
+            {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            mock_route(program_id, accounts, data)
+        }),
+    );
+}
+
+#[test]
+fn vault_sourced_leg_is_signed_by_the_swap_authority() {
+    let mut fixture = Fixture::new();
+    add_authority_route(&mut fixture);
+    let mut swap = fixture.swap(1_000_000, 9_000, 50);
+    swap.params.vault_authority_signs = true;
+    fixture.execute(&swap).unwrap();
+
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+    assert_eq!(fixture.token_amount(&fixture.vault), 0);
+}
+
+#[test]
+fn unsigned_leg_cannot_draw_on_the_vault_authority() {
+    let mut fixture = Fixture::new();
+    add_authority_route(&mut fixture);
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::SwapCpiFailed)
+    );
+}
@@ -0,0 +1,46 @@
+use crate::fixture::{error, Fixture};
+use crate::*;
+
+fn cap_writable_accounts(fixture: &mut Fixture, max_writable_accounts: u8) {
+    fixture
+        .update_config(UpdateConfigParams {
+            max_writable_accounts: Some(max_writable_accounts),
+            ..Default::default()
+        })
+        .unwrap();
+}
+
+// The default swap writes to the config, user, user input, both destinations,
+// treasury wallet and fee destination, and the vault. Its legs reuse the vault
+// and destinations, which count once.
+const DEFAULT_SWAP_WRITABLE: u8 = 8;
+
+#[test]
+fn writable_accounts_at_the_cap_pass() {
+    let mut fixture = Fixture::new();
+    cap_writable_accounts(&mut fixture, DEFAULT_SWAP_WRITABLE);
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
+
+#[test]
+fn writable_accounts_past_the_cap_are_rejected() {
+    let mut fixture = Fixture::new();
+    cap_writable_accounts(&mut fixture, DEFAULT_SWAP_WRITABLE - 1);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 9_000, 50)),
+        error(RaceswapError::TooManyWritableAccounts)
+    );
+    assert_eq!(fixture.token_amount(&fixture.user_main), 0);
+}
+
+#[test]
+fn zero_cap_disables_the_check() {
+    let mut fixture = Fixture::new();
+    cap_writable_accounts(&mut fixture, 0);
+
+    fixture.execute(&fixture.swap(1_000_000, 9_000, 50)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.user_main), 9_000);
+}
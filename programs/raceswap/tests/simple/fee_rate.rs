@@ -0,0 +1,61 @@
+use crate::fixture::{error, Fixture, TREASURY};
+use crate::RaceswapError;
+
+/// Lamports the treasury receives for a 1_000_000 swap.
+fn treasury_fee(fixture: &mut Fixture) -> u64 {
+    let before = fixture.ledger.lamports(&TREASURY);
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    fixture.ledger.lamports(&TREASURY) - before
+}
+
+#[test]
+fn fee_defaults_to_20_bps_without_a_config() {
+    let mut fixture = Fixture::new();
+    assert_eq!(treasury_fee(&mut fixture), 2_000);
+}
+
+#[test]
+fn fee_follows_the_configured_rate() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    fixture
+        .initialize_treasury_config(authority, TREASURY, 10)
+        .unwrap();
+    assert_eq!(treasury_fee(&mut fixture), 1_000);
+
+    fixture.set_treasury_fee_bps(authority, 0).unwrap();
+    assert_eq!(treasury_fee(&mut fixture), 0);
+}
+
+#[test]
+fn only_the_authority_can_change_the_rate() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    fixture
+        .initialize_treasury_config(authority, TREASURY, 20)
+        .unwrap();
+
+    let user = fixture.user;
+    assert_eq!(
+        fixture.set_treasury_fee_bps(user, 0),
+        error(RaceswapError::Unauthorized)
+    );
+    assert_eq!(treasury_fee(&mut fixture), 2_000);
+}
+
+#[test]
+fn rate_above_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    assert_eq!(
+        fixture.initialize_treasury_config(authority, TREASURY, 1_001),
+        error(RaceswapError::InvalidFeeConfig)
+    );
+    fixture
+        .initialize_treasury_config(authority, TREASURY, 1_000)
+        .unwrap();
+    assert_eq!(
+        fixture.set_treasury_fee_bps(authority, 1_001),
+        error(RaceswapError::InvalidFeeConfig)
+    );
+}
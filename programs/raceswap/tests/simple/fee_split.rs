@@ -0,0 +1,106 @@
+use crate::fixture::{error, Fixture, Swap, TREASURY};
+use crate::RaceswapError;
+use anchor_lang::prelude::*;
+use raceswap_common::{FeeShare, FeeSplitError};
+
+fn shares(shares: &[(Pubkey, u16)]) -> Vec<FeeShare> {
+    shares
+        .iter()
+        .map(|&(recipient, weight)| FeeShare { recipient, weight })
+        .collect()
+}
+
+/// A funded partner wallet besides the treasury.
+fn partner(fixture: &mut Fixture) -> Pubkey {
+    let partner = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(partner, Rent::default().minimum_balance(0));
+    partner
+}
+
+/// A fixture whose treasury config pays the built-in treasury.
+fn configured() -> Fixture {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    fixture
+        .initialize_treasury_config(authority, TREASURY, 20)
+        .unwrap();
+    fixture
+}
+
+/// A 1_000_000 swap (2_000 lamport fee) passing `recipients` ahead of the route.
+fn split_swap(fixture: &Fixture, recipients: &[Pubkey]) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    let mut remaining: Vec<AccountMeta> = recipients
+        .iter()
+        .map(|recipient| AccountMeta::new(*recipient, false))
+        .collect();
+    remaining.append(&mut swap.remaining);
+    swap.remaining = remaining;
+    swap
+}
+
+#[test]
+fn fee_is_split_by_the_configured_shares() {
+    let mut fixture = configured();
+    let partner = partner(&mut fixture);
+    let authority = fixture.authority;
+    fixture
+        .set_fee_shares(authority, shares(&[(TREASURY, 7_000), (partner, 3_000)]))
+        .unwrap();
+    let before = [TREASURY, partner].map(|key| fixture.ledger.lamports(&key));
+
+    fixture.execute(&split_swap(&fixture, &[TREASURY, partner])).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), before[0] + 1_400);
+    assert_eq!(fixture.ledger.lamports(&partner), before[1] + 600);
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn caller_cannot_redirect_a_configured_share() {
+    let mut fixture = configured();
+    let partner = partner(&mut fixture);
+    let authority = fixture.authority;
+    fixture
+        .set_fee_shares(authority, shares(&[(TREASURY, 7_000), (partner, 3_000)]))
+        .unwrap();
+
+    let user = fixture.user;
+    assert_eq!(
+        fixture.execute(&split_swap(&fixture, &[TREASURY, user])),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+}
+
+#[test]
+fn caller_accounts_take_no_share_without_a_split() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    let before = [TREASURY, user].map(|key| fixture.ledger.lamports(&key));
+
+    fixture.execute(&split_swap(&fixture, &[user])).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), before[0] + 2_000);
+    assert_eq!(fixture.ledger.lamports(&user), before[1] - 2_000);
+}
+
+#[test]
+fn split_must_give_the_treasury_a_share() {
+    let mut fixture = configured();
+    let partner = partner(&mut fixture);
+    let authority = fixture.authority;
+    assert_eq!(
+        fixture.set_fee_shares(authority, shares(&[(TREASURY, 0), (partner, 10_000)])),
+        error(FeeSplitError::InvalidFeeWeights)
+    );
+}
+
+#[test]
+fn only_the_treasury_authority_sets_the_split() {
+    let mut fixture = configured();
+    let user = fixture.user;
+    assert_eq!(
+        fixture.set_fee_shares(user, shares(&[(TREASURY, 5_000), (user, 5_000)])),
+        error(RaceswapError::Unauthorized)
+    );
+}
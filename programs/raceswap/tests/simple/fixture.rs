@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+
+//! A deployed program whose upgrade authority is `authority`, with a funded
+//! user holding an input token account and an empty destination, plus
+//! builders for the instructions the tests send.
+
+use crate::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use raceswap_common::FeeShare;
+use raceswap_harness::{mock_route, route_data, Ledger};
+use std::rc::Rc;
+
+pub const TREASURY: Pubkey = DEFAULT_TREASURY;
+pub const USER_LAMPORTS: u64 = 10_000_000_000;
+pub const SOURCE_BALANCE: u64 = 10_000_000;
+
+pub struct Fixture {
+    pub ledger: Ledger,
+    pub authority: Pubkey,
+    pub user: Pubkey,
+    pub program_data: Pubkey,
+    pub treasury_config: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+}
+
+/// One `execute_swap` call: params, named accounts and remaining accounts.
+pub struct Swap {
+    pub params: ExecuteSwapParams,
+    pub accounts: accounts::ExecuteSwap,
+    pub remaining: Vec<AccountMeta>,
+}
+
+pub fn error(code: impl Into<u32>) -> ProgramResult {
+    Err(raceswap_harness::anchor_error(code))
+}
+
+pub fn instruction(
+    accounts: &impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: &[AccountMeta],
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend_from_slice(remaining);
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let mut ledger = Ledger::new(ID, entry);
+        ledger.add_program(JUPITER_PROGRAM_ID, Rc::new(mock_route));
+
+        let authority = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let program_data = ledger.set_upgrade_authority(ID, Some(authority));
+        ledger.fund(authority, USER_LAMPORTS);
+        ledger.fund(user, USER_LAMPORTS);
+        ledger.fund(TREASURY, Rent::default().minimum_balance(0));
+        let input_mint = Pubkey::new_unique();
+        let output_mint = Pubkey::new_unique();
+        ledger.add_mint(input_mint, 6);
+        ledger.add_mint(output_mint, 6);
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        ledger.add_token_account(source, input_mint, user, SOURCE_BALANCE);
+        ledger.add_token_account(destination, output_mint, user, 0);
+
+        Self {
+            ledger,
+            authority,
+            user,
+            program_data,
+            treasury_config: Pubkey::find_program_address(&[TREASURY_CONFIG_SEED], &ID).0,
+            source,
+            destination,
+        }
+    }
+
+    /// Sets up the treasury config paid for by `payer`, with `authority` as
+    /// its authority.
+    pub fn initialize_treasury_config(
+        &mut self,
+        payer: Pubkey,
+        treasury: Pubkey,
+        treasury_fee_bps: u16,
+    ) -> ProgramResult {
+        let ix = instruction(
+            &accounts::InitializeTreasuryConfig {
+                treasury_config: self.treasury_config,
+                program: ID,
+                program_data: self.program_data,
+                payer,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeTreasuryConfig {
+                authority: self.authority,
+                treasury,
+                treasury_fee_bps,
+            },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn update_treasury(&mut self, signer: Pubkey, new_treasury: Pubkey) -> ProgramResult {
+        let ix = instruction(
+            &accounts::UpdateTreasury {
+                treasury_config: self.treasury_config,
+                authority: signer,
+            },
+            instruction::UpdateTreasury { new_treasury },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn set_treasury_fee_bps(&mut self, signer: Pubkey, treasury_fee_bps: u16) -> ProgramResult {
+        let ix = instruction(
+            &accounts::UpdateTreasury {
+                treasury_config: self.treasury_config,
+                authority: signer,
+            },
+            instruction::SetTreasuryFeeBps { treasury_fee_bps },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn set_fee_shares(&mut self, signer: Pubkey, fee_shares: Vec<FeeShare>) -> ProgramResult {
+        let ix = instruction(
+            &accounts::UpdateTreasury {
+                treasury_config: self.treasury_config,
+                authority: signer,
+            },
+            instruction::SetFeeShares { fee_shares },
+            &[],
+        );
+        self.ledger.process(&ix)
+    }
+
+    /// A swap of `amount` whose route takes `amount` from the source and
+    /// delivers `out` to the destination, with `min_out == out`.
+    pub fn swap(&self, amount: u64, out: u64) -> Swap {
+        let route = [self.source, self.destination];
+        Swap {
+            params: ExecuteSwapParams {
+                amount,
+                min_out: out,
+                jupiter_accounts: route
+                    .iter()
+                    .map(|pubkey| SerializableAccountMeta {
+                        pubkey: *pubkey,
+                        is_signer: false,
+                        is_writable: true,
+                    })
+                    .collect(),
+                jupiter_data: route_data(amount, out),
+                deadline_unix_ts: None,
+            },
+            accounts: accounts::ExecuteSwap {
+                user: self.user,
+                user_destination: self.destination,
+                treasury_config: self.treasury_config,
+                treasury: TREASURY,
+                jupiter_program: JUPITER_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            remaining: route
+                .iter()
+                .map(|pubkey| AccountMeta::new(*pubkey, false))
+                .collect(),
+        }
+    }
+
+    pub fn execute(&mut self, swap: &Swap) -> ProgramResult {
+        let ix = instruction(
+            &swap.accounts,
+            instruction::ExecuteSwap {
+                params: swap.params.clone(),
+            },
+            &swap.remaining,
+        );
+        self.ledger.process(&ix)
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        self.ledger.token_amount(key)
+    }
+}
@@ -0,0 +1,14 @@
+//! Behavior tests for the single-leg program in `src/lib_v2_simple.rs`.
+//!
+//! That source is not part of the crate's build, so it is compiled here at the
+//! test crate root (anchor resolves `crate::ID`) and driven through the
+//! in-process ledger from `raceswap-harness` with a mocked Jupiter route.
+
+include!("../../src/lib_v2_simple.rs");
+
+mod fixture;
+
+mod fee_rate;
+mod fee_split;
+mod treasury_rotation;
+mod validation;
@@ -0,0 +1,76 @@
+use crate::fixture::{error, Fixture, TREASURY};
+use crate::RaceswapError;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+/// A funded, system-owned treasury wallet.
+fn new_treasury(fixture: &mut Fixture) -> Pubkey {
+    let treasury = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(treasury, Rent::default().minimum_balance(0));
+    treasury
+}
+
+/// Runs a 1_000_000 swap with `treasury` as the fee destination.
+fn fee_paid_to(fixture: &mut Fixture, treasury: Pubkey) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.treasury = treasury;
+    fixture.execute(&swap)
+}
+
+#[test]
+fn fees_follow_the_rotated_treasury() {
+    let mut fixture = Fixture::new();
+    let first = new_treasury(&mut fixture);
+    let authority = fixture.authority;
+    fixture
+        .initialize_treasury_config(authority, first, 20)
+        .unwrap();
+
+    let before = fixture.ledger.lamports(&first);
+    fee_paid_to(&mut fixture, first).unwrap();
+    assert_eq!(fixture.ledger.lamports(&first), before + 2_000);
+    assert_eq!(
+        fee_paid_to(&mut fixture, TREASURY),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+
+    let second = new_treasury(&mut fixture);
+    fixture.update_treasury(authority, second).unwrap();
+    let before = fixture.ledger.lamports(&second);
+    fee_paid_to(&mut fixture, second).unwrap();
+    assert_eq!(fixture.ledger.lamports(&second), before + 2_000);
+    assert_eq!(
+        fee_paid_to(&mut fixture, first),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+}
+
+#[test]
+fn only_the_upgrade_authority_sets_up_the_treasury() {
+    let mut fixture = Fixture::new();
+    let (user, treasury) = (fixture.user, new_treasury(&mut fixture));
+    assert_eq!(
+        fixture.initialize_treasury_config(user, treasury, 20),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.ledger.exists(&fixture.treasury_config));
+    fee_paid_to(&mut fixture, TREASURY).unwrap();
+}
+
+#[test]
+fn only_the_treasury_authority_can_rotate() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    fixture
+        .initialize_treasury_config(authority, TREASURY, 20)
+        .unwrap();
+
+    let (user, treasury) = (fixture.user, new_treasury(&mut fixture));
+    assert_eq!(
+        fixture.update_treasury(user, treasury),
+        error(RaceswapError::Unauthorized)
+    );
+    fee_paid_to(&mut fixture, TREASURY).unwrap();
+}
@@ -0,0 +1,59 @@
+use crate::fixture::{error, Fixture, TREASURY};
+use raceswap_common::validation::ValidationError;
+use raceswap_common::MAX_INSTRUCTION_DATA_LEN;
+
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn zero_amount_is_rejected() {
+    let mut fixture = Fixture::new();
+    let swap = fixture.swap(0, 5_000);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::InvalidAmount)
+    );
+}
+
+#[test]
+fn jupiter_data_past_the_cap_is_rejected() {
+    let mut fixture = Fixture::new();
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.jupiter_data = vec![0; MAX_INSTRUCTION_DATA_LEN + 1];
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::InstructionDataTooLarge)
+    );
+}
+
+#[test]
+fn stale_swap_is_rejected_before_paying_fees() {
+    let mut fixture = Fixture::new();
+    fixture.ledger.set_unix_timestamp(NOW);
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.deadline_unix_ts = Some(NOW - 1);
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::DeadlineExceeded)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+
+    swap.params.deadline_unix_ts = Some(NOW);
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn impossible_min_out_reverts_the_fee() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_out = 5_001;
+    assert_eq!(
+        fixture.execute(&swap),
+        error(ValidationError::BelowMinOut)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+}
@@ -0,0 +1,33 @@
+use crate::fixture::{error, Fixture};
+use raceswap::{RaceswapConfig, RaceswapError};
+
+#[test]
+fn upgrade_authority_creates_the_config() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(30).unwrap();
+    let config: RaceswapConfig = fixture.ledger.anchor_account(&fixture.config);
+    assert_eq!(config.authority, fixture.authority);
+    assert_eq!(config.treasury_fee_bps, 30);
+}
+
+#[test]
+fn other_payers_cannot_front_run_the_config() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    assert_eq!(
+        fixture.initialize_config_as(user, 0),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.ledger.exists(&fixture.config));
+    fixture.initialize_config(20).unwrap();
+}
+
+#[test]
+fn immutable_program_cannot_create_the_config() {
+    let mut fixture = Fixture::new();
+    fixture.ledger.set_upgrade_authority(raceswap::ID, None);
+    assert_eq!(
+        fixture.initialize_config(20),
+        error(RaceswapError::Unauthorized)
+    );
+}
@@ -0,0 +1,117 @@
+use crate::fixture::{error, Fixture, Swap, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use raceswap::{RaceswapError, UpdateConfigParams};
+use raceswap_common::{FeeShare, FeeSplitError};
+
+/// A funded partner wallet besides the treasury.
+fn partner(fixture: &mut Fixture) -> Pubkey {
+    let partner = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(partner, Rent::default().minimum_balance(0));
+    partner
+}
+
+fn set_split(fixture: &mut Fixture, signer: Pubkey, shares: &[(Pubkey, u16)]) -> ProgramResult {
+    fixture.update_config(
+        signer,
+        UpdateConfigParams {
+            fee_shares: Some(
+                shares
+                    .iter()
+                    .map(|&(recipient, weight)| FeeShare { recipient, weight })
+                    .collect(),
+            ),
+            ..Default::default()
+        },
+    )
+}
+
+/// A 1_000_000 swap (2_000 lamport fee) passing `recipients` ahead of the route.
+fn split_swap(fixture: &Fixture, recipients: &[Pubkey]) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    let mut remaining: Vec<AccountMeta> = recipients
+        .iter()
+        .map(|recipient| AccountMeta::new(*recipient, false))
+        .collect();
+    remaining.append(&mut swap.remaining);
+    swap.remaining = remaining;
+    swap
+}
+
+#[test]
+fn fee_is_split_by_the_configured_shares() {
+    let mut fixture = Fixture::new();
+    let partner = partner(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    let authority = fixture.authority;
+    set_split(&mut fixture, authority, &[(TREASURY, 7_000), (partner, 3_000)]).unwrap();
+    let before = [TREASURY, partner].map(|key| fixture.ledger.lamports(&key));
+
+    fixture.execute(&split_swap(&fixture, &[TREASURY, partner])).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), before[0] + 1_400);
+    assert_eq!(fixture.ledger.lamports(&partner), before[1] + 600);
+}
+
+#[test]
+fn caller_cannot_redirect_a_configured_share() {
+    let mut fixture = Fixture::new();
+    let partner = partner(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    let authority = fixture.authority;
+    set_split(&mut fixture, authority, &[(TREASURY, 7_000), (partner, 3_000)]).unwrap();
+
+    let user = fixture.user;
+    assert_eq!(
+        fixture.execute(&split_swap(&fixture, &[TREASURY, user])),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+    assert_eq!(
+        fixture.execute(&split_swap(&fixture, &[partner, TREASURY])),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+}
+
+#[test]
+fn caller_accounts_take_no_share_without_a_split() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(20).unwrap();
+    let user = fixture.user;
+    let before = [TREASURY, user].map(|key| fixture.ledger.lamports(&key));
+
+    // The leading wallet is passed through to the route, not paid
+    fixture.execute(&split_swap(&fixture, &[user])).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), before[0] + 2_000);
+    assert_eq!(fixture.ledger.lamports(&user), before[1] - 2_000);
+}
+
+#[test]
+fn split_must_give_the_treasury_a_share() {
+    let mut fixture = Fixture::new();
+    let partner = partner(&mut fixture);
+    fixture.initialize_config(20).unwrap();
+    let authority = fixture.authority;
+    assert_eq!(
+        set_split(&mut fixture, authority, &[(TREASURY, 0), (partner, 10_000)]),
+        error(FeeSplitError::InvalidFeeWeights)
+    );
+
+    // A split that leaves the treasury out entirely never pays anyone
+    set_split(&mut fixture, authority, &[(partner, 10_000)]).unwrap();
+    assert_eq!(
+        fixture.execute(&split_swap(&fixture, &[partner])),
+        error(FeeSplitError::FeeRecipientMismatch)
+    );
+}
+
+#[test]
+fn only_the_config_authority_sets_the_split() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(20).unwrap();
+    let user = fixture.user;
+    assert_eq!(
+        set_split(&mut fixture, user, &[(TREASURY, 5_000), (user, 5_000)]),
+        error(RaceswapError::Unauthorized)
+    );
+}
@@ -0,0 +1,70 @@
+use crate::fixture::{error, Fixture, Swap, SOURCE_BALANCE, TREASURY};
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+use raceswap::RaceswapError;
+use raceswap_common::FeeMode;
+use raceswap_harness::route_data;
+
+/// A 1_000_000 swap paying its 2_000 fee in the input token to
+/// `treasury_token_account`, with the route built for the remaining 998_000.
+fn input_token_swap(fixture: &Fixture, treasury_token_account: Pubkey) -> Swap {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.fee_mode = FeeMode::InputToken;
+    swap.params.jupiter_data = route_data(998_000, 5_000);
+    swap.accounts.user_input = Some(fixture.source);
+    swap.accounts.input_mint = Some(fixture.input_mint);
+    swap.accounts.treasury_token_account = Some(treasury_token_account);
+    swap.accounts.token_program = Some(TOKEN_PROGRAM_ID);
+    swap
+}
+
+fn treasury_token_account(fixture: &mut Fixture, owner: Pubkey) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let input_mint = fixture.input_mint;
+    fixture.ledger.add_token_account(key, input_mint, owner, 0);
+    key
+}
+
+#[test]
+fn fee_is_taken_in_the_input_token() {
+    let mut fixture = Fixture::new();
+    let treasury_ata = treasury_token_account(&mut fixture, TREASURY);
+    let treasury_lamports = fixture.ledger.lamports(&TREASURY);
+
+    fixture
+        .execute(&input_token_swap(&fixture, treasury_ata))
+        .unwrap();
+    assert_eq!(fixture.token_amount(&treasury_ata), 2_000);
+    assert_eq!(
+        fixture.token_amount(&fixture.source),
+        SOURCE_BALANCE - 1_000_000
+    );
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_lamports);
+}
+
+#[test]
+fn missing_fee_token_accounts_are_rejected() {
+    let mut fixture = Fixture::new();
+    let treasury_ata = treasury_token_account(&mut fixture, TREASURY);
+    let mut swap = input_token_swap(&fixture, treasury_ata);
+    swap.accounts.user_input = None;
+
+    assert_eq!(
+        fixture.execute(&swap),
+        error(RaceswapError::MissingFeeTokenAccounts)
+    );
+}
+
+#[test]
+fn fee_account_not_owned_by_the_treasury_is_rejected() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    let other = treasury_token_account(&mut fixture, user);
+
+    assert_eq!(
+        fixture.execute(&input_token_swap(&fixture, other)),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+    assert_eq!(fixture.token_amount(&fixture.source), SOURCE_BALANCE);
+}
@@ -0,0 +1,66 @@
+use crate::fixture::{error, Fixture};
+use anchor_lang::prelude::Pubkey;
+use raceswap::{RaceswapError, UpdateConfigParams};
+use raceswap_common::validation::ValidationError;
+use raceswap_harness::mock_route;
+use std::rc::Rc;
+
+/// Initializes the config and points it at a freshly deployed route program.
+fn rotate(fixture: &mut Fixture) -> Pubkey {
+    let next = Pubkey::new_unique();
+    fixture.ledger.add_program(next, Rc::new(mock_route));
+    fixture.initialize_config(20).unwrap();
+    let authority = fixture.authority;
+    fixture
+        .update_config(
+            authority,
+            UpdateConfigParams {
+                jupiter_program: Some(next),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    next
+}
+
+#[test]
+fn v6_is_accepted_without_a_config() {
+    let mut fixture = Fixture::new();
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+}
+
+#[test]
+fn swaps_follow_the_rotated_program() {
+    let mut fixture = Fixture::new();
+    let next = rotate(&mut fixture);
+
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.jupiter_program = next;
+    fixture.execute(&swap).unwrap();
+    assert_eq!(fixture.token_amount(&fixture.destination), 5_000);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 5_000)),
+        error(ValidationError::InvalidJupiterProgram)
+    );
+}
+
+#[test]
+fn only_the_authority_can_rotate_the_program() {
+    let mut fixture = Fixture::new();
+    fixture.initialize_config(20).unwrap();
+
+    let user = fixture.user;
+    assert_eq!(
+        fixture.update_config(
+            user,
+            UpdateConfigParams {
+                jupiter_program: Some(Pubkey::new_unique()),
+                ..Default::default()
+            },
+        ),
+        error(RaceswapError::Unauthorized)
+    );
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+}
@@ -0,0 +1,18 @@
+//! Behavior tests for the deployed `raceswap` program (`src/lib.rs`), driven
+//! through the in-process ledger from `raceswap-harness` with a mocked
+//! Jupiter route.
+
+mod fixture;
+
+mod config_init;
+mod deadline;
+mod dry_run;
+mod fee_rate;
+mod fee_split;
+mod input_token_fee;
+mod jupiter_program;
+mod min_out;
+mod referrer;
+mod status;
+mod treasury_account;
+mod treasury_rotation;
@@ -0,0 +1,45 @@
+use crate::fixture::{error, Fixture, SOURCE_BALANCE, TREASURY};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorDeserialize;
+use raceswap_common::validation::ValidationError;
+use raceswap_common::SwapStatus;
+
+#[test]
+fn filled_amount_is_the_destination_delta() {
+    let mut fixture = Fixture::new();
+    let (destination, mint, user) = (fixture.destination, fixture.output_mint, fixture.user);
+    fixture
+        .ledger
+        .add_token_account(destination, mint, user, 1_000);
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+
+    assert_eq!(fixture.token_amount(&destination), 6_000);
+    let (_, data) = fixture.ledger.return_data().unwrap();
+    assert_eq!(SwapStatus::try_from_slice(&data).unwrap().main_out, 5_000);
+}
+
+#[test]
+fn fill_below_min_out_reverts() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.min_out = 5_001;
+
+    assert_eq!(fixture.execute(&swap), error(ValidationError::BelowMinOut));
+    assert_eq!(fixture.token_amount(&fixture.source), SOURCE_BALANCE);
+    assert_eq!(fixture.token_amount(&fixture.destination), 0);
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+}
+
+#[test]
+fn route_delivering_elsewhere_reverts() {
+    let mut fixture = Fixture::new();
+    let elsewhere = Pubkey::new_unique();
+    let (mint, user) = (fixture.output_mint, fixture.user);
+    fixture.ledger.add_token_account(elsewhere, mint, user, 0);
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.params.jupiter_accounts[1].pubkey = elsewhere;
+    swap.remaining[1].pubkey = elsewhere;
+
+    assert_eq!(fixture.execute(&swap), error(ValidationError::BelowMinOut));
+}
@@ -0,0 +1,43 @@
+use crate::fixture::{error, Fixture, TREASURY, USER_LAMPORTS};
+use anchor_lang::system_program;
+use raceswap_common::validation::ValidationError;
+use raceswap_common::FeeSplitError;
+
+#[test]
+fn fee_to_a_system_owned_treasury_is_accepted() {
+    let mut fixture = Fixture::new();
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+    fixture.execute(&fixture.swap(1_000_000, 5_000)).unwrap();
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before + 2_000);
+}
+
+#[test]
+fn fee_to_a_token_account_treasury_is_rejected() {
+    let mut fixture = Fixture::new();
+    let input_mint = fixture.input_mint;
+    fixture
+        .ledger
+        .add_token_account(TREASURY, input_mint, TREASURY, 0);
+    let treasury_before = fixture.ledger.lamports(&TREASURY);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 5_000)),
+        error(ValidationError::InvalidTreasuryAccount)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), treasury_before);
+    assert_eq!(fixture.ledger.lamports(&fixture.user), USER_LAMPORTS);
+}
+
+#[test]
+fn fee_leaving_the_treasury_below_rent_exemption_is_rejected() {
+    let mut fixture = Fixture::new();
+    fixture
+        .ledger
+        .set_account(TREASURY, 0, Vec::new(), system_program::ID);
+
+    assert_eq!(
+        fixture.execute(&fixture.swap(1_000_000, 5_000)),
+        error(FeeSplitError::TreasuryNotRentExempt)
+    );
+    assert_eq!(fixture.ledger.lamports(&TREASURY), 0);
+}
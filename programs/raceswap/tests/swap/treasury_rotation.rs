@@ -0,0 +1,129 @@
+use crate::fixture::{error, instruction, Fixture, TREASURY};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::system_program;
+use raceswap::{accounts, RaceswapError, UpdateConfigParams};
+
+fn initialize_treasury_config(
+    fixture: &mut Fixture,
+    payer: Pubkey,
+    treasury: Pubkey,
+) -> ProgramResult {
+    let ix = instruction(
+        &accounts::InitializeTreasuryConfig {
+            treasury_config: fixture.treasury_config,
+            program: raceswap::ID,
+            program_data: fixture.program_data,
+            payer,
+            system_program: system_program::ID,
+        },
+        raceswap::instruction::InitializeTreasuryConfig {
+            authority: fixture.authority,
+            treasury,
+        },
+        &[],
+    );
+    fixture.ledger.process(&ix)
+}
+
+fn update_treasury(fixture: &mut Fixture, signer: Pubkey, new_treasury: Pubkey) -> ProgramResult {
+    let ix = instruction(
+        &accounts::UpdateTreasury {
+            treasury_config: fixture.treasury_config,
+            authority: signer,
+        },
+        raceswap::instruction::UpdateTreasury { new_treasury },
+        &[],
+    );
+    fixture.ledger.process(&ix)
+}
+
+/// A funded, system-owned treasury wallet.
+fn new_treasury(fixture: &mut Fixture) -> Pubkey {
+    let treasury = Pubkey::new_unique();
+    fixture
+        .ledger
+        .fund(treasury, Rent::default().minimum_balance(0));
+    treasury
+}
+
+/// Runs a 1_000_000 swap with `treasury` as the fee destination.
+fn fee_paid_to(fixture: &mut Fixture, treasury: Pubkey) -> ProgramResult {
+    let mut swap = fixture.swap(1_000_000, 5_000);
+    swap.accounts.treasury = treasury;
+    fixture.execute(&swap)
+}
+
+#[test]
+fn fees_follow_the_rotated_treasury() {
+    let mut fixture = Fixture::new();
+    let first = new_treasury(&mut fixture);
+    let authority = fixture.authority;
+    initialize_treasury_config(&mut fixture, authority, first).unwrap();
+
+    let before = fixture.ledger.lamports(&first);
+    fee_paid_to(&mut fixture, first).unwrap();
+    assert_eq!(fixture.ledger.lamports(&first), before + 2_000);
+    assert_eq!(
+        fee_paid_to(&mut fixture, TREASURY),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+
+    let second = new_treasury(&mut fixture);
+    update_treasury(&mut fixture, authority, second).unwrap();
+    let before = fixture.ledger.lamports(&second);
+    fee_paid_to(&mut fixture, second).unwrap();
+    assert_eq!(fixture.ledger.lamports(&second), before + 2_000);
+    assert_eq!(
+        fee_paid_to(&mut fixture, first),
+        error(RaceswapError::InvalidTreasuryAccount)
+    );
+}
+
+#[test]
+fn only_the_upgrade_authority_sets_up_the_treasury() {
+    let mut fixture = Fixture::new();
+    let (user, treasury) = (fixture.user, new_treasury(&mut fixture));
+    assert_eq!(
+        initialize_treasury_config(&mut fixture, user, treasury),
+        error(RaceswapError::Unauthorized)
+    );
+    assert!(!fixture.ledger.exists(&fixture.treasury_config));
+    fee_paid_to(&mut fixture, TREASURY).unwrap();
+}
+
+#[test]
+fn config_authority_alone_cannot_set_up_the_treasury() {
+    let mut fixture = Fixture::new();
+    let user = fixture.user;
+    fixture.initialize_config(20).unwrap();
+    fixture
+        .update_config(
+            fixture.authority,
+            UpdateConfigParams {
+                new_authority: Some(user),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let treasury = new_treasury(&mut fixture);
+    assert_eq!(
+        initialize_treasury_config(&mut fixture, user, treasury),
+        error(RaceswapError::Unauthorized)
+    );
+}
+
+#[test]
+fn only_the_treasury_authority_can_rotate() {
+    let mut fixture = Fixture::new();
+    let authority = fixture.authority;
+    initialize_treasury_config(&mut fixture, authority, TREASURY).unwrap();
+
+    let (user, treasury) = (fixture.user, new_treasury(&mut fixture));
+    assert_eq!(
+        update_treasury(&mut fixture, user, treasury),
+        error(RaceswapError::Unauthorized)
+    );
+    fee_paid_to(&mut fixture, TREASURY).unwrap();
+}